@@ -0,0 +1,54 @@
+//! Per-frame cost of drawing the log view over a large buffer
+//!
+//! Run with `cargo bench --bench render_logs`.
+
+use std::{
+    hint::black_box,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use ratatui::{Terminal, backend::TestBackend};
+use uberlog_lib::{
+    LogMessage, LogTimestamp,
+    tui::{LayoutSection, section_logs::SectionLogs, theme::Theme},
+};
+
+const LOG_COUNT: usize = 100_000;
+const FRAMES: u32 = 200;
+
+fn main() {
+    let (command_tx, _command_rx) = mpsc::channel();
+    let mut section = SectionLogs::new(command_tx, Theme::default(), true, None, 0);
+    for i in 0..LOG_COUNT {
+        section.append_log(LogMessage {
+            timestamp: LogTimestamp::now(),
+            source_id: 0,
+            message: format!("[INFO] sensor {} reading {} ok", i % 8, i),
+            style: LogMessage::default_style(),
+            highlighted: false,
+            focused: false,
+            raw: None,
+        });
+    }
+
+    let mut terminal = Terminal::new(TestBackend::new(200, 50)).unwrap();
+
+    // Warm up
+    terminal.draw(|f| section.ui(f, f.area())).unwrap();
+
+    let mut total = Duration::ZERO;
+    for _ in 0..FRAMES {
+        let start = Instant::now();
+        terminal.draw(|f| section.ui(f, f.area())).unwrap();
+        total += start.elapsed();
+        black_box(terminal.backend());
+    }
+
+    println!(
+        "render_logs: {} lines, {:?} per frame ({} frames)",
+        LOG_COUNT,
+        total / FRAMES,
+        FRAMES
+    );
+}
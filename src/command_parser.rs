@@ -1,6 +1,6 @@
 use std::sync::mpsc::Sender;
 
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 use crate::{commander::Command, configuration::Alias};
 
@@ -109,7 +109,7 @@ impl CommandParser {
                 match (registered_inst.operation)(&self.command_tx, args.to_vec()) {
                     Ok(()) => (),
                     Err(e) => {
-                        let _ = self.command_tx.send(Command::PrintMessage(e));
+                        let _ = self.command_tx.send(Command::PrintError(e));
                     }
                 }
                 break;
@@ -124,8 +124,36 @@ impl CommandParser {
         self.state = State::Idle;
     }
 
+    /// Remove the word before the cursor, keeping the prompt character
+    fn delete_word(&mut self) {
+        let prompt_len = self.parsed_command.chars().next().map_or(0, |c| c.len_utf8());
+        let content = &self.parsed_command[prompt_len..];
+        let trimmed = content.trim_end();
+        let word_start = trimmed
+            .rfind(char::is_whitespace)
+            .map_or(0, |idx| idx + 1);
+        self.parsed_command.truncate(prompt_len + word_start);
+    }
+
+    /// Remove everything before the cursor, keeping the prompt character
+    fn delete_line(&mut self) {
+        let prompt_len = self.parsed_command.chars().next().map_or(0, |c| c.len_utf8());
+        self.parsed_command.truncate(prompt_len);
+    }
+
     /// Process keypresses received
-    pub fn process_key(&mut self, key: KeyCode) {
+    pub fn process_key(&mut self, key: KeyEvent) {
+        // Edition shortcuts
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            match key.code {
+                KeyCode::Char('w') => self.delete_word(),
+                KeyCode::Char('u') => self.delete_line(),
+                _ => (),
+            }
+            return;
+        }
+
+        let key = key.code;
         match key {
             // First time means start parsing command, subsequent times
             // mean it is just another character
@@ -152,9 +180,13 @@ impl CommandParser {
             // Cancel
             KeyCode::Esc => self.cancel_parsing(),
 
-            // Remove last character
+            // Remove last character, removing the prompt character means cancel
             KeyCode::Backspace => {
-                let _ = self.parsed_command.pop();
+                if self.parsed_command.len() <= 1 {
+                    self.cancel_parsing();
+                } else {
+                    let _ = self.parsed_command.pop();
+                }
             }
             _ => (),
         }
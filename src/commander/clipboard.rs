@@ -0,0 +1,36 @@
+use arboard::Clipboard;
+use tracing::{error, info};
+
+use super::{Commander, UiCommand};
+
+impl Commander {
+    /// Copy `text` to the system clipboard
+    ///
+    /// The clipboard is kept open for the rest of the session: on X11 its contents are served
+    /// by whoever set them, and would be lost with it. Without a graphical session (i.e. over
+    /// SSH) there is no clipboard, the user is told why.
+    pub(crate) fn copy_to_clipboard(&mut self, text: String) -> Result<(), String> {
+        let result = match self.clipboard.as_mut() {
+            Some(clipboard) => clipboard.set_text(text.as_str()),
+            None => Clipboard::new()
+                .and_then(|clipboard| self.clipboard.insert(clipboard).set_text(text.as_str())),
+        };
+
+        let response = match result {
+            Ok(()) => {
+                info!("Copied {} bytes to the clipboard", text.len());
+                UiCommand::TextMessage {
+                    message: format!("Copied {} characters to the clipboard", text.chars().count()),
+                }
+            }
+            Err(e) => {
+                error!("Unable to copy to the clipboard: {}", e);
+                UiCommand::ErrorMessage {
+                    message: format!("No clipboard available: {}", e),
+                }
+            }
+        };
+        let _ = self.command_response_tx.send(response);
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use defmt_decoder::{DecodeError, Encoding, Table};
+use tracing::{debug, warn};
+
+use crate::{LogTimestamp, log_source::LogSourceTrait};
+
+use super::Commander;
+
+/// Shown in place of the frames that could not be decoded
+pub const MALFORMED_FRAME_MARKER: &str = "[malformed defmt frame]";
+
+/// Decode the complete frames of `pending`, the bytes of an incomplete frame are left in it
+///
+/// Returns the text of every frame, in order. The frames that can not be decoded are shown as
+/// `MALFORMED_FRAME_MARKER`.
+pub fn decode_defmt_frames(table: &Table, pending: &mut Vec<u8>) -> Vec<String> {
+    let mut lines = Vec::new();
+    match table.encoding() {
+        // Every frame ends with a zero, the bytes after the last one are an incomplete frame
+        Encoding::Rzcobs => {
+            let end = pending.iter().rposition(|&b| b == 0).map_or(0, |pos| pos + 1);
+            let mut decoder = table.new_stream_decoder();
+            decoder.received(&pending[..end]);
+            loop {
+                match decoder.decode() {
+                    Ok(frame) => lines.push(frame.display(false).to_string()),
+                    Err(DecodeError::UnexpectedEof) => break,
+                    // The decoder skips it, the next frame can still be decoded
+                    Err(DecodeError::Malformed) => lines.push(String::from(MALFORMED_FRAME_MARKER)),
+                }
+            }
+            pending.drain(..end);
+        }
+        // Frames are not delimited, a frame can only be told from the previous one
+        _ => {
+            let mut consumed = 0;
+            loop {
+                match table.decode(&pending[consumed..]) {
+                    Ok((frame, count)) => {
+                        lines.push(frame.display(false).to_string());
+                        consumed += count;
+                    }
+                    Err(DecodeError::UnexpectedEof) => break,
+                    // Nothing tells where the next frame starts, drop what is left
+                    Err(DecodeError::Malformed) => {
+                        lines.push(String::from(MALFORMED_FRAME_MARKER));
+                        consumed = pending.len();
+                        break;
+                    }
+                }
+            }
+            pending.drain(..consumed);
+        }
+    }
+    lines
+}
+
+impl Commander {
+    /// For defmt RTT targets, parse the elf file and get the table of the format strings
+    pub fn defmt_table_from_elf(path: &String) -> Result<Table, String> {
+        let file_data =
+            std::fs::read(path).map_err(|e| format!("Unable to read <{}>: {}", path, e))?;
+
+        Table::parse(&file_data)
+            .map_err(|e| format!("Unable to read the defmt table of <{}>: {}", path, e))?
+            .ok_or(format!("<{}> has no defmt table, is the firmware using defmt?", path))
+    }
+
+    /// Decode the defmt frames received by a source
+    ///
+    /// Frames split across reads are completed with the next bytes, the ones missing their
+    /// end wait in the source storage meanwhile.
+    pub(crate) fn parse_defmt_bytes(&mut self, id: u32, idx: usize, bytes: Vec<u8>) -> Result<(), String> {
+        let Some(table) = self.defmt_tables.get(&id) else {
+            return Err(format!("Source {} has no defmt table", id));
+        };
+
+        let mut pending = self.log_sources[idx].take_storage().unwrap_or_default();
+        pending.extend(bytes);
+
+        let mut lines = decode_defmt_frames(table, &mut pending);
+
+        // Do not let a stream that never completes a frame grow the storage forever
+        let max_line_length = self.app_cfg.max_line_length;
+        if max_line_length > 0 && pending.len() > max_line_length {
+            warn!("Source {} exceeded {} bytes without a complete defmt frame", id, max_line_length);
+            pending.clear();
+            lines.push(String::from(MALFORMED_FRAME_MARKER));
+        }
+        debug!("Decoded {} defmt frames, {} bytes pending", lines.len(), pending.len());
+        self.log_sources[idx].set_storage(pending);
+
+        let ts = LogTimestamp::now();
+        for line in lines {
+            // A message may span several lines, every one of them is a log
+            for line in line.lines() {
+                self.push_log_line(ts, id, format!("{}\n", line), None);
+            }
+        }
+        Ok(())
+    }
+}
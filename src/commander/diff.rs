@@ -0,0 +1,71 @@
+use std::time::{Duration, Instant};
+
+use similar::{Algorithm, DiffTag, capture_diff_slices_deadline};
+
+/// How a row of a diff relates both files
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiffKind {
+    Same,
+    /// Only in the first file
+    Removed,
+    /// Only in the second file
+    Added,
+    /// Replaced by a different line
+    Changed,
+}
+
+/// Row of a side by side diff, with the line (number and text) of each file it shows
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffRow {
+    pub kind: DiffKind,
+    pub left: Option<(usize, String)>,
+    pub right: Option<(usize, String)>,
+}
+
+/// Time given to find the smallest diff, past it the differences left are not looked into as
+/// closely and may show as bigger than they are
+const DIFF_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Line level diff of `a` and `b`, as rows to show side by side
+///
+/// Lines removed and added next to each other are paired into changed rows. Returns whether
+/// the diff is exact, it is not when the files differ too much to find the smallest diff in
+/// time.
+pub fn diff_lines(a: &[String], b: &[String]) -> (Vec<DiffRow>, bool) {
+    diff_lines_with_timeout(a, b, DIFF_TIMEOUT)
+}
+
+/// Same as `diff_lines`, giving up on the smallest diff after `timeout`
+pub fn diff_lines_with_timeout(a: &[String], b: &[String], timeout: Duration) -> (Vec<DiffRow>, bool) {
+    let deadline = Instant::now() + timeout;
+    let ops = capture_diff_slices_deadline(Algorithm::Myers, a, b, Some(deadline));
+    let exact = Instant::now() < deadline;
+
+    let line = |lines: &[String], idx: usize| Some((idx + 1, lines[idx].clone()));
+    let mut rows = Vec::new();
+    for op in ops {
+        let (tag, old, new) = op.as_tag_tuple();
+        match tag {
+            DiffTag::Equal => rows.extend(old.zip(new).map(|(i, j)| DiffRow {
+                kind: DiffKind::Same,
+                left: line(a, i),
+                right: line(b, j),
+            })),
+            // Pair what was removed with what was added
+            DiffTag::Delete | DiffTag::Insert | DiffTag::Replace => {
+                for k in 0..old.len().max(new.len()) {
+                    let left = old.clone().nth(k).and_then(|i| line(a, i));
+                    let right = new.clone().nth(k).and_then(|j| line(b, j));
+                    let kind = match (&left, &right) {
+                        (Some(_), Some(_)) => DiffKind::Changed,
+                        (Some(_), None) => DiffKind::Removed,
+                        _ => DiffKind::Added,
+                    };
+                    rows.push(DiffRow { kind, left, right });
+                }
+            }
+        }
+    }
+
+    (rows, exact)
+}
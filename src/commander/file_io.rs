@@ -2,10 +2,13 @@ use std::io::Write;
 
 use tracing::error;
 
-use crate::log_source::{FileSource, LogSource, LogSourceTrait, StdinSource};
+use crate::{
+    APP_SOURCE_ID, LogFilterScope, LogMessage, LogTimestamp, NOTE_SOURCE_ID, STATUS_SOURCE_ID,
+    log_source::{AdbSource, FileSource, LogSource, LogSourceTrait, StdinSource, UdpSource},
+};
 
 pub use super::Commander;
-use super::UiCommand;
+use super::{INCOMPLETE_LINE_MARKER, UiCommand, collapse_carriage_return, diff_lines};
 
 impl Commander {
 
@@ -33,13 +36,74 @@ impl Commander {
         Ok(())
     }
 
+    /// Stream the `adb logcat` output of a device, the one in the configuration if no serial
+    /// is given
+    pub(crate) fn cmd_stream_adb(&mut self, serial: Option<String>) -> Result<(), String> {
+        // Get new source ID
+        let id = self.get_new_source_id();
+
+        // Create and connect it
+        let serial = serial.or(self.app_cfg.adb_serial.clone());
+        let mut new_source = AdbSource::new(id, self.app_cfg.adb_path.clone(), serial, self.command_tx.clone());
+        new_source.connect();
+
+        // Store it
+        self.log_sources.push(LogSource::AdbSource(new_source));
+
+        // Let UI know of the change
+        let _ = self.command_response_tx.send(UiCommand::AddNewSource(
+            id,
+            self.log_sources.last().unwrap().id_string(),
+        ));
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::SetConnectionSource(id, true));
+
+        Ok(())
+    }
+
+    /// Stream the datagrams received on a UDP port, every datagram being one or more lines
+    pub(crate) fn cmd_stream_udp(&mut self, port: u16) -> Result<(), String> {
+        // Get new source ID
+        let id = self.get_new_source_id();
+
+        // Create and connect it, binding the port can fail
+        let mut new_source = UdpSource::new(id, port, self.app_cfg.line_delimiter_byte(), self.command_tx.clone());
+        new_source.connect();
+        let connected = new_source.is_connected();
+
+        // Store it
+        self.log_sources.push(LogSource::UdpSource(new_source));
+
+        // Let UI know of the change
+        let _ = self.command_response_tx.send(UiCommand::AddNewSource(
+            id,
+            self.log_sources.last().unwrap().id_string(),
+        ));
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::SetConnectionSource(id, connected));
+
+        Ok(())
+    }
+
     /// Stream file
-    pub(crate) fn cmd_stream_file(&mut self, path: String) -> Result<(), String> {
+    ///
+    /// Unless `follow` is set, the source completes once the whole file is read. With `tail`
+    /// only the data appended from now on is read
+    pub(crate) fn cmd_stream_file(&mut self, path: String, follow: bool, tail: bool) -> Result<(), String> {
         // Get new source ID
         let id = self.get_new_source_id();
 
         // Create and connect it
-        let mut new_source = FileSource::new(id, path.clone(), self.command_tx.clone());
+        let mut new_source = FileSource::new(
+            id,
+            path.clone(),
+            follow,
+            tail,
+            self.app_cfg.line_delimiter_byte(),
+            self.command_tx.clone(),
+        );
         new_source.connect();
 
         // Store it
@@ -57,44 +121,355 @@ impl Commander {
         Ok(())
     }
 
-    /// Configure output log streaming
+    /// Stream a named pipe, for tools writing their logs to one
     ///
-    /// Receive a status update and a path where to stream
-    pub(crate) fn cmd_log_stream(&mut self, streaming: bool, path: String) -> Result<(), String> {
-        if streaming {
-            // Make sure we were not streaming already
-            if self.stream_logs {
-                error!("Already streaming!");
-                return Err("Already streaming".to_string());
+    /// The pipe is created if it does not exist yet, so the writer can be started afterwards.
+    /// It is then read as any other file, which already waits for writers to come and go.
+    pub(crate) fn cmd_stream_fifo(&mut self, path: String) -> Result<(), String> {
+        if let Err(e) = create_fifo(&path) {
+            let _ = self.command_response_tx.send(UiCommand::ErrorMessage { message: e });
+            return Ok(());
+        }
+        self.cmd_stream_file(path, true, false)
+    }
+
+    /// Start streaming the logs into `path`
+    ///
+    /// The logs received so far are written first. Unless `raw` is set, the filters applying to
+    /// exported logs are used.
+    pub(crate) fn cmd_stream_start(&mut self, path: String, raw: bool) -> Result<(), String> {
+        // Make sure we were not streaming into that file already
+        if self.output_streams.iter().any(|s| s.path == path) {
+            let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                message: format!("Already streaming into <{}>", path),
+            });
+            return Ok(());
+        }
+
+        // Otherwise open file
+        let mut file = match std::fs::File::create(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                error!("Unable to create {}: {}", path, e);
+                let _ = self.command_response_tx.send(UiCommand::ErrorMessage {
+                    message: format!("Unable to create <{}>: {}", path, e),
+                });
+                return Ok(());
             }
+        };
+        if self.app_cfg.stream_header {
+            let _ = file.write_all(self.stream_header().as_bytes());
+        }
+        for log in &self.log_messages {
+            let log = match raw {
+                true => Some(log.clone()),
+                false => self.apply_filters(log.timestamp, log.source_id, log.message.clone(), LogFilterScope::Export),
+            };
+            if let Some(log) = log {
+                let _ = file.write_all(log.message.as_bytes());
+            }
+        }
+        self.output_streams.push(OutputStream { path: path.clone(), file, raw });
 
-            // Otherwise open file
-            if let Ok(mut p) = std::fs::File::create(&path) {
-                for log in &self.log_messages {
-                    let _ = p.write_all(log.message.as_bytes());
+        let _ = self.command_response_tx.send(UiCommand::TextMessage {
+            message: format!("Saved/streaming data into <{}>", path),
+        });
+
+        Ok(())
+    }
+
+    /// Header describing the session, written at the top of streamed files
+    ///
+    /// Lists the start time and the connected sources, along with their MCU and backend when
+    /// they come from `.gadget.yaml`.
+    fn stream_header(&self) -> String {
+        let zone = LogTimestamp::zone();
+        let mut header = format!(
+            "# uberlog capture started at {}\n# timestamps timezone: {}\n",
+            zone.now_rfc3339(),
+            zone.description()
+        );
+
+        for source in self.log_sources.iter().filter(|s| s.is_connected()) {
+            let name = source.id_string();
+            let target = self
+                .target_cfg
+                .as_ref()
+                .and_then(|cfg| cfg.targets.iter().find(|t| t.name == name));
+            let line = match target {
+                Some(target) => {
+                    let backend = target.backend(self.target_backends.get(&name).copied().unwrap_or(0)).description();
+                    format!("# source: {} - mcu: {} - backend: {}\n", name, target.processor, backend)
                 }
-                self.stream_logs_file_handle = Some(p);
-                self.stream_logs = true;
-                let _ = self.command_response_tx.send(UiCommand::TextMessage {
-                    message: format!("Saved/streaming data into <{}>", path),
+                None => format!("# source: {}\n", name),
+            };
+            header.push_str(&line);
+        }
+
+        header
+    }
+
+    /// Write the logs stored so far into `path`, in `format`
+    ///
+    /// Unlike streaming, it is a snapshot: logs arriving later are not added. The filters
+    /// applying to exported logs are used, unless `view` is set: then the logs written are the
+    /// ones shown in the log view, with its filters and without the logs `:since_boot` hides.
+    pub(crate) fn cmd_export(&mut self, path: String, format: ExportFormat, view: bool) -> Result<(), String> {
+        let mut file = match std::fs::File::create(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                error!("Unable to create {}: {}", path, e);
+                let _ = self.command_response_tx.send(UiCommand::ErrorMessage {
+                    message: format!("Unable to create <{}>: {}", path, e),
                 });
+                return Ok(());
             }
-        } else {
-            // Make sure we were not -not- streaming already
-            if !self.stream_logs {
-                error!("Nothing to do, really");
-                return Err("Nothing to do, really".to_string());
+        };
+
+        let (logs, scope) = match view {
+            true => (self.floored_logs(), LogFilterScope::View),
+            false => (self.log_messages.iter(), LogFilterScope::Export),
+        };
+
+        let mut count = 0;
+        let mut result = Ok(());
+        for log in logs {
+            let Some(log) = self.apply_filters(log.timestamp, log.source_id, log.message.clone(), scope) else {
+                continue;
+            };
+            result = file.write_all(export_line(&log, &self.source_name(log.source_id), format).as_bytes());
+            if result.is_err() {
+                break;
             }
+            count += 1;
+        }
 
-            // Otherwise close file
-            self.stream_logs_file_handle = None;
-            self.stream_logs = false;
+        let response = match result {
+            Ok(_) => UiCommand::TextMessage {
+                message: format!(
+                    "Exported {} {}logs into <{}>",
+                    count,
+                    if view { "visible " } else { "" },
+                    path
+                ),
+            },
+            Err(e) => UiCommand::ErrorMessage {
+                message: format!("Unable to write <{}>: {}", path, e),
+            },
+        };
+        let _ = self.command_response_tx.send(response);
 
-            let _ = self.command_response_tx.send(UiCommand::TextMessage {
-                message: "Streaming stopped".to_string(),
-            });
+        Ok(())
+    }
+
+    /// Compare two captures line by line, the UI shows them side by side
+    ///
+    /// Reading and comparing big captures takes a while, it is done in its own thread so the
+    /// logs keep flowing meanwhile.
+    pub(crate) fn cmd_diff(&mut self, path_a: String, path_b: String) -> Result<(), String> {
+        let command_response_tx = self.command_response_tx.clone();
+        std::thread::spawn(move || {
+            let mut files = Vec::new();
+            for path in [&path_a, &path_b] {
+                match std::fs::read(path) {
+                    Ok(bytes) => files.push(
+                        String::from_utf8_lossy(&bytes)
+                            .lines()
+                            .map(String::from)
+                            .collect::<Vec<String>>(),
+                    ),
+                    Err(e) => {
+                        error!("Unable to read {}: {}", path, e);
+                        let _ = command_response_tx.send(UiCommand::ErrorMessage {
+                            message: format!("Unable to read <{}>: {}", path, e),
+                        });
+                        return;
+                    }
+                }
+            }
+
+            let (rows, exact) = diff_lines(&files[0], &files[1]);
+            let _ = command_response_tx.send(UiCommand::ShowDiff(path_a, path_b, rows, exact));
+        });
+        Ok(())
+    }
+
+    /// Name of a source, also once it is gone
+    fn source_name(&self, id: i32) -> String {
+        match id {
+            NOTE_SOURCE_ID => String::from("note"),
+            APP_SOURCE_ID => String::from("uberlog"),
+            STATUS_SOURCE_ID => String::from("status"),
+            _ => self
+                .get_source_idx(id as u32)
+                .map(|idx| self.log_sources[idx].id_string())
+                .or_else(|| self.removed_source_names.get(&(id as u32)).cloned())
+                .unwrap_or(format!("source_id_{}", id)),
         }
+    }
+
+    /// Bytes every source received without their terminating `\n` yet, as incomplete lines
+    ///
+    /// They are left in the sources, the rest of the line can still arrive.
+    fn partial_lines(&mut self) -> Vec<(u32, String)> {
+        let mut lines = Vec::new();
+        for source in &mut self.log_sources {
+            // Part of a defmt frame is not text
+            if self.defmt_tables.contains_key(&source.id()) {
+                continue;
+            }
+            let Some(bytes) = source.take_storage() else {
+                continue;
+            };
+            if !bytes.is_empty() {
+                let mut line = String::from_utf8_lossy(&bytes).to_string();
+                if self.app_cfg.collapse_carriage_return {
+                    line = collapse_carriage_return(&line);
+                }
+                lines.push((
+                    source.id(),
+                    format!("{}{}\n", line.trim_end_matches('\r'), INCOMPLETE_LINE_MARKER),
+                ));
+            }
+            source.set_storage(bytes);
+        }
+        lines
+    }
+
+    /// Stop streaming the logs into `path`, or into every file if no path is given
+    ///
+    /// The lines the sources are still receiving are written as well, marked as incomplete like
+    /// when a source is disconnected, so the file does not miss the tail of the logs.
+    pub(crate) fn cmd_stream_stop(&mut self, path: Option<String>) -> Result<(), String> {
+        let (mut stopped, kept): (Vec<OutputStream>, Vec<OutputStream>) =
+            std::mem::take(&mut self.output_streams)
+                .into_iter()
+                .partition(|s| path.as_ref().is_none_or(|path| &s.path == path));
+        self.output_streams = kept;
+
+        if self.app_cfg.stream_partial_lines && !stopped.is_empty() {
+            for (id, line) in self.partial_lines() {
+                let filtered = self.apply_filters(LogTimestamp::now(), id as i32, line.clone(), LogFilterScope::Export);
+                for stream in &mut stopped {
+                    let message = match stream.raw {
+                        true => Some(&line),
+                        false => filtered.as_ref().map(|log| &log.message),
+                    };
+                    if let Some(message) = message {
+                        let _ = stream.file.write_all(message.as_bytes());
+                    }
+                }
+            }
+        }
+
+        let message = match (stopped.len(), path) {
+            (0, Some(path)) => format!("Not streaming into <{}>", path),
+            (0, None) => "Not streaming into any file".to_string(),
+            (_, Some(path)) => format!("Streaming into <{}> stopped", path),
+            (_, None) => "Streaming stopped".to_string(),
+        };
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::TextMessage { message });
 
         Ok(())
     }
 }
+
+/// Make sure `path` is a named pipe, creating it if nothing is there
+#[cfg(unix)]
+fn create_fifo(path: &str) -> Result<(), String> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.file_type().is_fifo() => Ok(()),
+        Ok(_) => Err(format!("`{}` exists and is not a named pipe", path)),
+        Err(_) => {
+            let c_path = std::ffi::CString::new(path).map_err(|_| format!("Invalid path `{}`", path))?;
+            // SAFETY: the path is a valid NUL terminated string
+            match unsafe { libc::mkfifo(c_path.as_ptr(), 0o644) } {
+                0 => Ok(()),
+                _ => Err(format!(
+                    "Unable to create the named pipe `{}`: {}",
+                    path,
+                    std::io::Error::last_os_error()
+                )),
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn create_fifo(_path: &str) -> Result<(), String> {
+    Err(String::from("Named pipes are only supported on Unix"))
+}
+
+/// A file the logs are being streamed into
+pub struct OutputStream {
+    /// Path given when the stream was started
+    pub path: String,
+
+    pub(crate) file: std::fs::File,
+
+    /// Whether every log is written, ignoring the filters
+    pub raw: bool,
+}
+
+/// Longest APP-NAME field of a syslog header
+const SYSLOG_APP_NAME_LEN: usize = 48;
+
+/// A log as written by `:export`, coming from a source named `source`, ending with a newline
+pub fn export_line(log: &LogMessage, source: &str, format: ExportFormat) -> String {
+    let message = log.message.trim_end_matches(['\r', '\n']);
+    match format {
+        ExportFormat::Raw => format!("{}\n", message),
+        // RFC 5424, facility user and severity informational. Header fields are split at
+        // spaces and only take printable ASCII, the source name is made to fit
+        ExportFormat::Syslog => {
+            let app_name: String = source
+                .chars()
+                .map(|c| match c.is_ascii_graphic() {
+                    true => c,
+                    false => '_',
+                })
+                .take(SYSLOG_APP_NAME_LEN)
+                .collect();
+            let app_name = match app_name.is_empty() {
+                true => String::from("-"),
+                false => app_name,
+            };
+            format!("<14>1 {} - {} - - - {}\n", log.timestamp.to_rfc3339_string(), app_name, message)
+        }
+        ExportFormat::Json => format!(
+            "{}\n",
+            serde_json::json!({
+                "timestamp": log.timestamp.to_datetime_string(),
+                "source": source,
+                "message": message,
+            })
+        ),
+    }
+}
+
+/// Layout of the files written by `:export`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    /// RFC 5424 syslog lines, with the source as application name
+    Syslog,
+    /// One JSON object per line, with `timestamp`, `source` and `message`
+    Json,
+    /// The messages alone
+    Raw,
+}
+
+impl ExportFormat {
+    /// Parse `syslog`, `json` or `raw`
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.to_lowercase().as_str() {
+            "syslog" => Some(ExportFormat::Syslog),
+            "json" => Some(ExportFormat::Json),
+            "raw" => Some(ExportFormat::Raw),
+            _ => None,
+        }
+    }
+}
@@ -1,9 +1,12 @@
-use std::sync::mpsc::Sender;
+use std::{collections::vec_deque, str::FromStr, sync::mpsc::Sender};
 
-use ratatui::style::{self, Modifier, Style};
-use tracing::debug;
+use ratatui::style::{self, Color, Style};
+use tracing::{debug, error, info};
 
-use crate::{commander::UiCommand, LogFilter, LogFilterType, LogMessage, LogTimestamp};
+use crate::{
+    commander::UiCommand, configuration::ApplicationConfiguration, FilterRegex, LogFilter,
+    LogFilterScope, LogFilterType, LogMessage, LogTimestamp, MatchCount,
+};
 
 use super::{Command, Commander};
 
@@ -15,116 +18,747 @@ impl Commander {
     pub(crate) fn clear_filters(&mut self) -> Result<(), String> {
         // Clear filters
         self.filters.clear();
+        self.filters_dirty = true;
+
+        self.reprocess_logs();
+        Ok(())
+    }
+
+    /// Drop the logs hidden by the filters, and then the filters themselves
+    ///
+    /// The hidden logs cannot be recovered afterwards, so unless `force` is set the user is only
+    /// told how many would be lost.
+    pub(crate) fn compact_logs(&mut self, force: bool) -> Result<(), String> {
+        let visible: Vec<LogMessage> = self
+            .floored_logs()
+            .filter(|msg| {
+                self.apply_filters(msg.timestamp, msg.source_id, msg.message.to_string(), LogFilterScope::View)
+                    .is_some()
+            })
+            .cloned()
+            .collect();
+        let hidden_count = self.log_messages.len() - visible.len();
+
+        if !force {
+            let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                message: format!(
+                    "Compacting permanently drops {} hidden logs, use `:compact force` to do it",
+                    hidden_count
+                ),
+            });
+            return Ok(());
+        }
+
+        self.log_messages = visible.into();
+        self.view_floor = 0;
+        self.clear_filters()?;
+        let _ = self.command_response_tx.send(UiCommand::TextMessage {
+            message: format!("Dropped {} hidden logs, filters cleared", hidden_count),
+        });
+        Ok(())
+    }
+
+    /// Save the filters to the session file, if they changed since the last time
+    ///
+    /// They are written in the `:filter_file` format, so `:recover` can restore them after a
+    /// crash or in the next session.
+    pub(crate) fn save_session_filters(&mut self) -> Result<(), String> {
+        if !self.filters_dirty {
+            return Ok(());
+        }
+        self.filters_dirty = false;
+
+        let mut contents = String::from("# Filters of the last uberlog session, restore them with :recover\n");
+        // The highlight rules are loaded again with the configuration
+        for filter in self.filters.iter().filter(|f| !f.from_config) {
+            contents.push_str(&filter_to_args(filter));
+            contents.push('\n');
+        }
+
+        let path = ApplicationConfiguration::session_filters_path();
+        match std::fs::write(&path, contents) {
+            Ok(_) => info!("Saved {} filters to {}", self.filters.len(), path.display()),
+            Err(e) => error!("Unable to save the filters to {}: {}", path.display(), e),
+        }
+        Ok(())
+    }
+
+    /// Add the `highlight_rules` of the configuration as highlight filters
+    ///
+    /// They go first, so the highlight filters added by the user take precedence. Rules with an
+    /// invalid pattern or color are skipped, letting the user know.
+    pub(crate) fn load_highlight_rules(&mut self) {
+        let mut errors = Vec::new();
+        for rule in &self.app_cfg.highlight_rules {
+            let regex = FilterRegex::new(&rule.pattern, rule.ignore_case);
+            let color = Color::from_str(&rule.color);
+            match (regex, color) {
+                (Ok(regex), Ok(color)) => self.filters.push(LogFilter {
+                    kind: LogFilterType::Highlighter,
+                    msg: rule.pattern.clone(),
+                    style: Style::default().fg(color),
+                    scope: LogFilterScope::default(),
+                    focus: false,
+                    group: None,
+                    regex: Some(regex),
+                    ignore_case: rule.ignore_case,
+                    from_config: true,
+                    match_count: MatchCount::default(),
+                    enabled: true,
+                    columns: None,
+                    source: None,
+                }),
+                (Err(e), _) => errors.push(format!("invalid regex <{}>: {}", rule.pattern, e)),
+                (_, Err(_)) => errors.push(format!("unknown color <{}>", rule.color)),
+            }
+        }
+
+        if !errors.is_empty() {
+            error!("Skipped highlight rules: {}", errors.join(", "));
+            let _ = self.command_response_tx.send(UiCommand::ErrorMessage {
+                message: format!("Skipped highlight rules, {}", errors.join(", ")),
+            });
+        }
+        self.send_filter_list();
+    }
+
+    /// Write the current filters into `path`, as YAML
+    pub(crate) fn save_filters(&self, path: String) -> Result<(), String> {
+        let result = serde_yaml::to_string(&self.filters)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| std::fs::write(&path, contents).map_err(|e| e.to_string()));
+        let response = match result {
+            Ok(_) => UiCommand::TextMessage {
+                message: format!("Saved {} filters to <{}>", self.filters.len(), path),
+            },
+            Err(e) => UiCommand::ErrorMessage {
+                message: format!("Unable to save the filters to <{}>: {}", path, e),
+            },
+        };
+        let _ = self.command_response_tx.send(response);
+        Ok(())
+    }
+
+    /// Replace the current filters with the ones saved in `path` by `save_filters`
+    pub(crate) fn load_filters(&mut self, path: String) -> Result<(), String> {
+        let filters = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| {
+                serde_yaml::from_str::<Vec<LogFilter>>(&contents).map_err(|e| e.to_string())
+            });
+        let filters = match filters {
+            Ok(filters) => filters,
+            Err(e) => {
+                let _ = self.command_response_tx.send(UiCommand::ErrorMessage {
+                    message: format!("Unable to load the filters from <{}>: {}", path, e),
+                });
+                return Ok(());
+            }
+        };
+
+        let message = format!("Loaded {} filters from <{}>", filters.len(), path);
+        self.filters = filters;
+        self.filters_dirty = true;
+        self.reprocess_logs();
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::TextMessage { message });
+        Ok(())
+    }
+
+    /// Let the user know the filters of the last session can be restored
+    pub(crate) fn offer_filter_recovery(&self) {
+        let path = ApplicationConfiguration::session_filters_path();
+        let filter_count = std::fs::read_to_string(&path).map_or(0, |contents| {
+            contents
+                .lines()
+                .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+                .count()
+        });
+        if filter_count > 0 {
+            let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                message: format!(
+                    "{} filters of the last session were saved, `:recover` restores them",
+                    filter_count
+                ),
+            });
+        }
+    }
+
+    /// Let the UI know about the current filter list
+    pub(crate) fn send_filter_list(&self) {
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::UpdateFilterList(self.filters.clone()));
+    }
+
+    /// Count a new log in the counter filters it matches
+    ///
+    /// Counters see every log, whatever the rest of filters do with it. The UI is updated
+    /// periodically, not on every log.
+    pub(crate) fn count_filter_matches(&mut self, message: &str) {
+        for filter in &mut self.filters {
+            if filter.kind == LogFilterType::Counter && filter.enabled && filter.matches(message) {
+                filter.match_count.0 += 1;
+                self.filter_counts_dirty = true;
+            }
+        }
+    }
+
+    /// Count again from scratch, over all the logs, the matches of the counter filters
+    fn recount_filter_matches(&mut self) {
+        for filter in &mut self.filters {
+            if filter.kind == LogFilterType::Counter && filter.enabled {
+                filter.match_count.0 = self
+                    .log_messages
+                    .iter()
+                    .filter(|msg| filter.matches(&msg.message))
+                    .count();
+            }
+        }
+        self.filter_counts_dirty = false;
+    }
+
+    /// Regenerate the filtered log list
+    ///
+    /// Meant to be used after the filter list changes, sends both the filtered logs and the new
+    /// filter list (with the counters recomputed) to the UI. Returns how many logs are visible.
+    pub(crate) fn reprocess_logs(&mut self) -> usize {
+        self.recount_filter_matches();
 
-        // Empty current log list
         let filtered_messages: Vec<LogMessage> = self
-            .log_messages
-            .iter()
-            .map(|msg| self.apply_filters(msg.timestamp, msg.source_id, msg.message.to_string()))
-            .map(|msg| msg.unwrap())
+            .floored_logs()
+            .filter_map(|msg| {
+                let mut log = self.apply_filters(msg.timestamp, msg.source_id, msg.message.to_string(), LogFilterScope::View)?;
+                log.raw = msg.raw.clone();
+                Some(log)
+            })
             .collect();
+        let visible_count = filtered_messages.len();
 
         let _ = self
             .command_response_tx
             .send(UiCommand::UpdateLogs(filtered_messages));
+        self.send_filter_list();
+        visible_count
+    }
+
+    /// Logs from the view floor (see `since_boot`) on
+    pub(crate) fn floored_logs(&self) -> vec_deque::Iter<'_, LogMessage> {
+        self.log_messages.range(self.view_floor.min(self.log_messages.len())..)
+    }
+
+    /// Go to the logs of the current boot of the targets
+    ///
+    /// That is, to the last line matching the configured `boot_pattern`. With `hide` the logs
+    /// before it are hidden from the view too, until `show_before_boot`.
+    pub(crate) fn since_boot(&mut self, hide: bool) -> Result<(), String> {
+        let Some(pattern) = self.app_cfg.boot_pattern.clone() else {
+            let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                message: String::from("No boot_pattern configured"),
+            });
+            return Ok(());
+        };
+
+        // Only device output, not notes or status lines
+        let Some(boot_idx) = self
+            .log_messages
+            .iter()
+            .rposition(|msg| msg.source_id >= 0 && msg.message.contains(&pattern))
+        else {
+            let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                message: format!("No line matching the boot pattern <{}>", pattern),
+            });
+            return Ok(());
+        };
+
+        if hide {
+            self.view_floor = boot_idx;
+            self.reprocess_logs();
+            let _ = self.command_response_tx.send(UiCommand::GotoLog(0));
+            let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                message: format!(
+                    "Hiding the {} logs before the last boot, `:since_boot off` shows them again",
+                    boot_idx
+                ),
+            });
+            return Ok(());
+        }
+
+        // The boot line may be filtered out, then go to the first visible log after it
+        let floor = self.view_floor.min(boot_idx);
+        let row = self
+            .log_messages
+            .range(floor..boot_idx)
+            .filter(|msg| {
+                self.apply_filters(msg.timestamp, msg.source_id, msg.message.to_string(), LogFilterScope::View)
+                    .is_some()
+            })
+            .count();
+        let _ = self.command_response_tx.send(UiCommand::GotoLog(row));
         Ok(())
     }
 
+    /// Show again the logs hidden by `since_boot`
+    pub(crate) fn show_before_boot(&mut self) -> Result<(), String> {
+        if self.view_floor == 0 {
+            return Ok(());
+        }
+        self.view_floor = 0;
+        self.reprocess_logs();
+        Ok(())
+    }
+
+    /// Number of logs passing the view filters
+    fn visible_log_count(&self) -> usize {
+        self.floored_logs()
+            .filter(|msg| {
+                self.apply_filters(msg.timestamp, msg.source_id, msg.message.to_string(), LogFilterScope::View)
+                    .is_some()
+            })
+            .count()
+    }
+
     /// Add a new filter
     ///
     /// Not only store the new filter, but also regenerate the filtered log list and send it to the
     /// application so it can update the log view
     pub(crate) fn add_filter(&mut self, filter: LogFilter) -> Result<(), String> {
-        // Add new filter
-        self.filters.push(filter.clone());
+        self.add_filters(vec![filter])
+    }
 
-        // Empty current log list
-        let filtered_messages: Vec<LogMessage> = self
-            .log_messages
+    /// Add several filters
+    ///
+    /// Same as `add_filter`, but the log list is regenerated only once
+    pub(crate) fn add_filters(&mut self, mut filters: Vec<LogFilter>) -> Result<(), String> {
+        debug!("Added {:?}", filters);
+
+        // Filters given a source ID (`#<id>`) are stored with its name, which is what stays valid
+        for filter in &mut filters {
+            let id = filter
+                .source
+                .as_ref()
+                .and_then(|source| source.strip_prefix('#'))
+                .and_then(|id| id.parse::<i32>().ok());
+            let Some(id) = id else {
+                continue;
+            };
+            match self.source_id_string(id) {
+                Some(name) => filter.source = Some(name),
+                None => return Err(format!("No source with ID {}", id)),
+            }
+        }
+
+        let visible_before = self.visible_log_count();
+
+        // Skip the filters already present, they would only duplicate work
+        let mut duplicates = 0;
+        let mut added = Vec::new();
+        for filter in filters {
+            match self.filters.contains(&filter) {
+                true => duplicates += 1,
+                false => {
+                    added.push(filter.msg.clone());
+                    // Keep the filters of a group together, after its last one
+                    let group_end = filter.group.as_ref().and_then(|group| {
+                        self.filters
+                            .iter()
+                            .rposition(|f| f.group.as_ref() == Some(group))
+                    });
+                    match group_end {
+                        Some(idx) => self.filters.insert(idx + 1, filter),
+                        None => self.filters.push(filter),
+                    }
+                    self.filters_dirty = true;
+                }
+            }
+        }
+        if duplicates > 0 {
+            let message = match duplicates {
+                1 => String::from("Filter already present"),
+                n => format!("{} filters already present, skipped them", n),
+            };
+            let _ = self
+                .command_response_tx
+                .send(UiCommand::TextMessage { message });
+        }
+
+        let visible_after = self.reprocess_logs();
+        self.report_empty_view(&added, visible_before, visible_after);
+        Ok(())
+    }
+
+    /// Replace the filter list, after the user edited it in the filter view
+    ///
+    /// Deleting or moving filters applies nothing new, only the filters enabled by the change
+    /// are reported if the view ends up empty.
+    pub(crate) fn set_filters(&mut self, filters: Vec<LogFilter>) -> Result<(), String> {
+        let visible_before = self.visible_log_count();
+        let enabled: Vec<String> = filters
             .iter()
-            .map(|msg| self.apply_filters(msg.timestamp, msg.source_id, msg.message.to_string()))
-            .filter(|msg| msg.is_some())
-            .map(|msg| msg.unwrap())
+            .filter(|f| f.enabled && !self.filters.contains(f))
+            .map(|f| f.msg.clone())
             .collect();
 
-        let _ = self
-            .command_response_tx
-            .send(UiCommand::UpdateLogs(filtered_messages));
-        debug!("Added {:?}", filter);
+        self.filters = filters;
+        self.filters_dirty = true;
 
+        let visible_after = self.reprocess_logs();
+        self.report_empty_view(&enabled, visible_before, visible_after);
         Ok(())
     }
 
+    /// Tell the user when the filters just applied left an empty view
+    ///
+    /// It is more likely a typo in the filter than data without matches.
+    fn report_empty_view(&self, applied: &[String], visible_before: usize, visible_after: usize) {
+        if visible_after > 0 || visible_before == 0 || applied.is_empty() {
+            return;
+        }
+        let message = match applied {
+            [msg] => format!("Filter <{}> matched 0 of {} lines", msg, visible_before),
+            _ => format!("The new filters matched 0 of {} lines", visible_before),
+        };
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::TextMessage { message });
+    }
+
 
     /// Apply filters to a log message
-    pub(crate) fn apply_filters(&self, timestamp: LogTimestamp, id: i32, log: String) -> Option<LogMessage> {
-        let mut log = Some(LogMessage {
-            timestamp: timestamp.clone(),
-            style: Style::default().add_modifier(Modifier::DIM),
+    ///
+    /// Only the filters whose scope covers `target` (view or export) are taken into account,
+    /// and of those bound to a source only the ones of the log source.
+    ///
+    /// Filters form a pipeline: every step works on the output of the previous one. An
+    /// ungrouped filter is a step on its own, while the consecutive filters of a group make a
+    /// single step, in which the inclusion filters are alternatives (the log must match any of
+    /// them) and the rest are applied in order to the logs kept.
+    pub(crate) fn apply_filters(&self, timestamp: LogTimestamp, id: i32, log: String, target: LogFilterScope) -> Option<LogMessage> {
+        let mut log = LogMessage {
+            timestamp,
+            style: self.source_style(id),
             message: log,
             source_id: id,
-        });
+            highlighted: false,
+            focused: false,
+            raw: None,
+        };
 
-        for current_filter in &self.filters {
-            if log.is_none() {
-                return log;
+        // The filters of every source, together with the ones of this source
+        let name = match self.filters.iter().any(|f| f.source.is_some()) {
+            true => self.source_id_string(id),
+            false => None,
+        };
+        let filters: Vec<&LogFilter> = self
+            .filters
+            .iter()
+            .filter(|f| {
+                f.enabled && f.scope.applies_to(target) && f.source.as_ref().is_none_or(|source| Some(source) == name.as_ref())
+            })
+            .collect();
+        for step in filters.chunk_by(|a, b| a.group.is_some() && a.group == b.group) {
+            let matches = |filter: &LogFilter| filter.matches(&log.message);
+
+            let mut inclusions = step.iter().filter(|f| f.kind == LogFilterType::Inclusion).peekable();
+            if inclusions.peek().is_some() && !inclusions.any(|f| matches(f)) {
+                return None;
             }
-            match current_filter.kind {
-                LogFilterType::Inclusion => {
-                    let tmp_log = log.clone().unwrap();
-                    let retain_it = tmp_log.message.contains(&current_filter.msg)
-                        && !current_filter.msg.is_empty();
-                    if retain_it {
-                        continue;
-                    } else {
-                        log = None;
-                    }
-                }
-                LogFilterType::Exclusion => {
-                    let tmp_log = log.clone().unwrap();
-                    let retain_it = !tmp_log.message.contains(&current_filter.msg)
-                        && !current_filter.msg.is_empty();
-                    if retain_it {
-                        continue;
-                    } else {
-                        log = None;
-                    }
-                }
-                LogFilterType::Highlighter => {
-                    let tmp_log = log.clone().unwrap();
-                    let matches_msg = tmp_log.message.contains(&current_filter.msg)
-                        && !current_filter.msg.is_empty();
-                    if matches_msg {
-                        log = Some(LogMessage {
-                            timestamp: timestamp.clone(),
-                            message: log.unwrap().message,
-                            style: current_filter.style,
-                            source_id: id,
-                        });
+
+            for current_filter in step {
+                let retain_it = match current_filter.kind {
+                    LogFilterType::LongerThan(length) => line_length(&log.message) > length,
+                    LogFilterType::ShorterThan(length) => line_length(&log.message) < length,
+                    LogFilterType::After(limit) => timestamp.is_at_or_after(&limit),
+                    LogFilterType::Before(limit) => timestamp.is_at_or_before(&limit),
+                    LogFilterType::Inclusion | LogFilterType::Counter => true,
+                    LogFilterType::Exclusion => !current_filter.msg.is_empty() && !matches(current_filter),
+                    LogFilterType::Highlighter => {
+                        if matches(current_filter) {
+                            log.style = current_filter.style;
+                            log.highlighted = true;
+                            log.focused |= current_filter.focus;
+                        }
+                        true
                     }
+                };
+                if !retain_it {
+                    return None;
                 }
             }
         }
-        log
+        Some(log)
     }
 
+    /// Move a group of filters one step earlier (`up`) or later in the pipeline
+    pub(crate) fn move_filter_group(&mut self, group: String, up: bool) -> Result<(), String> {
+        let mut steps: Vec<Vec<LogFilter>> = self
+            .filters
+            .chunk_by(|a, b| a.group.is_some() && a.group == b.group)
+            .map(|step| step.to_vec())
+            .collect();
+
+        let Some(idx) = steps.iter().position(|step| step[0].group.as_ref() == Some(&group)) else {
+            let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                message: format!("No filter group <{}>", group),
+            });
+            return Ok(());
+        };
+
+        let new_idx = match up {
+            true => idx.checked_sub(1),
+            false => Some(idx + 1).filter(|i| *i < steps.len()),
+        };
+        let Some(new_idx) = new_idx else {
+            let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                message: format!("Filter group <{}> is already the {} step", group, if up { "first" } else { "last" }),
+            });
+            return Ok(());
+        };
+
+        steps.swap(idx, new_idx);
+        self.filters = steps.concat();
+        self.filters_dirty = true;
+        self.reprocess_logs();
+        Ok(())
+    }
 }
 
+/// Characters of a log, without its line ending
+fn line_length(message: &str) -> usize {
+    message.trim_end_matches(['\r', '\n']).chars().count()
+}
 
 /// Add filter callback
 ///
 /// Add a filter by parsing the `input` field. It has the general form:
-/// {h/i/e} (optional)color word
+/// {h/i/e/c}(optional modifiers) (optional)color word
+///
+/// Modifiers:
+///     v -> only apply to the log view (default)
+///     x -> only apply to exported/streamed logs
+///     b -> apply both to the log view and exported/streamed logs
+///     f -> only for highlight filters, fold the logs not highlighted by it in the log view
+///     r -> the word is a regex, instead of plain text
+///     i -> ignore the case when matching
+///
+/// Length filters have their own form, with the same scope modifiers: len[v/x/b]{>/<}N
+///
+/// Time filters too: {after/before} HH:MM:SS
+///
+/// Any of them can be preceded by `@group` to add the filter to that group, and by
+/// `src:<name>` to only apply it to the logs of the source with that name (or `src:#<id>` by ID,
+/// which is replaced by the name when the filter is added)
 ///
 /// Examples:
+///     len>200 -> only keep lines longer than 200 characters
+///     len<5 -> only keep lines shorter than 5 characters
+///     lenx>200 -> only write to files the lines longer than 200 characters
+///     after 10:00:00 -> only keep lines received at 10:00:00 or later
 ///     h red wrn -> add highlight filter (color red) for lines containing "wrn"
 ///     i tempo -> add inclusion filter for lines containing "tempo"
 ///     e tempo -> add exclusion filter for lines containing "tempo"
+///     c panic -> count the lines containing "panic", without hiding any
+///     ex tempo -> do not write lines containing "tempo" to files, but show them
+///     hf red wrn -> highlight lines containing "wrn", folding the rest
+///     hi yellow error -> highlight lines containing "error", "Error", "ERROR"...
+///     hr red ERR\[\d+\] -> highlight lines matching the regex "ERR\[\d+\]"
+///     @net i wifi -> add "wifi" to the inclusion filters of group "net"
+///     src:#2 e heartbeat -> hide the lines containing "heartbeat", only for source 2
+///     'src:Main app (RTT)' e heartbeat -> same, for the source named "Main app (RTT)"
 pub fn add_filter(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    let filter = parse_filter(&input)?;
+    let _ = sender.send(Command::AddFilter(filter));
+    Ok(())
+}
+
+/// Add all the filters defined in a file
+///
+/// Every line of the file has the same form as the arguments of `:filter`, empty lines and
+/// lines starting with `#` are ignored.
+pub fn add_filter_file(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() != 1 {
+        return Err(String::from("Wrong arguments, expected just the path"));
+    }
+
+    let contents = std::fs::read_to_string(&input[0])
+        .map_err(|e| format!("Unable to read <{}>: {}", input[0], e))?;
+
+    let mut filters = Vec::new();
+    let mut errors = Vec::new();
+    for (line_idx, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parsed = shell_words::split(line)
+            .map_err(|e| e.to_string())
+            .and_then(|args| parse_filter(&args));
+        match parsed {
+            Ok(filter) => filters.push(filter),
+            Err(e) => errors.push(format!("line {}: {}", line_idx + 1, e)),
+        }
+    }
+
+    let mut message = format!("Loaded {} filters from <{}>", filters.len(), input[0]);
+    if !errors.is_empty() {
+        message = format!("{}, errors in {}", message, errors.join(", "));
+    }
+
+    let _ = sender.send(Command::AddFilters(filters));
+    let _ = sender.send(Command::PrintMessage(message));
+    Ok(())
+}
+
+/// Restore the filters of the last session
+///
+/// They are saved as they change, so they survive a crash
+pub fn recover_filters(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if !input.is_empty() {
+        return Err(String::from("Wrong arguments, expected none"));
+    }
+
+    let path = ApplicationConfiguration::session_filters_path();
+    if !path.exists() {
+        return Err(String::from("No filters were saved"));
+    }
+    add_filter_file(sender, vec![path.display().to_string()])
+}
+
+/// Move a group of filters within the pipeline: `:filter_group <name> <up|down>`
+pub fn filter_group(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() != 2 {
+        return Err(String::from("Wrong arguments, expected <name> <up|down>"));
+    }
+    let up = match input[1].as_str() {
+        "up" => true,
+        "down" => false,
+        other => return Err(format!("Unknown direction <{}>, expected up or down", other)),
+    };
+    let _ = sender.send(Command::MoveFilterGroup(input[0].clone(), up));
+    Ok(())
+}
+
+/// Arguments of a `:filter` command creating `filter`, the inverse of `parse_filter`
+pub fn filter_to_args(filter: &LogFilter) -> String {
+    let mut args = match &filter.group {
+        Some(group) => format!("@{} {}", group, filter_args(filter)),
+        None => filter_args(filter),
+    };
+    if let Some(source) = &filter.source {
+        args = format!("{} {}", shell_words::quote(&format!("src:{}", source)), args);
+    }
+    match filter.enabled {
+        true => args,
+        false => format!("off {}", args),
+    }
+}
+
+/// Same as `filter_to_args`, ignoring the group and the source
+fn filter_args(filter: &LogFilter) -> String {
+    let scope = match filter.scope {
+        LogFilterScope::View => "",
+        LogFilterScope::Export => "x",
+        LogFilterScope::Both => "b",
+    };
+
+    let kind = match filter.kind {
+        LogFilterType::LongerThan(length) => return format!("len{}>{}", scope, length),
+        LogFilterType::ShorterThan(length) => return format!("len{}<{}", scope, length),
+        LogFilterType::After(_) | LogFilterType::Before(_) => return filter.msg.clone(),
+        LogFilterType::Highlighter => "h",
+        LogFilterType::Inclusion => "i",
+        LogFilterType::Exclusion => "e",
+        LogFilterType::Counter => "c",
+    };
+    let focus = if filter.focus { "f" } else { "" };
+    let regex = if filter.regex.is_some() { "r" } else { "" };
+    let ignore_case = if filter.ignore_case { "i" } else { "" };
+    let msg = shell_words::quote(&filter.msg);
+    let columns = match filter.columns {
+        Some((first, last)) => format!("col:{}-{} ", first, last),
+        None => String::new(),
+    };
+
+    match filter.kind {
+        LogFilterType::Highlighter => {
+            let color = match filter.style.fg {
+                Some(Color::Red) => "red",
+                Some(Color::Green) => "green",
+                Some(Color::Yellow) => "yellow",
+                Some(Color::White) => "white",
+                Some(Color::Magenta) => "magenta",
+                _ => "blue",
+            };
+            format!("{}{}{}{}{}{} {} {}", columns, kind, scope, focus, regex, ignore_case, color, msg)
+        }
+        _ => format!("{}{}{}{}{}{} {}", columns, kind, scope, focus, regex, ignore_case, msg),
+    }
+}
+
+/// Parse the arguments of a `:filter` command into a filter
+pub fn parse_filter(input: &[String]) -> Result<LogFilter, String> {
     if input.is_empty() {
         return Err(String::from("Filter information missing"));
     }
 
+    // Disabled filter, as saved for `:recover`
+    if input[0] == "off" {
+        let mut filter = parse_filter(&input[1..])?;
+        filter.enabled = false;
+        return Ok(filter);
+    }
+
+    if let Some(range) = input[0].strip_prefix("col:") {
+        let columns = parse_column_range(range)?;
+        let mut filter = parse_filter(&input[1..])?;
+        let keyword_filter = matches!(
+            filter.kind,
+            LogFilterType::Inclusion | LogFilterType::Exclusion | LogFilterType::Highlighter | LogFilterType::Counter
+        );
+        if !keyword_filter {
+            return Err(String::from("Only keyword filters can have a column range"));
+        }
+        if filter.columns.is_some() {
+            return Err(String::from("A filter can only have one column range"));
+        }
+        filter.columns = Some(columns);
+        return Ok(filter);
+    }
+
+    if let Some(source) = input[0].strip_prefix("src:") {
+        if source.is_empty() {
+            return Err(String::from("Source name missing after src:"));
+        }
+        let mut filter = parse_filter(&input[1..])?;
+        if filter.source.is_some() {
+            return Err(String::from("A filter can only be bound to one source"));
+        }
+        filter.source = Some(source.to_string());
+        return Ok(filter);
+    }
+
+    if let Some(group) = input[0].strip_prefix('@') {
+        if group.is_empty() {
+            return Err(String::from("Group name missing after @"));
+        }
+        let mut filter = parse_filter(&input[1..])?;
+        if filter.group.is_some() {
+            return Err(String::from("A filter can only be in one group"));
+        }
+        filter.group = Some(group.to_string());
+        return Ok(filter);
+    }
+
+    if let Some(predicate) = input[0].strip_prefix("len") {
+        return parse_length_filter(predicate);
+    }
+
+    if input[0] == "after" || input[0] == "before" {
+        return parse_time_filter(input);
+    }
+
     if input.len() < 2 {
         return Err(String::from(
             "Wrong arguments. Expected \'/{h,i,e} {color} word\'",
@@ -133,14 +767,34 @@ pub fn add_filter(sender: &Sender<Command>, input: Vec<String>) -> Result<(), St
 
     let mut idx = 0;
 
-    let kind = match input[idx].chars().next() {
+    let mut kind_chars = input[idx].chars();
+    let kind = match kind_chars.next() {
         Some('h') => LogFilterType::Highlighter,
         Some('i') => LogFilterType::Inclusion,
         Some('e') => LogFilterType::Exclusion,
+        Some('c') => LogFilterType::Counter,
         _ => {
             return Err("Wrong argument".to_owned());
         }
     };
+
+    // Parse modifiers
+    let mut scope = LogFilterScope::default();
+    let mut focus = false;
+    let mut is_regex = false;
+    let mut ignore_case = false;
+    for modifier in kind_chars {
+        match modifier {
+            'v' => scope = LogFilterScope::View,
+            'x' => scope = LogFilterScope::Export,
+            'b' => scope = LogFilterScope::Both,
+            'f' if kind == LogFilterType::Highlighter => focus = true,
+            'f' => return Err(String::from("Only highlight filters can focus")),
+            'r' => is_regex = true,
+            'i' => ignore_case = true,
+            _ => return Err(format!("Unknown filter modifier <{}>", modifier)),
+        }
+    }
     idx = idx + 1;
 
     // Inclusion/exclusion do not change color
@@ -163,11 +817,111 @@ pub fn add_filter(sender: &Sender<Command>, input: Vec<String>) -> Result<(), St
         ..Default::default()
     };
 
-    let _ = sender.send(Command::AddFilter(LogFilter {
+    // Compile regexes once, here, so a wrong one is reported right away
+    let msg = input[idx].clone();
+    let regex = match is_regex {
+        true => Some(
+            FilterRegex::new(&msg, ignore_case)
+                .map_err(|e| format!("Invalid regex <{}>: {}", msg, e))?,
+        ),
+        false => None,
+    };
+
+    Ok(LogFilter {
         style: filter_style,
         kind,
-        msg: input[idx].clone(),
-    }));
+        msg,
+        scope,
+        focus,
+        group: None,
+        regex,
+        ignore_case,
+        from_config: false,
+        match_count: MatchCount::default(),
+        enabled: true,
+        columns: None,
+        source: None,
+    })
+}
 
-    Ok(())
+/// Parse the `first-last` part of a `col:first-last` column range, columns start at 1
+fn parse_column_range(range: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("Invalid column range <{}>, expected i.e. col:13-17", range);
+    let (first, last) = range.split_once('-').ok_or_else(invalid)?;
+    let first = first.parse::<usize>().map_err(|_| invalid())?;
+    let last = last.parse::<usize>().map_err(|_| invalid())?;
+    if first == 0 || last < first {
+        return Err(invalid());
+    }
+    Ok((first, last))
+}
+
+/// Parse a `{after/before} HH:MM:SS` time filter
+fn parse_time_filter(input: &[String]) -> Result<LogFilter, String> {
+    if input.len() != 2 {
+        return Err(format!("Wrong arguments. Expected '{} HH:MM:SS'", input[0]));
+    }
+    let limit = LogTimestamp::parse(&input[1])
+        .ok_or(format!("Invalid time <{}>, expected HH:MM:SS", input[1]))?;
+
+    let kind = match input[0].as_str() {
+        "after" => LogFilterType::After(limit),
+        _ => LogFilterType::Before(limit),
+    };
+
+    Ok(LogFilter {
+        style: Style::default(),
+        kind,
+        msg: format!("{} {}", input[0], input[1]),
+        scope: LogFilterScope::default(),
+        focus: false,
+        group: None,
+        regex: None,
+        ignore_case: false,
+        from_config: false,
+        match_count: MatchCount::default(),
+        enabled: true,
+        columns: None,
+        source: None,
+    })
+}
+
+/// Parse the `[v/x/b]{>/<}N` part of a length filter
+fn parse_length_filter(predicate: &str) -> Result<LogFilter, String> {
+    let parse_length = |text: &str| {
+        text.parse::<usize>()
+            .map_err(|_| format!("Invalid length <{}>", text))
+    };
+
+    // Scope modifier, as for the rest of filters
+    let (scope, predicate) = match predicate.chars().next() {
+        Some('v') => (LogFilterScope::View, &predicate[1..]),
+        Some('x') => (LogFilterScope::Export, &predicate[1..]),
+        Some('b') => (LogFilterScope::Both, &predicate[1..]),
+        _ => (LogFilterScope::default(), predicate),
+    };
+
+    let kind = if let Some(length) = predicate.strip_prefix('>') {
+        LogFilterType::LongerThan(parse_length(length)?)
+    } else if let Some(length) = predicate.strip_prefix('<') {
+        LogFilterType::ShorterThan(parse_length(length)?)
+    } else {
+        return Err(String::from("Wrong arguments. Expected 'len>N' or 'len<N'"));
+    };
+
+    Ok(LogFilter {
+        style: Style::default(),
+        kind,
+        msg: format!("len{}", predicate),
+        scope,
+        focus: false,
+        group: None,
+        regex: None,
+        ignore_case: false,
+        from_config: false,
+        match_count: MatchCount::default(),
+        enabled: true,
+        columns: None,
+        source: None,
+    })
 }
@@ -1,26 +1,49 @@
 use std::{
+    collections::{HashMap, HashSet, VecDeque},
     fmt,
+    str::FromStr,
     io::Write,
-    sync::mpsc::{Receiver, Sender},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, RecvTimeoutError, Sender},
+    },
+    time::{Duration, Instant},
 };
 
 use crate::{
-    LogFilter, LogMessage, LogTimestamp,
-    configuration::{ApplicationConfiguration, LogBackend, TargetConfiguration},
-    log_source::{LogSource, LogSourceTrait, RttSource, UartSource},
+    APP_SOURCE_ID, LogFilter, LogFilterScope, LogMessage, LogTimestamp, MatchCount, NOTE_SOURCE_ID,
+    STATUS_LINE_PREFIX, STATUS_SOURCE_ID,
+    configuration::{ApplicationConfiguration, LogBackend, TARGET_CFG_PATH, TargetConfiguration},
+    log_source::{LogSource, LogSourceTrait, RttSource, UartControlLine, UartSource},
 };
 use elf::{ElfBytes, endian::AnyEndian};
-use probe_rs::probe::{DebugProbeInfo, list::Lister};
+use probe_rs::probe::{DebugProbeError, DebugProbeInfo, Probe, WireProtocol, list::Lister};
 use probe_rs::flashing;
-use ratatui::style::{Modifier, Style};
+use ratatui::style::{Color, Style};
+use regex::Regex;
 use tracing::{debug, error, info, warn};
 
+mod clipboard;
+mod defmt;
+pub use defmt::{MALFORMED_FRAME_MARKER, decode_defmt_frames};
+mod diff;
+pub use diff::{DiffKind, DiffRow, diff_lines, diff_lines_with_timeout};
 mod file_io;
+mod notify_handler;
+use notify_handler::MatchTrigger;
 mod source_handler;
 mod user_commands;
+pub use file_io::{ExportFormat, OutputStream, export_line};
 mod filter_handler;
-pub use user_commands::{find_log, stream_file, stream_start, stream_stop};
-pub use filter_handler::add_filter;
+pub use user_commands::{compact, diff, dump_config, filter_load, filter_save, find_log, goto_time, grep, hex, loglevel, note, since_boot, notify, notify_clear, on_match, on_match_clear, backend, profile, rtt_channel, export, stream_adb, stream_fifo, stream_file, stream_udp, stream_start, stream_stop, uart_line};
+pub use filter_handler::{add_filter, add_filter_file, filter_group, filter_to_args, parse_filter, recover_filters};
+
+/// Time given to the debug probe enumeration before giving up on it
+const PROBE_LISTING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Appended to lines that were flushed before receiving their terminating `\n`
+pub const INCOMPLETE_LINE_MARKER: &str = " [incomplete]";
 
 pub struct Commander {
     /// Connected target information
@@ -33,17 +56,96 @@ pub struct Commander {
     filters: Vec<LogFilter>,
 
     /// All received log messages
-    log_messages: Vec<LogMessage>,
+    log_messages: VecDeque<LogMessage>,
+
+    /// Index of the first log shown in the view, the ones before are hidden by `:since_boot`
+    view_floor: usize,
+
+    /// Parsed `source_colors` of the configuration
+    source_colors: Vec<(String, Color)>,
+
+    /// Sources whose in-progress (not yet terminated) line is being shown in the UI
+    partial_line_sources: HashSet<u32>,
+
+    /// Lines received from each source, and the sources whose count changed since the UI was
+    /// last updated
+    source_line_counts: HashMap<u32, usize>,
+    updated_source_stats: HashSet<u32>,
+
+    /// The filters changed since they were last saved to the session file
+    filters_dirty: bool,
+
+    /// The counter filters counted new matches since the UI was last updated
+    filter_counts_dirty: bool,
+
+    /// Names of the sources removed, their logs are still exported under them
+    removed_source_names: HashMap<u32, String>,
+
+    /// Paused sources, along with the data received meanwhile
+    paused_sources: HashMap<u32, Vec<u8>>,
+
+    /// Patterns triggering a desktop notification, when was the last one sent, and how many
+    /// matches were not notified since then
+    notify_patterns: Vec<String>,
+    last_notification: Option<Instant>,
+    suppressed_notifications: usize,
+
+    /// Commands run on matching lines, and whether the user allowed them in this session
+    match_triggers: Vec<MatchTrigger>,
+    match_triggers_allowed: bool,
+
+    /// System clipboard, opened on the first copy
+    clipboard: Option<arboard::Clipboard>,
 
     /// Target configuration (from .gadget.yaml)
     pub target_cfg: Option<TargetConfiguration>,
 
+    /// Profile the target configuration comes from, if switched with `:profile`
+    active_profile: Option<String>,
+
     /// Application configuration (from ~/.config/uberlog/config.yaml)
     pub app_cfg: ApplicationConfiguration,
 
-    /// Log streaming information
-    pub stream_logs: bool,
-    pub stream_logs_file_handle: Option<std::fs::File>,
+    /// Timestamp pattern lines are split at, from `split_on_timestamp`
+    timestamp_split_regex: Option<Regex>,
+
+    /// When was the probe information last refreshed
+    last_probe_refresh: Option<Instant>,
+
+    /// A probe enumeration is running (or hung)
+    probe_listing_busy: Arc<AtomicBool>,
+
+    /// Consecutive probe refreshes the probe of a source has been missing from, by source id
+    missing_probe_refreshes: HashMap<u32, u32>,
+
+    /// RTT up channels picked by the user for every target, for the rest of the session
+    rtt_channels: HashMap<String, Vec<usize>>,
+
+    /// Backend picked by the user for every target able to log several ways, for the rest of
+    /// the session
+    target_backends: HashMap<String, usize>,
+
+    /// Targets to connect once their probe is detected, along with when to give up
+    pending_connections: Vec<(String, Instant)>,
+
+    /// Last time every source received data or was acted on by the user, by source id
+    last_source_activity: HashMap<u32, Instant>,
+
+    /// The idle sources are being checked for, only once some target has an idle timeout
+    idle_source_checks_started: bool,
+
+    /// When the sources with bytes waiting for the end of their line last received data
+    incomplete_line_since: HashMap<u32, Instant>,
+
+    /// When the first byte of the line every source is receiving arrived, for
+    /// `first_byte_timestamps`
+    line_start_timestamps: HashMap<u32, LogTimestamp>,
+
+    /// defmt tables of the sources logging with `DefmtRtt`, by source id
+    defmt_tables: HashMap<u32, defmt_decoder::Table>,
+
+    /// Files the logs are being streamed into
+    pub output_streams: Vec<OutputStream>,
 
     /// Command input
     pub command_rx: Receiver<Command>,
@@ -60,31 +162,73 @@ pub struct Commander {
 
 pub enum Command {
     // File
-    StreamFile(String),
+    StreamFile(String, bool /* Follow */, bool /* Tail */),
+    SourceCompleted(u32),
     StreamStdin,
-    StreamLogs(bool, String),
+    StreamAdb(Option<String> /* Device serial */),
+    StreamUdp(u16 /* Port */),
+    StreamFifo(String /* Path */),
+    StartStream(String, bool),
+    ExportLogs(String /* Path */, ExportFormat, bool /* Only the logs in the view */),
+    StopStream(Option<String>),
 
     // LogSources
     ConnectLogSource(u32),
+    ConnectLogSourceByName(String),
+    SetUartLine(Option<String> /* Target name, all if None */, UartControlLine, bool /* Level */),
+    SetLogLevel(Option<String> /* Target name, all if None */, String /* Level */),
+    SetRttChannels(Option<String> /* Target name, all if None */, Vec<usize>),
+    SetBackend(String /* Target name */, usize),
     DisconnectLogSource(u32),
+    TogglePause(u32),
+    SwitchProfile(String),
 
     // Probes
     RefreshProbeInfo,
-    Reset(u32),
+    AutoRefreshProbeInfo,
+    SendSourceStats,
+    DisconnectIdleSources,
+    FlushIncompleteLines,
+    SaveSessionFilters,
+    Reset(u32 /* ID */, bool /* Force */),
     Reflash(u32),
 
     // Misc
     PrintMessage(String),
+    PrintError(String),
 
     // Filters
     AddFilter(LogFilter),
+    AddFilters(Vec<LogFilter>),
+    SetFilters(Vec<LogFilter>),
     ClearFilters,
+    MoveFilterGroup(String, bool /* Up */),
+    SaveFilters(String),
+    LoadFilters(String),
+    Compact(bool),
     GetFilters,
 
     // Logs
     ParseLogBytes(u32, Vec<u8>),
     ClearLogs,
-    FindLog(String),
+    FindLog(String, Option<Color>, usize /* Matches to jump */, Option<String> /* Search name */),
+    Grep(String),
+    DiffFiles(String, String),
+    GotoTime(LogTimestamp),
+    ToggleHexView(Option<i32> /* Source ID, every source if None */),
+    SinceBoot(bool /* Hide the logs before */),
+    ShowBeforeBoot,
+    InsertNote(String),
+    CopyToClipboard(String),
+    DumpConfig,
+
+    // Notifications
+    AddNotifyPattern(String),
+    ClearNotifyPatterns,
+    ReportSuppressedNotifications,
+    AllowMatchTriggers,
+    AddMatchTrigger(String /* Pattern */, String /* Command */),
+    ClearMatchTriggers,
 }
 
 impl fmt::Display for Command {
@@ -94,17 +238,57 @@ impl fmt::Display for Command {
             Command::GetFilters => "GetFilters",
             Command::ParseLogBytes(_, _) => "ParseLogBytes",
             Command::ClearFilters => "ClearFilters",
-            Command::Reset(_) => "Reset",
+            Command::MoveFilterGroup(_, _) => "MoveFilterGroup",
+            Command::SaveFilters(_) => "SaveFilters",
+            Command::LoadFilters(_) => "LoadFilters",
+            Command::Compact(_) => "Compact",
+            Command::Reset(_, _) => "Reset",
             Command::Reflash(_) => "Reflash",
             Command::AddFilter(_) => "AddFilter",
+            Command::AddFilters(_) => "AddFilters",
+            Command::SetFilters(_) => "SetFilters",
             Command::PrintMessage(_) => "PrintMessage",
-            Command::FindLog(_) => "FindLog",
+            Command::PrintError(_) => "PrintError",
+            Command::FindLog(_, _, _, _) => "FindLog",
+            Command::Grep(_) => "Grep",
+            Command::DiffFiles(_, _) => "DiffFiles",
+            Command::GotoTime(_) => "GotoTime",
+            Command::ToggleHexView(_) => "ToggleHexView",
+            Command::SinceBoot(_) => "SinceBoot",
+            Command::ShowBeforeBoot => "ShowBeforeBoot",
+            Command::InsertNote(_) => "InsertNote",
+            Command::CopyToClipboard(_) => "CopyToClipboard",
+            Command::DumpConfig => "DumpConfig",
+            Command::AddNotifyPattern(_) => "AddNotifyPattern",
+            Command::ClearNotifyPatterns => "ClearNotifyPatterns",
+            Command::ReportSuppressedNotifications => "ReportSuppressedNotifications",
+            Command::AllowMatchTriggers => "AllowMatchTriggers",
+            Command::AddMatchTrigger(_, _) => "AddMatchTrigger",
+            Command::ClearMatchTriggers => "ClearMatchTriggers",
             Command::RefreshProbeInfo => "RefreshProbeInfo",
-            Command::StreamLogs(_, _) => "StreamLogs",
-            Command::StreamFile(_) => "StreamFile",
+            Command::AutoRefreshProbeInfo => "AutoRefreshProbeInfo",
+            Command::SendSourceStats => "SendSourceStats",
+            Command::DisconnectIdleSources => "DisconnectIdleSources",
+            Command::FlushIncompleteLines => "FlushIncompleteLines",
+            Command::SaveSessionFilters => "SaveSessionFilters",
+            Command::StartStream(_, _) => "StartStream",
+            Command::StopStream(_) => "StopStream",
+            Command::ExportLogs(_, _, _) => "ExportLogs",
+            Command::StreamFile(_, _, _) => "StreamFile",
+            Command::SourceCompleted(_) => "SourceCompleted",
             Command::StreamStdin => "StreamStdin",
+            Command::StreamAdb(_) => "StreamAdb",
+            Command::StreamUdp(_) => "StreamUdp",
+            Command::StreamFifo(_) => "StreamFifo",
             Command::ConnectLogSource(_) => "ConnectLogSource",
+            Command::ConnectLogSourceByName(_) => "ConnectLogSourceByName",
+            Command::SetUartLine(_, _, _) => "SetUartLine",
+            Command::SetLogLevel(_, _) => "SetLogLevel",
+            Command::SetRttChannels(_, _) => "SetRttChannels",
+            Command::SetBackend(_, _) => "SetBackend",
             Command::DisconnectLogSource(_) => "DisconnectLogSource",
+            Command::TogglePause(_) => "TogglePause",
+            Command::SwitchProfile(_) => "SwitchProfile",
         };
         write!(f, "{}", text)
     }
@@ -115,31 +299,54 @@ pub enum UiCommand {
     TextMessage {
         message: String,
     },
+    /// Same as `TextMessage`, but the UI keeps an indicator until it is acknowledged
+    ErrorMessage {
+        message: String,
+    },
 
     /// Sources
     AddNewSource(u32 /* ID */, String /* Text to display */),
     RemoveSource(u32 /* ID */),
     SetConnectionSource(u32 /* ID */, bool /* Is connected */),
+    SetSourceCompleted(u32 /* ID */),
+    SetSourcePaused(u32 /* ID */, bool /* Is paused */),
+    SetSourceStats(u32 /* ID */, usize /* Lines received */),
     SetProgress(u32 /* ID */, u16 /* Progress % */, String /* Stage */),
 
     /// Filters
     UpdateFilterList(Vec<LogFilter>),
     UpdateLogs(Vec<LogMessage>),
+    UpdatePartialLog(u32 /* ID */, Option<LogMessage>),
 
     /// Log search
-    UpdateSearchLog(String),
+    UpdateSearchLog(String, Option<Color>, usize /* Matches to jump */, Option<String> /* Search name */),
+    ShowGrep(String, Vec<(usize, LogMessage)>),
+    ShowDiff(String, String, Vec<DiffRow>, bool /* Exact */),
+    GotoTime(LogTimestamp),
+    GotoLog(usize /* Index in the filtered logs */),
+    ToggleHexView(Option<i32> /* Source ID, every source if None */),
 }
 
 impl fmt::Display for UiCommand {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let text = match self {
             UiCommand::TextMessage { message: _ } => "TextMessage",
+            UiCommand::ErrorMessage { message: _ } => "ErrorMessage",
             UiCommand::AddNewSource(_, _) => "AddNewSource",
             UiCommand::SetConnectionSource(_, _) => "SetConnectionSource",
+            UiCommand::SetSourceCompleted(_) => "SetSourceCompleted",
+            UiCommand::SetSourcePaused(_, _) => "SetSourcePaused",
+            UiCommand::SetSourceStats(_, _) => "SetSourceStats",
             UiCommand::SetProgress(_, _, _) => "SetProgress",
             UiCommand::UpdateFilterList(_) => "UpdateFilterList",
             UiCommand::UpdateLogs(_) => "UpdateLogs",
-            UiCommand::UpdateSearchLog(_) => "UpdateSearchLog",
+            UiCommand::UpdatePartialLog(_, _) => "UpdatePartialLog",
+            UiCommand::UpdateSearchLog(_, _, _, _) => "UpdateSearchLog",
+            UiCommand::ShowGrep(_, _) => "ShowGrep",
+            UiCommand::ShowDiff(_, _, _, _) => "ShowDiff",
+            UiCommand::GotoTime(_) => "GotoTime",
+            UiCommand::ToggleHexView(_) => "ToggleHexView",
+            UiCommand::GotoLog(_) => "GotoLog",
             UiCommand::RemoveSource(_) => "RemoveSource",
         };
         write!(f, "{}", text)
@@ -153,7 +360,7 @@ impl fmt::Display for UiCommand {
 #[derive(Clone)]
 pub enum LogBackendInformation {
     Rtt(u64),
-    Uart(String, u32),
+    Uart(String /* Device */, u32 /* Baud */, Option<bool> /* Initial DTR */, Option<bool> /* Initial RTS */),
 }
 
 /// This class holds the whole state of a target MCU
@@ -173,6 +380,27 @@ pub struct TargetMcu {
 
     /// Details about the log backend used by the target
     pub backend: LogBackendInformation,
+
+    /// Debug protocol and speed (kHz), probe-rs defaults if not set
+    pub protocol: Option<WireProtocol>,
+    pub speed_khz: Option<u32>,
+
+    /// Attach with the target held in reset
+    pub attach_under_reset: bool,
+}
+
+impl TargetMcu {
+    /// Open the probe of the target, applying the configured protocol and speed
+    pub fn open_probe(&self) -> Result<Probe, DebugProbeError> {
+        let mut probe = self.probe_info.open()?;
+        if let Some(protocol) = self.protocol {
+            probe.select_protocol(protocol)?;
+        }
+        if let Some(speed_khz) = self.speed_khz {
+            probe.set_speed(speed_khz)?;
+        }
+        Ok(probe)
+    }
 }
 
 impl Commander {
@@ -192,20 +420,186 @@ impl Commander {
             log_sources: Vec::new(),
             log_source_id: 0,
             filters: Vec::new(),
-            log_messages: Vec::new(),
+            log_messages: VecDeque::new(),
+            view_floor: 0,
+            source_colors: Vec::new(),
+            partial_line_sources: HashSet::new(),
+            source_line_counts: HashMap::new(),
+            updated_source_stats: HashSet::new(),
+            removed_source_names: HashMap::new(),
+            paused_sources: HashMap::new(),
+            notify_patterns: Vec::new(),
+            last_notification: None,
+            suppressed_notifications: 0,
+            match_triggers: Vec::new(),
+            match_triggers_allowed: false,
+            clipboard: None,
+            filters_dirty: false,
+            filter_counts_dirty: false,
             target_cfg: cfg,
+            active_profile: None,
             app_cfg: app_cfg.clone(),
+            last_probe_refresh: None,
+            probe_listing_busy: Arc::new(AtomicBool::new(false)),
+            missing_probe_refreshes: HashMap::new(),
+            rtt_channels: HashMap::new(),
+            target_backends: HashMap::new(),
+            pending_connections: Vec::new(),
+            last_source_activity: HashMap::new(),
+            idle_source_checks_started: false,
+            incomplete_line_since: HashMap::new(),
+            line_start_timestamps: HashMap::new(),
+            defmt_tables: HashMap::new(),
+            timestamp_split_regex: None,
             command_rx,
             command_tx,
             command_response_tx,
             log_message_tx: rtt_tx,
-            stream_logs: false,
-            stream_logs_file_handle: None,
+            output_streams: Vec::new(),
         };
+        if let Some(pattern) = &app_cfg.split_on_timestamp {
+            match Regex::new(pattern) {
+                Ok(regex) => ret.timestamp_split_regex = Some(regex),
+                Err(e) => {
+                    error!("Invalid split_on_timestamp pattern: {}", e);
+                    let _ = ret.command_response_tx.send(UiCommand::TextMessage {
+                        message: format!("Invalid split_on_timestamp pattern <{}>, not splitting lines", pattern),
+                    });
+                }
+            }
+        }
+        ret.load_highlight_rules();
+        ret.load_source_colors();
         let _ = ret.cmd_refresh_probe_info();
+        ret.start_probe_auto_refresh();
+        ret.start_source_stats_updates();
+        ret.start_idle_source_checks();
+        ret.start_incomplete_line_checks();
+        ret.start_filter_autosave();
+        ret.start_suppressed_notification_reports();
+        ret.offer_filter_recovery();
         ret
     }
 
+    /// Periodically request the filters to be saved, if they changed
+    ///
+    /// Saving on every change would thrash the disk while filters are being tweaked
+    fn start_filter_autosave(&self) {
+        let command_tx = self.command_tx.clone();
+        std::thread::spawn(move || {
+            info!("Thread started - Filter autosave");
+            loop {
+                std::thread::sleep(Duration::from_secs(2));
+                if command_tx.send(Command::SaveSessionFilters).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Periodically request the matches held back by the notification rate limit to be
+    /// reported
+    ///
+    /// Otherwise they would only be told about with the next match, if any ever arrives
+    fn start_suppressed_notification_reports(&self) {
+        let command_tx = self.command_tx.clone();
+        std::thread::spawn(move || {
+            info!("Thread started - Suppressed notification reports");
+            loop {
+                std::thread::sleep(Duration::from_secs(1));
+                if command_tx.send(Command::ReportSuppressedNotifications).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Periodically request the source statistics to be sent to the UI
+    ///
+    /// Sending them on every line would flood the UI when a source is chatty
+    fn start_source_stats_updates(&self) {
+        let command_tx = self.command_tx.clone();
+        std::thread::spawn(move || {
+            info!("Thread started - Source statistics");
+            loop {
+                std::thread::sleep(Duration::from_secs(1));
+                if command_tx.send(Command::SendSourceStats).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Periodically request the sources idle for too long to be disconnected
+    ///
+    /// Targets opt in with `idle_disconnect_s`, the rest are never disconnected. Without any
+    /// of them there is nothing to check, it is called again when switching profiles.
+    pub(crate) fn start_idle_source_checks(&mut self) {
+        let any_idle_timeout = self
+            .target_cfg
+            .as_ref()
+            .is_some_and(|cfg| cfg.targets.iter().any(|t| t.idle_disconnect_s.is_some()));
+        if self.idle_source_checks_started || !any_idle_timeout {
+            return;
+        }
+        self.idle_source_checks_started = true;
+
+        let command_tx = self.command_tx.clone();
+        std::thread::spawn(move || {
+            info!("Thread started - Idle source checks");
+            loop {
+                std::thread::sleep(Duration::from_secs(1));
+                if command_tx.send(Command::DisconnectIdleSources).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Periodically request the lines left without newline for too long to be shown, if
+    /// configured
+    fn start_incomplete_line_checks(&self) {
+        let timeout_ms = self.app_cfg.incomplete_line_timeout_ms;
+        if timeout_ms == 0 {
+            return;
+        }
+
+        // Often enough for the lines to show up close to the timeout
+        let interval = Duration::from_millis((timeout_ms / 4).clamp(50, 1000));
+        let command_tx = self.command_tx.clone();
+        std::thread::spawn(move || {
+            info!("Thread started - Incomplete line checks ({} ms)", timeout_ms);
+            loop {
+                std::thread::sleep(interval);
+                if command_tx.send(Command::FlushIncompleteLines).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Periodically request a probe refresh, if configured
+    ///
+    /// Newly attached probes then show up, and vanished ones are removed, without user
+    /// interaction. Disabled by default since it means enumerating USB devices all the time.
+    fn start_probe_auto_refresh(&self) {
+        let interval_ms = self.app_cfg.probe_refresh_interval_ms;
+        if interval_ms == 0 {
+            return;
+        }
+
+        let command_tx = self.command_tx.clone();
+        std::thread::spawn(move || {
+            info!("Thread started - Probe auto refresh ({} ms)", interval_ms);
+            loop {
+                std::thread::sleep(Duration::from_millis(interval_ms));
+                if command_tx.send(Command::AutoRefreshProbeInfo).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
     /// Process incoming commands
     ///
     /// Core of this module, this function is designed in a way that a thread is to be calling it periodically
@@ -217,24 +611,69 @@ impl Commander {
                 Command::RefreshProbeInfo => {
                     return self.cmd_refresh_probe_info();
                 }
-                Command::Reset(source_id) => {
-                    return self.reset_log_source(source_id);
+                Command::SendSourceStats => {
+                    return self.send_source_stats();
+                }
+                Command::DisconnectIdleSources => {
+                    return self.disconnect_idle_sources();
+                }
+                Command::FlushIncompleteLines => {
+                    self.flush_incomplete_lines();
+                }
+                Command::SaveSessionFilters => {
+                    return self.save_session_filters();
+                }
+                Command::AutoRefreshProbeInfo => {
+                    // Debounce: skip it if a refresh happened recently anyway
+                    let interval = Duration::from_millis(self.app_cfg.probe_refresh_interval_ms);
+                    let recently_refreshed = self
+                        .last_probe_refresh
+                        .is_some_and(|t| t.elapsed() < interval / 2);
+                    if self.target_cfg.is_some() && !recently_refreshed {
+                        return self.cmd_refresh_probe_info();
+                    }
+                }
+                Command::Reset(source_id, force) => {
+                    return self.reset_log_source(source_id, force);
                 }
                 Command::Reflash(source_id) => {
                     return self.reflash_log_source(source_id);
                 }
-                Command::StreamLogs(streaming, path) => {
-                    return self.cmd_log_stream(streaming, path);
+                Command::StartStream(path, raw) => {
+                    return self.cmd_stream_start(path, raw);
+                }
+                Command::StopStream(path) => {
+                    return self.cmd_stream_stop(path);
+                }
+                Command::ExportLogs(path, format, view) => {
+                    return self.cmd_export(path, format, view);
                 }
                 Command::ParseLogBytes(id, bytes) => {
                     return self.cmd_parse_bytes(id, bytes);
                 }
-                Command::StreamFile(path) => {
-                    return self.cmd_stream_file(path);
+                Command::StreamFile(path, follow, tail) => {
+                    return self.cmd_stream_file(path, follow, tail);
+                }
+                Command::SourceCompleted(id) => {
+                    return self.complete_log_source(id);
                 }
                 Command::StreamStdin => {
                     return self.cmd_stream_stdin();
                 }
+                Command::StreamAdb(serial) => {
+                    return self.cmd_stream_adb(serial);
+                }
+                Command::StreamUdp(port) => {
+                    return self.cmd_stream_udp(port);
+                }
+                Command::StreamFifo(path) => {
+                    return self.cmd_stream_fifo(path);
+                }
+                Command::PrintError(msg) => {
+                    let _ = self
+                        .command_response_tx
+                        .send(UiCommand::ErrorMessage { message: msg });
+                }
                 Command::PrintMessage(msg) => {
                     let _ = self
                         .command_response_tx
@@ -243,26 +682,112 @@ impl Commander {
                 Command::AddFilter(filter) => {
                     return self.add_filter(filter);
                 }
+                Command::AddFilters(filters) => {
+                    return self.add_filters(filters);
+                }
+                Command::SetFilters(filters) => {
+                    return self.set_filters(filters);
+                }
                 Command::ClearFilters => {
                     return self.clear_filters();
                 }
+                Command::MoveFilterGroup(group, up) => {
+                    return self.move_filter_group(group, up);
+                }
+                Command::SaveFilters(path) => {
+                    return self.save_filters(path);
+                }
+                Command::LoadFilters(path) => {
+                    return self.load_filters(path);
+                }
                 Command::GetFilters => {
-                    let _ = self
-                        .command_response_tx
-                        .send(UiCommand::UpdateFilterList(self.filters.clone()));
+                    self.send_filter_list();
+                }
+                Command::Compact(force) => {
+                    return self.compact_logs(force);
                 }
                 Command::ClearLogs => {
                     return self.clear_logs();
                 }
-                Command::FindLog(log) => {
-                    return self.update_log_search(log);
+                Command::FindLog(log, color, count, name) => {
+                    return self.update_log_search(log, color, count, name);
+                }
+                Command::CopyToClipboard(text) => {
+                    return self.copy_to_clipboard(text);
+                }
+                Command::InsertNote(text) => {
+                    return self.insert_note(text);
+                }
+                Command::DumpConfig => {
+                    return self.dump_config();
+                }
+                Command::AddNotifyPattern(pattern) => {
+                    return self.add_notify_pattern(pattern);
+                }
+                Command::ClearNotifyPatterns => {
+                    return self.clear_notify_patterns();
+                }
+                Command::ReportSuppressedNotifications => {
+                    return self.report_suppressed_notifications();
+                }
+                Command::AllowMatchTriggers => {
+                    return self.allow_match_triggers();
+                }
+                Command::AddMatchTrigger(pattern, command) => {
+                    return self.add_match_trigger(pattern, command);
+                }
+                Command::ClearMatchTriggers => {
+                    return self.clear_match_triggers();
+                }
+                Command::Grep(pattern) => {
+                    return self.grep_logs(pattern);
+                }
+                Command::DiffFiles(path_a, path_b) => {
+                    return self.cmd_diff(path_a, path_b);
+                }
+                Command::GotoTime(timestamp) => {
+                    let _ = self
+                        .command_response_tx
+                        .send(UiCommand::GotoTime(timestamp));
+                }
+                Command::ToggleHexView(id) => {
+                    let _ = self
+                        .command_response_tx
+                        .send(UiCommand::ToggleHexView(id));
+                }
+                Command::SinceBoot(hide) => {
+                    return self.since_boot(hide);
+                }
+                Command::ShowBeforeBoot => {
+                    return self.show_before_boot();
                 }
                 Command::ConnectLogSource(id) => {
                     return self.connect_log_source(id);
                 }
+                Command::ConnectLogSourceByName(name) => {
+                    return self.connect_log_source_by_name(name);
+                }
+                Command::SetLogLevel(target, level) => {
+                    return self.set_log_level(target, level);
+                }
+                Command::SetUartLine(target, line, level) => {
+                    return self.set_uart_line(target, line, level);
+                }
+                Command::SetRttChannels(target, channels) => {
+                    return self.set_rtt_channels(target, channels);
+                }
+                Command::SetBackend(target, backend) => {
+                    return self.set_target_backend(target, backend);
+                }
                 Command::DisconnectLogSource(id) => {
                     return self.disconnect_log_source(id);
                 }
+                Command::TogglePause(id) => {
+                    return self.toggle_pause_log_source(id);
+                }
+                Command::SwitchProfile(name) => {
+                    return self.switch_profile(name);
+                }
             }
         } else {
             error!("Channel broke, stop further processing");
@@ -272,10 +797,46 @@ impl Commander {
     }
 
     /// Change the log being searched for
-    fn update_log_search(&self, log: String) -> Result<(), String> {
+    fn update_log_search(
+        &self,
+        log: String,
+        color: Option<Color>,
+        count: usize,
+        name: Option<String>,
+    ) -> Result<(), String> {
         let _ = self
             .command_response_tx
-            .send(UiCommand::UpdateSearchLog(log));
+            .send(UiCommand::UpdateSearchLog(log, color, count, name));
+        Ok(())
+    }
+
+    /// Send to the UI all stored logs containing `pattern`, along with their position
+    fn grep_logs(&self, pattern: String) -> Result<(), String> {
+        let matches = self
+            .log_messages
+            .iter()
+            .enumerate()
+            .filter(|(_, log)| log.message.contains(&pattern))
+            .map(|(idx, log)| (idx, log.clone()))
+            .collect();
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::ShowGrep(pattern, matches));
+        Ok(())
+    }
+
+    /// Let the UI know about the line count of the sources that changed
+    fn send_source_stats(&mut self) -> Result<(), String> {
+        for id in self.updated_source_stats.drain() {
+            let lines = self.source_line_counts.get(&id).copied().unwrap_or(0);
+            let _ = self
+                .command_response_tx
+                .send(UiCommand::SetSourceStats(id, lines));
+        }
+        if self.filter_counts_dirty {
+            self.filter_counts_dirty = false;
+            self.send_filter_list();
+        }
         Ok(())
     }
 
@@ -284,6 +845,16 @@ impl Commander {
     /// Remove all stored logs and request a clear also to the UI
     fn clear_logs(&mut self) -> Result<(), String> {
         self.log_messages.clear();
+        self.view_floor = 0;
+        for count in self.source_line_counts.values_mut() {
+            *count = 0;
+        }
+        self.updated_source_stats
+            .extend(self.source_line_counts.keys().copied());
+        for filter in &mut self.filters {
+            filter.match_count = MatchCount::default();
+        }
+        self.send_filter_list();
         let _ = self
             .command_response_tx
             .send(UiCommand::UpdateLogs(Vec::new()));
@@ -297,6 +868,16 @@ impl Commander {
     /// process them into log messages (strings). It also applies all the defined filters and
     /// let's the UI know that a new message has been received.
     fn cmd_parse_bytes(&mut self, id: u32, bytes: Vec<u8>) -> Result<(), String> {
+        self.mark_source_activity(id);
+
+        // Paused sources keep their data aside, or drop it
+        if let Some(buffer) = self.paused_sources.get_mut(&id) {
+            if self.app_cfg.buffer_paused_sources {
+                buffer.extend(bytes);
+            }
+            return Ok(());
+        }
+
         // Get current bytes
         let idx = match self.get_source_idx(id) {
             Some(idx) => idx,
@@ -306,36 +887,47 @@ impl Commander {
             }
         };
 
+        // Binary frames, not text
+        if self.defmt_tables.contains_key(&id) {
+            return self.parse_defmt_bytes(id, idx, bytes);
+        }
+
         let mut log_bytes = match self.log_sources[idx].take_storage() {
             Some(bytes) => bytes,
             None => Vec::new(),
         };
+        let pending_len = log_bytes.len();
 
         // Append new bytes
         let bytes_len = bytes.len();
         log_bytes.extend(bytes);
 
-        // Remove zeros
-        log_bytes.retain(|&b| b != 0);
+        // Remove zeros, unless they are what ends the lines
+        let delimiter = self.app_cfg.line_delimiter_byte();
+        if delimiter != 0 {
+            log_bytes.retain(|&b| b != 0);
+        }
 
         debug!(
             "Received {} bytes. Current storage state:\n{:?}",
             bytes_len, log_bytes
         );
 
-        // Get timestamp
-        let ts = LogTimestamp::now();
+        // Get timestamp, the lines started before this data can keep the one of their start
+        let batch_ts = LogTimestamp::now();
+        let first_byte_timestamps = self.app_cfg.first_byte_timestamps;
 
         let mut count = 0;
 
-        // Split at every newline
-        for raw_line in log_bytes.split_inclusive(|&c| c == b'\n') {
-            // If current line does not contain '\n' do not process it (is incomplete)
-            if !raw_line.contains(&b'\n') {
+        // Split at every delimiter
+        for raw_line in log_bytes.split_inclusive(|&c| c == delimiter) {
+            // If current line does not contain the delimiter do not process it (is incomplete)
+            if !raw_line.contains(&delimiter) {
                 break;
             }
 
             // Update count of used-up bytes
+            let line_start = count;
             count = count + raw_line.len();
 
             debug!("Bytes:\n{:?}", raw_line);
@@ -353,40 +945,316 @@ impl Commander {
                 }
             };
             */
-            let line = String::from_utf8_lossy(raw_line).to_string();
+            let mut line = String::from_utf8_lossy(raw_line).to_string();
+            if self.app_cfg.collapse_carriage_return {
+                line = collapse_carriage_return(&line);
+            }
 
-            debug!("Line: {}", &line);
+            // Lines are always stored ending in `\n`, whatever delimiter the source uses
+            if delimiter != b'\n' {
+                line.pop();
+                line.push('\n');
+            }
 
-            // Store it
-            self.log_messages.push(LogMessage {
-                timestamp: ts,
-                source_id: id as i32,
-                message: line.clone(),
-                style: Style::default().add_modifier(Modifier::DIM),
-            });
+            debug!("Line: {}", &line);
 
-            // If we are streaming logs to a file, add the line to it
-            if let Some(handle) = &mut self.stream_logs_file_handle {
-                if !self.stream_logs {
-                    error!("Handle not null even though streaming is disabled!!");
+            let ts = match first_byte_timestamps && line_start < pending_len {
+                true => self.line_start_timestamps.get(&id).copied().unwrap_or(batch_ts),
+                false => batch_ts,
+            };
+            let raw: Option<Arc<[u8]>> = self.app_cfg.keep_raw_bytes.then(|| Arc::from(raw_line));
+
+            // Recover the lines glued together, if configured
+            match &self.timestamp_split_regex {
+                Some(regex) => {
+                    // The bytes of every piece can not be told apart, only a line left whole
+                    // keeps them
+                    let lines = split_on_timestamp(regex, line);
+                    let raw = raw.filter(|_| lines.len() == 1);
+                    for line in lines {
+                        self.push_log_line(ts, id, line, raw.clone());
+                    }
                 }
-
-                let _ = handle.write_all(line.as_bytes());
-            }
-
-            // Apply filters
-            if let Some(log_message) = self.apply_filters(ts, id as i32, line.to_string()) {
-                let _ = self.log_message_tx.send(log_message);
+                None => self.push_log_line(ts, id, line, raw),
             }
         }
 
         // Let's try to be as ineficient as possible
         let (_, b) = log_bytes.split_at(count);
+
+        // A `\r` in the pending bytes means something (i.e. a progress bar) is being updated
+        // in place, show it as it goes instead of waiting for the line to be completed
+        if self.app_cfg.collapse_carriage_return && b.contains(&b'\r') {
+            let line = collapse_carriage_return(&String::from_utf8_lossy(b));
+            let partial_log = self.apply_filters(batch_ts, id as i32, line, LogFilterScope::View);
+            self.partial_line_sources.insert(id);
+            let _ = self
+                .command_response_tx
+                .send(UiCommand::UpdatePartialLog(id, partial_log));
+        } else {
+            self.clear_partial_line(id);
+        }
+
         self.log_sources[idx].set_storage(Vec::from(b));
+        match b.is_empty() {
+            true => self.incomplete_line_since.remove(&id),
+            false => self.incomplete_line_since.insert(id, Instant::now()),
+        };
+
+        // Keep when the pending line started, unless it did with this data
+        if first_byte_timestamps {
+            if b.is_empty() {
+                self.line_start_timestamps.remove(&id);
+            } else if count < pending_len {
+                self.line_start_timestamps.entry(id).or_insert(batch_ts);
+            } else {
+                self.line_start_timestamps.insert(id, batch_ts);
+            }
+        }
+
+        // Do not let a source that never sends a newline grow the storage forever
+        let max_line_length = self.app_cfg.max_line_length;
+        if max_line_length > 0 && b.len() > max_line_length {
+            warn!("Source {} exceeded {} bytes without newline", id, max_line_length);
+            self.flush_source(id, true);
+        }
+
+        Ok(())
+    }
+
+    /// Remove the in-progress line of a source from the UI, if any
+    fn clear_partial_line(&mut self, id: u32) {
+        if self.partial_line_sources.remove(&id) {
+            let _ = self
+                .command_response_tx
+                .send(UiCommand::UpdatePartialLog(id, None));
+        }
+    }
+
+    /// Show the lines without newline of the sources that received nothing for
+    /// `incomplete_line_timeout_ms`, marked as incomplete
+    ///
+    /// Otherwise output that never ends its line (i.e. a `> ` prompt) would not be seen. The
+    /// rest of the line, if it ever comes, is shown as a line of its own. Paused sources are
+    /// left alone, and so are the ones whose line is already shown as it goes (i.e. a progress
+    /// bar updated with `\r`).
+    fn flush_incomplete_lines(&mut self) {
+        let timeout = Duration::from_millis(self.app_cfg.incomplete_line_timeout_ms);
+        let stale: Vec<u32> = self
+            .incomplete_line_since
+            .iter()
+            .filter(|(id, since)| {
+                since.elapsed() >= timeout
+                    && !self.paused_sources.contains_key(id)
+                    && !self.partial_line_sources.contains(id)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        for id in stale {
+            debug!("Source {} left a line without newline for {:?}", id, timeout);
+            self.flush_source(id, true);
+        }
+    }
+
+    /// Register a new log line
+    ///
+    /// Store it, stream it to the output file if needed and, if it survives the filters, let
+    /// the UI know about it.
+    fn push_log_line(&mut self, ts: LogTimestamp, id: u32, line: String, raw: Option<Arc<[u8]>>) {
+        *self.source_line_counts.entry(id).or_insert(0) += 1;
+        self.updated_source_stats.insert(id);
+        if !self.notify_patterns.is_empty() {
+            self.check_notify_patterns(id, &line);
+        }
+        if !self.match_triggers.is_empty() {
+            self.check_match_triggers(id, &line);
+        }
+        self.push_log_message(ts, id as i32, line, raw);
+    }
+
+    /// Add a note from the user to the logs
+    ///
+    /// It is handled as any other log line (filtered, searched, exported), but it comes from its
+    /// own source and it is styled differently.
+    fn insert_note(&mut self, text: String) -> Result<(), String> {
+        self.push_log_message(LogTimestamp::now(), NOTE_SOURCE_ID, format!("NOTE: {}\n", text), None);
+        Ok(())
+    }
+
+    /// Record an event (i.e. a source connecting) in the logs
+    ///
+    /// It comes from its own source and style, and starts with `@status` so it can be told
+    /// apart from device output and filtered out.
+    pub(crate) fn push_status_line(&mut self, text: String) {
+        self.push_log_message(
+            LogTimestamp::now(),
+            STATUS_SOURCE_ID,
+            format!("{}{}\n", STATUS_LINE_PREFIX, text),
+            None,
+        );
+    }
 
+    /// Print the configuration in effect into the log view
+    ///
+    /// It is only shown, not stored, so it does not end up in the exported logs
+    fn dump_config(&self) -> Result<(), String> {
+        let app_cfg = serde_yaml::to_string(&self.app_cfg)
+            .unwrap_or_else(|e| format!("# Unable to serialize it: {}\n", e));
+        let mut text = format!(
+            "# Application configuration ({})\n{}",
+            ApplicationConfiguration::path().display(),
+            app_cfg
+        );
+        match &self.target_cfg {
+            Some(target_cfg) => {
+                let target_cfg = serde_yaml::to_string(target_cfg)
+                    .unwrap_or_else(|e| format!("# Unable to serialize it: {}\n", e));
+                let origin = match &self.active_profile {
+                    Some(name) => format!("profile {}", name),
+                    None => String::from(TARGET_CFG_PATH),
+                };
+                text.push_str(&format!("# Target configuration ({})\n{}", origin, target_cfg));
+            }
+            None => text.push_str(&format!("# Target configuration: {} not loaded\n", TARGET_CFG_PATH)),
+        }
+
+        let timestamp = LogTimestamp::now();
+        for line in text.lines() {
+            let _ = self.log_message_tx.send(LogMessage {
+                timestamp,
+                source_id: APP_SOURCE_ID,
+                message: format!("{}\n", line),
+                style: LogMessage::default_style(),
+                highlighted: false,
+                focused: false,
+                raw: None,
+            });
+        }
         Ok(())
     }
 
+    /// Parse the `source_colors` of the configuration, skipping (and reporting) the unknown
+    /// colors
+    fn load_source_colors(&mut self) {
+        let mut errors = Vec::new();
+        for (source, color) in &self.app_cfg.source_colors {
+            match Color::from_str(color) {
+                Ok(color) => self.source_colors.push((source.clone(), color)),
+                Err(_) => errors.push(format!("unknown color <{}> of <{}>", color, source)),
+            }
+        }
+
+        if !errors.is_empty() {
+            error!("Skipped source colors: {}", errors.join(", "));
+            let _ = self.command_response_tx.send(UiCommand::ErrorMessage {
+                message: format!("Skipped source colors, {}", errors.join(", ")),
+            });
+        }
+    }
+
+    /// Style of the logs of a source not affected by any filter, its `source_colors` entry if
+    /// it has one
+    pub(crate) fn source_style(&self, id: i32) -> Style {
+        if self.source_colors.is_empty() || id < 0 {
+            return LogMessage::base_style(id);
+        }
+
+        // An entry for the id is more specific than one for the name
+        let id_string = id.to_string();
+        let name = self.source_id_string(id);
+        self.source_colors
+            .iter()
+            .find(|(source, _)| *source == id_string)
+            .or_else(|| self.source_colors.iter().find(|(source, _)| Some(source) == name.as_ref()))
+            .map_or(LogMessage::base_style(id), |(_, color)| Style::default().fg(*color))
+    }
+
+    /// Store a log message, stream it and send it to the UI
+    fn push_log_message(&mut self, ts: LogTimestamp, id: i32, line: String, raw: Option<Arc<[u8]>>) {
+        self.count_filter_matches(&line);
+
+        // Store it, dropping the oldest log if full
+        self.log_messages.push_back(LogMessage {
+            timestamp: ts,
+            source_id: id,
+            message: line.clone(),
+            style: self.source_style(id),
+            highlighted: false,
+            focused: false,
+            raw: raw.clone(),
+        });
+        if self.app_cfg.max_log_lines > 0 && self.log_messages.len() > self.app_cfg.max_log_lines {
+            self.log_messages.pop_front();
+            self.view_floor = self.view_floor.saturating_sub(1);
+        }
+
+        // If we are streaming logs to files, add the line to them
+        if !self.output_streams.is_empty() {
+            let filtered = self.apply_filters(ts, id, line.clone(), LogFilterScope::Export);
+            for stream in &mut self.output_streams {
+                let message = match stream.raw {
+                    true => Some(&line),
+                    false => filtered.as_ref().map(|log| &log.message),
+                };
+                if let Some(message) = message {
+                    let _ = stream.file.write_all(message.as_bytes());
+                }
+            }
+        }
+
+        // Apply filters
+        if let Some(mut log_message) = self.apply_filters(ts, id, line, LogFilterScope::View) {
+            log_message.raw = raw;
+            let _ = self.log_message_tx.send(log_message);
+        }
+    }
+
+    /// Flush the pending bytes of a log source
+    ///
+    /// Bytes that did not get a terminating `\n` yet are kept in the source storage, waiting for
+    /// the rest of the line. When that is never going to happen (i.e. the source is being
+    /// disconnected) emit them as a line so they are not lost, marked as incomplete if
+    /// `mark_incomplete` is set. Otherwise the source is known to have ended cleanly (i.e. the
+    /// last line of a file without a trailing delimiter).
+    pub(crate) fn flush_source(&mut self, id: u32, mark_incomplete: bool) {
+        self.incomplete_line_since.remove(&id);
+        let line_start = self.line_start_timestamps.remove(&id);
+        let idx = match self.get_source_idx(id) {
+            Some(idx) => idx,
+            None => return,
+        };
+
+        let log_bytes = match self.log_sources[idx].take_storage() {
+            Some(bytes) if !bytes.is_empty() => bytes,
+            _ => return,
+        };
+
+        // Part of a defmt frame can not be decoded, let alone shown as text
+        if self.defmt_tables.contains_key(&id) {
+            debug!("Dropping {} bytes of an incomplete defmt frame of source {}", log_bytes.len(), id);
+            return;
+        }
+
+        debug!("Flushing {} pending bytes of source {}", log_bytes.len(), id);
+
+        let mut line = String::from_utf8_lossy(&log_bytes).to_string();
+        if self.app_cfg.collapse_carriage_return {
+            line = collapse_carriage_return(&line);
+        }
+        self.clear_partial_line(id);
+        let marker = match mark_incomplete {
+            true => INCOMPLETE_LINE_MARKER,
+            false => "",
+        };
+        let raw: Option<Arc<[u8]>> = self.app_cfg.keep_raw_bytes.then(|| Arc::from(log_bytes));
+        self.push_log_line(
+            line_start.filter(|_| self.app_cfg.first_byte_timestamps).unwrap_or_else(LogTimestamp::now),
+            id,
+            format!("{}{}\n", line.trim_end_matches('\r'), marker),
+            raw,
+        );
+    }
+
     /// Implementation for Command::GetProbes
     ///
     /// Reinitialize all the probe/target information and use it to generate a vector of `TargetInformation`, which
@@ -394,15 +1262,28 @@ impl Commander {
     /// Self reveiew: If this was better it would be nasty, currently is just... welp.
     fn cmd_refresh_probe_info(&mut self) -> Result<(), String> {
         info!("Refresh probe information");
+        self.last_probe_refresh = Some(Instant::now());
 
         if self.target_cfg.is_none() {
             let _ = self.command_response_tx.send(UiCommand::TextMessage { message: "No .gadget.yaml file provided".to_string() });
             return Ok(());
         }
 
+        // Without the probe list nothing is known about the targets, so leave the sources as
+        // they are
+        let probes = match self.list_probes() {
+            Ok(probes) => probes,
+            Err(e) => {
+                error!("{}", e);
+                let _ = self
+                    .command_response_tx
+                    .send(UiCommand::ErrorMessage { message: e });
+                return Ok(());
+            }
+        };
+
         // Add new probes
-        let lister = Lister::new();
-        for probe in lister.list_all() {
+        for probe in &probes {
             // More IDs than needed will be generated.
             let id = self.get_new_source_id();
 
@@ -410,7 +1291,7 @@ impl Commander {
                 .target_cfg.as_ref().unwrap()
                 .targets
                 .iter()
-                .filter(|t| t.probe_id == *probe.serial_number.as_ref().unwrap())
+                .filter(|t| probe.serial_number.as_ref() == Some(&t.probe_id))
                 .next()
             {
                 // Get current list of probe serial ids
@@ -434,89 +1315,216 @@ impl Commander {
                     }
                 }
 
+                // Targets able to log several ways use the one picked by the user, the first
+                // one until then
+                let backend_idx = self.target_backends.get(&target.name).copied().unwrap_or(0);
+                let backend = target.backend(backend_idx);
+                let backends_hint = match target.other_backends.is_empty() {
+                    true => None,
+                    false => Some(format!(
+                        "{} backends: {}. Using {}, pick another one with `:backend <n> {}`",
+                        target.name,
+                        target
+                            .backends()
+                            .enumerate()
+                            .map(|(idx, backend)| format!("{} {}", idx, backend.description()))
+                            .collect::<Vec<String>>()
+                            .join(", "),
+                        backend_idx,
+                        shell_words::quote(&target.name)
+                    )),
+                };
+
+                // Frames can only be decoded with the table of the firmware
+                let defmt_table = match backend {
+                    LogBackend::DefmtRtt { elf_path } => match Commander::defmt_table_from_elf(elf_path) {
+                        Ok(table) => Some(table),
+                        Err(e) => {
+                            error!("Skipping target {}: {}", target.name, e);
+                            let _ = self.command_response_tx.send(UiCommand::ErrorMessage {
+                                message: format!("Target <{}>: {}", target.name, e),
+                            });
+                            continue;
+                        }
+                    },
+                    _ => None,
+                };
+
                 let new_target = TargetMcu {
                     name: target.name.clone(),
                     mcu: target.processor.clone(),
                     probe_info: probe.clone(),
-                    backend: match &target.log_backend {
-                        LogBackend::Rtt { elf_path } => {
-                            LogBackendInformation::Rtt(Commander::rtt_block_from_elf(elf_path)?)
-                        }
-                        LogBackend::Uart { dev, baud } => {
-                            LogBackendInformation::Uart(dev.clone(), *baud)
+                    protocol: target.protocol,
+                    speed_khz: target.speed_khz,
+                    attach_under_reset: target.attach_under_reset,
+                    backend: match backend {
+                        LogBackend::Rtt { elf_path, .. } | LogBackend::DefmtRtt { elf_path } => match Commander::rtt_block_from_elf(elf_path) {
+                            Ok(address) => LogBackendInformation::Rtt(address),
+                            Err(e) => {
+                                error!("Skipping target {}: {}", target.name, e);
+                                let _ = self.command_response_tx.send(UiCommand::ErrorMessage {
+                                    message: format!("Target <{}>: {}", target.name, e),
+                                });
+                                continue;
+                            }
+                        },
+                        LogBackend::Uart { dev, baud, initial_dtr, initial_rts } => {
+                            LogBackendInformation::Uart(dev.clone(), *baud, *initial_dtr, *initial_rts)
                         }
                     },
                 };
 
                 // Also add the log source
-                match &target.log_backend {
-                    LogBackend::Rtt { elf_path: _ } => {
+                let mut channel_sources = Vec::new();
+                match backend {
+                    LogBackend::Rtt { channels, .. } => {
                         // Create the log source
-                        let new_source = RttSource::new(id, new_target, self.command_tx.clone());
+                        let mut new_source = RttSource::new(id, new_target, self.command_tx.clone());
+                        let channels = self.rtt_channels.get(&target.name).unwrap_or(channels);
+                        if let Some(channel) = channels.first() {
+                            new_source.set_up_channel(*channel);
+                        }
+                        let extra_channels: Vec<usize> = channels.iter().skip(1).copied().collect();
+                        for channel in &extra_channels {
+                            let channel_id = self.get_new_source_id();
+                            channel_sources.push(new_source.add_channel_source(*channel, channel_id));
+                        }
                         // Store it
                         self.log_sources.push(LogSource::RttSource(new_source));
                     }
-                    LogBackend::Uart { dev: _, baud: _ } => {
+                    LogBackend::DefmtRtt { .. } => {
+                        // defmt only uses channel 0
+                        let new_source = RttSource::new(id, new_target, self.command_tx.clone());
+                        self.log_sources.push(LogSource::RttSource(new_source));
+                    }
+                    LogBackend::Uart { .. } => {
                         // Create the log source
                         let new_source = UartSource::new(id, new_target, self.command_tx.clone(), self.command_response_tx.clone());
                         // Store it
                         self.log_sources.push(LogSource::UartSource(new_source));
                     }
                 }
+                if let Some(table) = defmt_table {
+                    self.defmt_tables.insert(id, table);
+                }
                 // Let UI know of the change
                 let _ = self.command_response_tx.send(UiCommand::AddNewSource(
                     id,
                     self.log_sources.last().unwrap().id_string(),
                 ));
+                for channel_source in channel_sources {
+                    let _ = self
+                        .command_response_tx
+                        .send(UiCommand::AddNewSource(channel_source.id(), channel_source.id_string()));
+                    self.log_sources.push(LogSource::RttChannel(channel_source));
+                }
+                if let Some(message) = backends_hint {
+                    let _ = self
+                        .command_response_tx
+                        .send(UiCommand::TextMessage { message });
+                }
             }
         }
 
         // Get current available probe serials
-        let available_probes_serials: Vec<String> = lister
-            .list_all()
+        let available_probes_serials: Vec<String> = probes
             .iter()
-            .map(|t| t.serial_number.clone().expect("No serial!"))
+            .filter_map(|t| t.serial_number.clone())
             .collect();
 
         // And remove the log sources that are not available anymore
-        for i in 0..self.log_sources.len() {
+        let mut i = 0;
+        while i < self.log_sources.len() {
             let keep_source = match &mut self.log_sources[i] {
                 LogSource::FileSource(_) => true,
                 LogSource::StdinSource(_) => true,
+                LogSource::AdbSource(_) => true,
+                LogSource::UdpSource(_) => true,
+                LogSource::Custom(_) => true,
                 LogSource::RttSource(s) => {
                     available_probes_serials.contains(s.get_probe_state().serial_number.as_ref().unwrap())
                 },
+                LogSource::RttChannel(s) => {
+                    available_probes_serials.contains(s.get_probe_state().serial_number.as_ref().unwrap())
+                },
                 LogSource::UartSource(s) => {
                     available_probes_serials.contains(s.get_probe_state().serial_number.as_ref().unwrap())
                 }
             };
 
-            if !keep_source {
-                self.log_sources[i].disconnect();
+            // A probe can briefly vanish during a USB re-enumeration, give it some refreshes
+            // to come back before removing its source
+            let id = self.log_sources[i].id();
+            let keep_source = match keep_source {
+                true => {
+                    self.missing_probe_refreshes.remove(&id);
+                    true
+                }
+                false => {
+                    let missing = self.missing_probe_refreshes.entry(id).or_insert(0);
+                    *missing += 1;
+                    *missing < self.app_cfg.probe_missing_refreshes
+                }
+            };
 
-                let _ = self
-                    .command_response_tx
-                    .send(UiCommand::RemoveSource(self.log_sources[i].id()));
+            if !keep_source {
+                self.drop_source(i, "detached");
+            } else {
+                i += 1;
             }
         }
 
+        self.connect_pending_sources()
+    }
 
-        Ok(())
+    /// List the connected debug probes
+    ///
+    /// Enumerating USB devices can panic or hang on some systems (i.e. permissions, udev), so
+    /// it is done in its own thread, given up after a while. A hung enumeration is not retried
+    /// until it returns, to not pile up stuck threads.
+    fn list_probes(&mut self) -> Result<Vec<DebugProbeInfo>, String> {
+        if self.probe_listing_busy.load(Ordering::Relaxed) {
+            return Err(String::from(
+                "Probe enumeration still hanging from the last attempt, check udev rules/permissions",
+            ));
+        }
+
+        self.probe_listing_busy.store(true, Ordering::Relaxed);
+        let busy = self.probe_listing_busy.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let probes = Lister::new().list_all();
+            busy.store(false, Ordering::Relaxed);
+            let _ = tx.send(probes);
+        });
+
+        match rx.recv_timeout(PROBE_LISTING_TIMEOUT) {
+            Ok(probes) => Ok(probes),
+            Err(RecvTimeoutError::Timeout) => Err(format!(
+                "Probe enumeration timed out after {} s, check udev rules/permissions",
+                PROBE_LISTING_TIMEOUT.as_secs()
+            )),
+            Err(RecvTimeoutError::Disconnected) => {
+                // The thread panicked before clearing the flag
+                self.probe_listing_busy.store(false, Ordering::Relaxed);
+                Err(String::from("Probe enumeration failed, check udev rules/permissions"))
+            }
+        }
     }
 
     /// For RTT targets, parse the elf file and get the RTT address
-    /// fn cmd_disconnect(&mut self, probe_serial: String) -> Result<(), String> {
-    fn rtt_block_from_elf(path: &String) -> Result<u64, String> {
-        let path = std::path::PathBuf::from(path);
-        let file_data = std::fs::read(path).unwrap();
+    pub fn rtt_block_from_elf(path: &String) -> Result<u64, String> {
+        let file_data =
+            std::fs::read(path).map_err(|e| format!("Unable to read <{}>: {}", path, e))?;
 
         let slice = file_data.as_slice();
-        let file = ElfBytes::<AnyEndian>::minimal_parse(slice).unwrap();
+        let file = ElfBytes::<AnyEndian>::minimal_parse(slice)
+            .map_err(|e| format!("<{}> is not a valid elf file: {}", path, e))?;
 
         let (symtab, strtab) = file
             .symbol_table()
-            .expect("Failed to read symbol table")
-            .expect("Failed to find symbol table");
+            .map_err(|e| format!("Failed to read the symbol table of <{}>: {}", path, e))?
+            .ok_or(format!("<{}> has no symbol table", path))?;
 
         // Does not seem to be possible to use fancy functions with iterators, so old school
 
@@ -535,7 +1543,47 @@ impl Commander {
         }
 
         error!("Unable to find _SEGGER_RTT symbol in elf file");
-        Err(String::new())
+        Err(format!("<{}> has no _SEGGER_RTT symbol", path))
     }
 
 }
+
+/// Split a line before every timestamp it contains, other than the one it starts with
+///
+/// Every resulting line keeps its own ending.
+fn split_on_timestamp(regex: &Regex, line: String) -> Vec<String> {
+    let starts: Vec<usize> = regex
+        .find_iter(&line)
+        .map(|m| m.start())
+        .filter(|start| *start > 0)
+        .collect();
+    if starts.is_empty() {
+        return vec![line];
+    }
+
+    let mut lines = Vec::new();
+    let mut previous = 0;
+    for start in starts {
+        lines.push(format!("{}\n", &line[previous..start]));
+        previous = start;
+    }
+    lines.push(line[previous..].to_string());
+    lines
+}
+
+/// Keep only what would be visible in a terminal after the carriage returns of a line
+///
+/// Every `\r` that is not part of the line ending moves the cursor back to the beginning of the
+/// line, so the last non-empty segment is the one that remains on screen. The line ending, if
+/// any, is preserved.
+fn collapse_carriage_return(line: &str) -> String {
+    let body = line.trim_end_matches(['\r', '\n']);
+    let ending = &line[body.len()..];
+
+    let visible = body
+        .rsplit('\r')
+        .find(|segment| !segment.is_empty())
+        .unwrap_or_default();
+
+    format!("{}{}", visible, ending)
+}
@@ -0,0 +1,223 @@
+use std::{
+    process::Stdio,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use notify_rust::Notification;
+use tracing::{error, info};
+
+use crate::log_source::LogSourceTrait;
+
+use super::{Command, Commander, UiCommand};
+
+/// Minimum time between two desktop notifications, matches in between are only counted
+const NOTIFY_MIN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Minimum time between two runs of the same `:on_match` command
+const TRIGGER_MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Shell command run when a line contains a pattern, set with `:on_match`
+pub struct MatchTrigger {
+    pattern: String,
+    command: String,
+
+    /// When was the command last started, and whether it is still running
+    last_run: Option<Instant>,
+    running: Arc<AtomicBool>,
+}
+
+impl Commander {
+
+    /// Notify the user (through the desktop) of the lines containing `pattern`
+    pub(crate) fn add_notify_pattern(&mut self, pattern: String) -> Result<(), String> {
+        let message = match self.notify_patterns.contains(&pattern) {
+            true => format!("Already notifying <{}>", pattern),
+            false => {
+                let message = format!("Notifying lines containing <{}>", pattern);
+                self.notify_patterns.push(pattern);
+                message
+            }
+        };
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::TextMessage { message });
+        Ok(())
+    }
+
+    /// Stop notifying any pattern
+    pub(crate) fn clear_notify_patterns(&mut self) -> Result<(), String> {
+        self.notify_patterns.clear();
+        self.suppressed_notifications = 0;
+        let _ = self.command_response_tx.send(UiCommand::TextMessage {
+            message: String::from("Notifications cleared"),
+        });
+        Ok(())
+    }
+
+    /// Send a desktop notification if a line received from a source matches a pattern
+    ///
+    /// Notifications are rate limited: the matches arriving too soon after the previous one
+    /// are counted, and the count is reported with the next notification.
+    pub(crate) fn check_notify_patterns(&mut self, id: u32, line: &str) {
+        let Some(pattern) = self.notify_patterns.iter().find(|p| line.contains(p.as_str())) else {
+            return;
+        };
+
+        if self
+            .last_notification
+            .is_some_and(|t| t.elapsed() < NOTIFY_MIN_INTERVAL)
+        {
+            self.suppressed_notifications += 1;
+            return;
+        }
+
+        let source = match self.get_source_idx(id) {
+            Some(idx) => self.log_sources[idx].id_string(),
+            None => format!("source {}", id),
+        };
+        let summary = format!("uberlog: <{}> in {}", pattern, source);
+        let mut body = line.trim_end().to_string();
+        if self.suppressed_notifications > 0 {
+            body.push_str(&format!("\n(+{} earlier matches)", self.suppressed_notifications));
+        }
+        self.last_notification = Some(Instant::now());
+        self.suppressed_notifications = 0;
+
+        send_desktop_notification(summary, body, self.command_tx.clone());
+    }
+
+    /// Tell about the matches held back by the rate limit, once a notification can be shown
+    pub(crate) fn report_suppressed_notifications(&mut self) -> Result<(), String> {
+        if self.suppressed_notifications == 0
+            || self
+                .last_notification
+                .is_some_and(|t| t.elapsed() < NOTIFY_MIN_INTERVAL)
+        {
+            return Ok(());
+        }
+
+        let summary = format!("uberlog: {} more matches", self.suppressed_notifications);
+        let body = format!("Patterns: {}", self.notify_patterns.join(", "));
+        self.last_notification = Some(Instant::now());
+        self.suppressed_notifications = 0;
+
+        send_desktop_notification(summary, body, self.command_tx.clone());
+        Ok(())
+    }
+
+    /// Allow `:on_match` commands for the rest of the session
+    pub(crate) fn allow_match_triggers(&mut self) -> Result<(), String> {
+        self.match_triggers_allowed = true;
+        let _ = self.command_response_tx.send(UiCommand::TextMessage {
+            message: String::from("Commands on matching lines allowed for this session"),
+        });
+        Ok(())
+    }
+
+    /// Run `command` whenever a line containing `pattern` arrives
+    ///
+    /// Running arbitrary commands from the logs has to be allowed first, once per session
+    pub(crate) fn add_match_trigger(&mut self, pattern: String, command: String) -> Result<(), String> {
+        if !self.match_triggers_allowed {
+            let _ = self.command_response_tx.send(UiCommand::ErrorMessage {
+                message: String::from(
+                    "Commands on matching lines are disabled, `:on_match --allow` enables them for this session",
+                ),
+            });
+            return Ok(());
+        }
+
+        let message = format!("Running <{}> on lines containing <{}>", command, pattern);
+        self.match_triggers.push(MatchTrigger {
+            pattern,
+            command,
+            last_run: None,
+            running: Arc::new(AtomicBool::new(false)),
+        });
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::TextMessage { message });
+        Ok(())
+    }
+
+    /// Remove every `:on_match` command
+    pub(crate) fn clear_match_triggers(&mut self) -> Result<(), String> {
+        self.match_triggers.clear();
+        let _ = self.command_response_tx.send(UiCommand::TextMessage {
+            message: String::from("Commands on matching lines cleared"),
+        });
+        Ok(())
+    }
+
+    /// Run the commands whose pattern a line received from a source matches
+    ///
+    /// The line is given to the command in `UBERLOG_LINE`, and the source name in
+    /// `UBERLOG_SOURCE`. A command is not started again while it is still running, or if it
+    /// was started too recently, so a flood of matches cannot spawn a flood of processes.
+    pub(crate) fn check_match_triggers(&mut self, id: u32, line: &str) {
+        let source = match self.get_source_idx(id) {
+            Some(idx) => self.log_sources[idx].id_string(),
+            None => format!("source {}", id),
+        };
+
+        for trigger in &mut self.match_triggers {
+            if !line.contains(&trigger.pattern)
+                || trigger.running.load(Ordering::Relaxed)
+                || trigger.last_run.is_some_and(|t| t.elapsed() < TRIGGER_MIN_INTERVAL)
+            {
+                continue;
+            }
+            trigger.last_run = Some(Instant::now());
+            trigger.running.store(true, Ordering::Relaxed);
+
+            let command = trigger.command.clone();
+            let running = trigger.running.clone();
+            let line = line.trim_end().to_string();
+            let source = source.clone();
+            let command_tx = self.command_tx.clone();
+            std::thread::spawn(move || {
+                info!("Running on_match command: {}", command);
+                let result = std::process::Command::new("sh")
+                    .arg("-c")
+                    .arg(&command)
+                    .env("UBERLOG_LINE", line)
+                    .env("UBERLOG_SOURCE", source)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status();
+                let error = match result {
+                    Ok(status) if status.success() => None,
+                    Ok(status) => Some(format!("on_match command <{}> failed: {}", command, status)),
+                    Err(e) => Some(format!("Unable to run on_match command <{}>: {}", command, e)),
+                };
+                if let Some(error) = error {
+                    error!("{}", error);
+                    let _ = command_tx.send(Command::PrintError(error));
+                }
+                running.store(false, Ordering::Relaxed);
+            });
+        }
+    }
+}
+
+/// Show a desktop notification
+///
+/// It is sent from its own thread so a slow notification daemon does not hold back the logs
+fn send_desktop_notification(summary: String, body: String, command_tx: std::sync::mpsc::Sender<Command>) {
+    std::thread::spawn(move || {
+        info!("Desktop notification: {}", summary);
+        let result = Notification::new().summary(&summary).body(&body).show();
+        if let Err(e) = result {
+            error!("Unable to send a desktop notification: {}", e);
+            let _ = command_tx.send(Command::PrintError(format!(
+                "Unable to send a desktop notification: {}",
+                e
+            )));
+        }
+    });
+}
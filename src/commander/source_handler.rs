@@ -1,6 +1,18 @@
-use crate::{commander::UiCommand, log_source::LogSourceTrait};
+use crate::{
+    commander::UiCommand,
+    configuration::{TARGET_CFG_PATH, expand_profile_env_vars},
+    log_source::{LogSource, LogSourceTrait, UartControlLine},
+};
 
-use super::Commander;
+use std::{
+    sync::mpsc::Sender,
+    time::{Duration, Instant},
+};
+
+use super::{Command, Commander};
+
+/// How long a connection by name waits for the probe of its target to be detected
+const PENDING_CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
 
 impl Commander {
 
@@ -27,17 +39,266 @@ impl Commander {
         None
     }
 
+    /// Name of a source, `None` for the ones not connected anymore and for messages of the
+    /// tool itself
+    pub(crate) fn source_id_string(&self, id: i32) -> Option<String> {
+        let id = u32::try_from(id).ok()?;
+        self.get_source_idx(id).map(|idx| self.log_sources[idx].id_string())
+    }
+
+    /// Tell the user a command targeted a source that does not exist (yet)
+    ///
+    /// The command is dropped, but not silently
+    fn report_missing_source(&self, id: u32) {
+        let _ = self.command_response_tx.send(UiCommand::TextMessage {
+            message: format!("Source {} not found, it may not have been detected yet", id),
+        });
+    }
+
+    /// Ids of the sources connected and disconnected along with a source, the extra RTT
+    /// channels of a target
+    fn channel_source_ids(&self, idx: usize) -> Vec<u32> {
+        match &self.log_sources[idx] {
+            LogSource::RttSource(s) => s.channel_source_ids(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Add a source defined outside of uberlog
+    ///
+    /// `build` gets the id of the new source and the channel its data is sent through, as
+    /// `Command::ParseLogBytes(id, bytes)`. The source is shown in the Log source view like the
+    /// rest, and connected from there. Returns its id.
+    pub fn add_custom_source<F>(&mut self, build: F) -> u32
+    where
+        F: FnOnce(u32, Sender<Command>) -> Box<dyn LogSourceTrait + Send>,
+    {
+        let id = self.get_new_source_id();
+        self.log_sources.push(LogSource::Custom(build(id, self.command_tx.clone())));
+
+        // Let UI know of the change
+        let _ = self.command_response_tx.send(UiCommand::AddNewSource(
+            id,
+            self.log_sources.last().unwrap().id_string(),
+        ));
+        id
+    }
+
     /// Connect a log source
     ///
     /// Identify the internal log source and connect it
     pub(crate) fn connect_log_source(&mut self, id: u32) -> Result<(), String> {
         if let Some(idx) = self.get_source_idx(id) {
+            self.mark_source_activity(id);
             self.log_sources[idx].connect();
+            if self.log_sources[idx].is_connected() {
+                self.push_status_line(format!("{} connected", self.log_sources[idx].id_string()));
+            }
             let _ = self
                 .command_response_tx
                 .send(UiCommand::SetConnectionSource(id, true));
+            for channel_id in self.channel_source_ids(idx) {
+                let _ = self
+                    .command_response_tx
+                    .send(UiCommand::SetConnectionSource(channel_id, true));
+            }
+        } else {
+            self.report_missing_source(id);
+        }
+
+        Ok(())
+    }
+
+    /// Connect a log source given its name
+    ///
+    /// For targets the name is the one defined in `.gadget.yaml`. A target whose probe was not
+    /// detected yet (i.e. right on startup) is waited for a few seconds. If there is no such
+    /// source the user is told why, so it can be fixed.
+    pub(crate) fn connect_log_source_by_name(&mut self, name: String) -> Result<(), String> {
+        if let Some(source) = self.log_sources.iter().find(|s| s.id_string() == name) {
+            return self.connect_log_source(source.id());
+        }
+
+        let in_cfg = self
+            .target_cfg
+            .as_ref()
+            .is_some_and(|cfg| cfg.targets.iter().any(|t| t.name == name));
+        if in_cfg && !self.pending_connections.iter().any(|(n, _)| *n == name) {
+            self.pending_connections
+                .push((name.clone(), Instant::now() + PENDING_CONNECTION_TIMEOUT));
+            self.start_pending_connection_retries();
+            let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                message: format!("Waiting for the probe of target <{}>", name),
+            });
+            return Ok(());
+        }
+
+        let message = match in_cfg {
+            true => format!("Probe of target <{}> is not connected", name),
+            false => format!("Target <{}> not found in .gadget.yaml", name),
+        };
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::TextMessage { message });
+
+        Ok(())
+    }
+
+    /// Drive a control line of the UART sources
+    ///
+    /// Applies to the source of the `target` given, or to every UART source if `None`
+    pub(crate) fn set_uart_line(
+        &mut self,
+        target: Option<String>,
+        line: UartControlLine,
+        level: bool,
+    ) -> Result<(), String> {
+        let mut results = Vec::new();
+        for source in &mut self.log_sources {
+            if let LogSource::UartSource(uart_source) = source
+                && target.as_ref().is_none_or(|t| *t == uart_source.id_string())
+            {
+                let result = match uart_source.set_control_line(line, level) {
+                    Ok(()) => format!("{} {}", line, if level { "on" } else { "off" }),
+                    Err(e) => format!("unable to set {}, {}", line, e),
+                };
+                results.push(format!("{}: {}", uart_source.id_string(), result));
+            }
+        }
+
+        let message = match results.is_empty() {
+            true => String::from("No matching UART target"),
+            false => results.join(" | "),
+        };
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::TextMessage { message });
+
+        Ok(())
+    }
+
+    /// Send the `loglevel_command` of `target` with `level`, or of every target having one if
+    /// `None`
+    ///
+    /// Only connected RTT and UART sources can be written to.
+    pub(crate) fn set_log_level(&mut self, target: Option<String>, level: String) -> Result<(), String> {
+        let commands: Vec<(String, String)> = self
+            .target_cfg
+            .as_ref()
+            .map(|cfg| {
+                cfg.targets
+                    .iter()
+                    .filter(|t| target.as_ref().is_none_or(|name| *name == t.name))
+                    .filter_map(|t| Some((t.name.clone(), t.loglevel_command.clone()?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if commands.is_empty() {
+            let message = match &target {
+                Some(name) => format!("{} has no loglevel_command in {}", name, TARGET_CFG_PATH),
+                None => format!("No target has a loglevel_command in {}", TARGET_CFG_PATH),
+            };
+            let _ = self.command_response_tx.send(UiCommand::TextMessage { message });
+            return Ok(());
+        }
+
+        let mut results = Vec::new();
+        for (name, command) in commands {
+            let bytes = command.replace("%level", &level).into_bytes();
+            let source = self.log_sources.iter_mut().find(|s| s.id_string() == name);
+            let result = match source {
+                Some(LogSource::RttSource(rtt_source)) => rtt_source.write(bytes),
+                Some(LogSource::UartSource(uart_source)) => uart_source.write(&bytes),
+                Some(_) => Err(String::from("unable to write to its source")),
+                None => Err(String::from("not connected")),
+            };
+            results.push(match result {
+                Ok(()) => format!("{}: log level {} requested", name, level),
+                Err(e) => format!("{}: unable to set the log level, {}", name, e),
+            });
+        }
+        let _ = self.command_response_tx.send(UiCommand::TextMessage {
+            message: results.join(" | "),
+        });
+
+        Ok(())
+    }
+
+    /// Read other RTT up channels of `target`, or of every RTT target if none is given
+    ///
+    /// The first channel is the logs of the target, every other one gets a source of its own,
+    /// as with the `channels` of the configuration. The sources of the channels read before
+    /// are removed. The choice is remembered for the session, also if the probe is detached and
+    /// attached again. Connected targets are reconnected to apply it.
+    pub(crate) fn set_rtt_channels(&mut self, target: Option<String>, channels: Vec<usize>) -> Result<(), String> {
+        let targets: Vec<(u32, String, bool)> = self
+            .log_sources
+            .iter()
+            .filter_map(|source| match source {
+                LogSource::RttSource(rtt_source)
+                    if target.as_ref().is_none_or(|t| *t == rtt_source.id_string()) =>
+                {
+                    Some((rtt_source.id(), rtt_source.id_string(), rtt_source.is_connected()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        if targets.is_empty() {
+            let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                message: String::from("No matching RTT target"),
+            });
+            return Ok(());
         }
 
+        let list = channels.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(", ");
+        let _ = self.command_response_tx.send(UiCommand::TextMessage {
+            message: format!(
+                "Reading RTT up channels {} of {}",
+                list,
+                targets.iter().map(|(_, name, _)| name.as_str()).collect::<Vec<_>>().join(", ")
+            ),
+        });
+
+        for (id, name, connected) in targets {
+            if connected {
+                self.disconnect_log_source(id)?;
+            }
+            self.rtt_channels.insert(name, channels.clone());
+
+            // The sources of the channels read so far go away, new ones are made for the rest
+            let Some(idx) = self.get_source_idx(id) else {
+                continue;
+            };
+            for channel_id in self.channel_source_ids(idx) {
+                if let Some(channel_idx) = self.get_source_idx(channel_id) {
+                    self.drop_source(channel_idx, "removed, RTT channels changed");
+                }
+            }
+            let mut channel_sources = Vec::new();
+            let channel_ids: Vec<u32> = channels.iter().skip(1).map(|_| self.get_new_source_id()).collect();
+            if let Some(idx) = self.get_source_idx(id)
+                && let LogSource::RttSource(rtt_source) = &mut self.log_sources[idx]
+            {
+                rtt_source.clear_channel_sources();
+                if let Some(channel) = channels.first() {
+                    rtt_source.set_up_channel(*channel);
+                }
+                for (channel, channel_id) in channels.iter().skip(1).zip(channel_ids) {
+                    channel_sources.push(rtt_source.add_channel_source(*channel, channel_id));
+                }
+            }
+            for channel_source in channel_sources {
+                let _ = self
+                    .command_response_tx
+                    .send(UiCommand::AddNewSource(channel_source.id(), channel_source.id_string()));
+                self.log_sources.push(LogSource::RttChannel(channel_source));
+            }
+
+            if connected {
+                self.connect_log_source(id)?;
+            }
+        }
         Ok(())
     }
 
@@ -47,21 +308,160 @@ impl Commander {
     pub(crate) fn disconnect_log_source(&mut self, id: u32) -> Result<(), String> {
         if let Some(idx) = self.get_source_idx(id) {
             self.log_sources[idx].disconnect();
+            self.flush_source(id, true);
+            self.push_status_line(format!("{} disconnected", self.log_sources[idx].id_string()));
             let _ = self
                 .command_response_tx
                 .send(UiCommand::SetConnectionSource(id, false));
+            for channel_id in self.channel_source_ids(idx) {
+                self.flush_source(channel_id, true);
+                let _ = self
+                    .command_response_tx
+                    .send(UiCommand::SetConnectionSource(channel_id, false));
+            }
+        } else {
+            self.report_missing_source(id);
+        }
+
+        Ok(())
+    }
+
+    /// Mark a log source as completed
+    ///
+    /// The source has no more data to provide (i.e. a file was read till the end), so it is
+    /// disconnected and the UI told about it.
+    pub(crate) fn complete_log_source(&mut self, id: u32) -> Result<(), String> {
+        if let Some(idx) = self.get_source_idx(id) {
+            self.log_sources[idx].disconnect();
+            self.flush_source(id, false);
+            self.push_status_line(format!("{} finished", self.log_sources[idx].id_string()));
+            let _ = self
+                .command_response_tx
+                .send(UiCommand::SetSourceCompleted(id));
+            let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                message: format!("Finished reading {}", self.log_sources[idx].id_string()),
+            });
         }
 
         Ok(())
     }
 
+    /// Pause or resume showing the logs of a source
+    ///
+    /// The source keeps being read while paused. Depending on `buffer_paused_sources` its data
+    /// is kept and parsed when resuming, or dropped.
+    pub(crate) fn toggle_pause_log_source(&mut self, id: u32) -> Result<(), String> {
+        let Some(idx) = self.get_source_idx(id) else {
+            self.report_missing_source(id);
+            return Ok(());
+        };
+        let name = self.log_sources[idx].id_string();
+        self.mark_source_activity(id);
+
+        let (paused, message) = match self.paused_sources.remove(&id) {
+            Some(buffered) => {
+                let message = match self.app_cfg.buffer_paused_sources {
+                    true => format!("<{}> resumed, {} bytes were buffered", name, buffered.len()),
+                    false => format!("<{}> resumed", name),
+                };
+                if !buffered.is_empty() {
+                    self.cmd_parse_bytes(id, buffered)?;
+                }
+                (false, message)
+            }
+            None => {
+                self.paused_sources.insert(id, Vec::new());
+                (true, format!("<{}> paused", name))
+            }
+        };
+
+        self.push_status_line(format!("{} {}", name, if paused { "paused" } else { "resumed" }));
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::SetSourcePaused(id, paused));
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::TextMessage { message });
+
+        Ok(())
+    }
+
     /// Reset MCU
-    pub(crate) fn reset_log_source(&mut self, id: u32) -> Result<(), String> {
+    ///
+    /// Resetting a source while it is connected can desync its session (i.e. RTT), so unless
+    /// `force` is set it is refused. When forced, the source is disconnected, reset and
+    /// connected again.
+    ///
+    /// Streams have no target to reset: a file is read again from the beginning, while stdin
+    /// and adb can not be rewound and only drop their incomplete line.
+    pub(crate) fn reset_log_source(&mut self, id: u32, force: bool) -> Result<(), String> {
         if let Some(idx) = self.get_source_idx(id) {
-            match self.log_sources[idx].reset() {
-                Ok(_) => return Ok(()),
-                Err(e) => return Err(e.to_string())
+            self.mark_source_activity(id);
+            if let LogSource::FileSource(_)
+            | LogSource::StdinSource(_)
+            | LogSource::AdbSource(_)
+            | LogSource::UdpSource(_) = self.log_sources[idx]
+            {
+                let response = match self.log_sources[idx].reset() {
+                    Ok(_) => UiCommand::TextMessage {
+                        message: match self.log_sources[idx] {
+                            LogSource::FileSource(_) => format!(
+                                "<{}> restarted from the beginning",
+                                self.log_sources[idx].id_string()
+                            ),
+                            _ => format!(
+                                "<{}> can not be rewound, dropped its incomplete line",
+                                self.log_sources[idx].id_string()
+                            ),
+                        },
+                    },
+                    Err(e) => UiCommand::ErrorMessage {
+                        message: format!("Unable to reset <{}>: {}", self.log_sources[idx].id_string(), e),
+                    },
+                };
+                let _ = self.command_response_tx.send(UiCommand::SetConnectionSource(
+                    id,
+                    self.log_sources[idx].is_connected(),
+                ));
+                let _ = self.command_response_tx.send(response);
+                return Ok(());
             }
+
+            let connected = self.log_sources[idx].is_connected();
+            if connected && !force {
+                let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                    message: format!(
+                        "<{}> is connected, disconnect it first or press `!` to force the reset",
+                        self.log_sources[idx].id_string()
+                    ),
+                });
+                return Ok(());
+            }
+
+            if connected {
+                self.disconnect_log_source(id)?;
+            }
+
+            let result = self.log_sources[idx].reset();
+            if result.is_ok() {
+                self.push_status_line(format!("{} reset", self.log_sources[idx].id_string()));
+            }
+
+            if connected {
+                self.connect_log_source(id)?;
+            }
+
+            let response = match result {
+                Ok(_) => UiCommand::TextMessage {
+                    message: format!("<{}> reset", self.log_sources[idx].id_string()),
+                },
+                Err(e) => UiCommand::ErrorMessage {
+                    message: format!("Unable to reset <{}>: {}", self.log_sources[idx].id_string(), e),
+                },
+            };
+            let _ = self.command_response_tx.send(response);
+        } else {
+            self.report_missing_source(id);
         }
 
         Ok(())
@@ -70,12 +470,248 @@ impl Commander {
     /// Reflash a MCU
     pub(crate) fn reflash_log_source(&mut self, id: u32) -> Result<(), String> {
         if let Some(idx) = self.get_source_idx(id) {
+            self.mark_source_activity(id);
             match self.log_sources[idx].reflash() {
                 Ok(_) => return Ok(()),
                 Err(e) => return Err(e.to_string())
             }
         }
+        self.report_missing_source(id);
+
+        Ok(())
+    }
+
+    /// Make the profile `name` the active target configuration
+    ///
+    /// The sources of the previous targets are disconnected and removed, then the probes are
+    /// looked up again against the targets of the new profile.
+    pub(crate) fn switch_profile(&mut self, name: String) -> Result<(), String> {
+        let Some(profile) = self.app_cfg.profiles.get(&name).cloned() else {
+            let mut names: Vec<&String> = self.app_cfg.profiles.keys().collect();
+            names.sort();
+            let message = match names.is_empty() {
+                true => format!("Profile <{}> not found, no profiles configured", name),
+                false => format!(
+                    "Profile <{}> not found, available: {}",
+                    name,
+                    names.iter().map(|n| n.as_str()).collect::<Vec<_>>().join(", ")
+                ),
+            };
+            let _ = self
+                .command_response_tx
+                .send(UiCommand::ErrorMessage { message });
+            return Ok(());
+        };
+        let profile = match expand_profile_env_vars(&profile) {
+            Ok(profile) => profile,
+            Err(e) => {
+                let _ = self.command_response_tx.send(UiCommand::ErrorMessage {
+                    message: format!("Unable to load profile <{}>: {}", name, e),
+                });
+                return Ok(());
+            }
+        };
+
+        // Drop the sources of the current targets, files and stdin are not tied to a profile
+        let mut i = 0;
+        while i < self.log_sources.len() {
+            match &self.log_sources[i] {
+                LogSource::RttSource(_) | LogSource::RttChannel(_) | LogSource::UartSource(_) => {
+                    self.drop_source(i, &format!("removed for profile {}", name));
+                }
+                _ => i += 1,
+            }
+        }
+
+        self.target_cfg = Some(profile);
+        self.active_profile = Some(name.clone());
+        self.start_idle_source_checks();
+        self.cmd_refresh_probe_info()?;
+
+        let matched: Vec<String> = self
+            .log_sources
+            .iter()
+            .filter(|s| matches!(s, LogSource::RttSource(_) | LogSource::UartSource(_)))
+            .map(|s| s.id_string())
+            .collect();
+        let message = match matched.is_empty() {
+            true => format!("Profile <{}> active, no target probe found", name),
+            false => format!("Profile <{}> active, targets found: {}", name, matched.join(", ")),
+        };
+        self.push_status_line(format!("profile {} active", name));
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::TextMessage { message });
+
+        Ok(())
+    }
+
+    /// Disconnect the source at `idx` and forget about it, the UI as well
+    ///
+    /// Every source removal goes through here, so nothing is left behind for its id. `reason`
+    /// follows the source name in the status line, i.e. `detached`.
+    pub(crate) fn drop_source(&mut self, idx: usize, reason: &str) {
+        let id = self.log_sources[idx].id();
+        let name = self.log_sources[idx].id_string();
+        self.log_sources[idx].disconnect();
+        self.flush_source(id, true);
 
+        self.partial_line_sources.remove(&id);
+        self.source_line_counts.remove(&id);
+        self.updated_source_stats.remove(&id);
+        self.paused_sources.remove(&id);
+        self.missing_probe_refreshes.remove(&id);
+        self.last_source_activity.remove(&id);
+        self.incomplete_line_since.remove(&id);
+        self.line_start_timestamps.remove(&id);
+        self.defmt_tables.remove(&id);
+
+        self.push_status_line(format!("{} {}", name, reason));
+        self.removed_source_names.insert(id, name);
+        self.log_sources.remove(idx);
+        let _ = self
+            .command_response_tx
+            .send(UiCommand::RemoveSource(id));
+    }
+
+    /// Log through backend number `backend` of `target` (as listed in `.gadget.yaml`, starting
+    /// with `log_backend`)
+    ///
+    /// The choice is remembered for the session. The source of the target is created again
+    /// for it, and connected if it was.
+    pub(crate) fn set_target_backend(&mut self, target: String, backend: usize) -> Result<(), String> {
+        let backend_count = self
+            .target_cfg
+            .as_ref()
+            .and_then(|cfg| cfg.targets.iter().find(|t| t.name == target))
+            .map(|t| t.backends().count());
+        let message = match backend_count {
+            None => Some(format!("Target <{}> not found in .gadget.yaml", target)),
+            Some(count) if backend >= count => {
+                Some(format!("Target <{}> has {} backends, numbered from 0", target, count))
+            }
+            _ => None,
+        };
+        if let Some(message) = message {
+            let _ = self
+                .command_response_tx
+                .send(UiCommand::TextMessage { message });
+            return Ok(());
+        }
+        self.target_backends.insert(target.clone(), backend);
+
+        // The extra RTT channels go along with the source
+        let mut connected = false;
+        if let Some(idx) = self.log_sources.iter().position(|s| s.id_string() == target) {
+            connected = self.log_sources[idx].is_connected();
+            let channel_ids = self.channel_source_ids(idx);
+            let reason = format!("removed to use backend {}", backend);
+            self.drop_source(idx, &reason);
+            for channel_id in channel_ids {
+                if let Some(idx) = self.get_source_idx(channel_id) {
+                    self.drop_source(idx, &reason);
+                }
+            }
+        }
+
+        let _ = self.command_response_tx.send(UiCommand::TextMessage {
+            message: format!("Using backend {} of <{}>", backend, target),
+        });
+        self.cmd_refresh_probe_info()?;
+        if connected {
+            self.connect_log_source_by_name(target)?;
+        }
+        Ok(())
+    }
+
+    /// Look for the probes again every second while some connection is waiting for them
+    fn start_pending_connection_retries(&self) {
+        let command_tx = self.command_tx.clone();
+        std::thread::spawn(move || {
+            // One extra round so the expired connections get reported
+            for _ in 0..=PENDING_CONNECTION_TIMEOUT.as_secs() {
+                std::thread::sleep(Duration::from_secs(1));
+                if command_tx.send(Command::RefreshProbeInfo).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Connect the sources waited for that showed up, and give up on the ones that took too
+    /// long. Called after every probe refresh
+    pub(crate) fn connect_pending_sources(&mut self) -> Result<(), String> {
+        let pending = std::mem::take(&mut self.pending_connections);
+        for (name, deadline) in pending {
+            if let Some(source) = self.log_sources.iter().find(|s| s.id_string() == name) {
+                self.connect_log_source(source.id())?;
+            } else if Instant::now() >= deadline {
+                let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                    message: format!("Probe of target <{}> is not connected", name),
+                });
+            } else {
+                self.pending_connections.push((name, deadline));
+            }
+        }
+        Ok(())
+    }
+
+    /// The source received data or the user acted on it, it is not idle
+    ///
+    /// The data of an extra RTT channel keeps the target connected too, it is the source of
+    /// the target that gets disconnected.
+    pub(crate) fn mark_source_activity(&mut self, id: u32) {
+        let now = Instant::now();
+        self.last_source_activity.insert(id, now);
+        let parent_id = self.log_sources.iter().find_map(|source| match source {
+            LogSource::RttChannel(channel_source) if channel_source.id_eq(id) => Some(channel_source.parent_id()),
+            _ => None,
+        });
+        if let Some(parent_id) = parent_id {
+            self.last_source_activity.insert(parent_id, now);
+        }
+    }
+
+    /// Time without activity the source of a target is disconnected after, if it opted in
+    fn idle_disconnect_timeout(&self, source: &LogSource) -> Option<Duration> {
+        let name = source.id_string();
+        self.target_cfg
+            .as_ref()?
+            .targets
+            .iter()
+            .find(|t| t.name == name)?
+            .idle_disconnect_s
+            .map(Duration::from_secs)
+    }
+
+    /// Disconnect the sources that got no data nor user interaction for too long
+    ///
+    /// Sources connected some other way than `connect_log_source` start counting the first
+    /// time they are checked.
+    pub(crate) fn disconnect_idle_sources(&mut self) -> Result<(), String> {
+        let now = Instant::now();
+        let mut idle = Vec::new();
+        for source in self.log_sources.iter().filter(|s| s.is_connected()) {
+            let Some(timeout) = self.idle_disconnect_timeout(source) else {
+                continue;
+            };
+            let last_activity = *self.last_source_activity.entry(source.id()).or_insert(now);
+            if now.duration_since(last_activity) >= timeout {
+                idle.push((source.id(), source.id_string(), timeout));
+            }
+        }
+
+        for (id, name, timeout) in idle {
+            self.disconnect_log_source(id)?;
+            self.last_source_activity.remove(&id);
+            let _ = self.command_response_tx.send(UiCommand::TextMessage {
+                message: format!(
+                    "{} disconnected after {} s without activity",
+                    name,
+                    timeout.as_secs()
+                ),
+            });
+        }
         Ok(())
     }
 }
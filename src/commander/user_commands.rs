@@ -1,36 +1,388 @@
-use std::sync::mpsc::Sender;
+use std::{str::FromStr, sync::mpsc::Sender};
 
-use super::Command;
+use ratatui::style::Color;
+
+use crate::{LogTimestamp, log_source::UartControlLine};
+
+use super::{Command, ExportFormat};
 
 /// Start streaming into a file
+///
+/// Appending `raw` after the path writes every log, ignoring the filters: `:stream_out path raw`
 pub fn stream_start(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
-    if input.len() != 1 {
-        return Err(String::from("Wrong arguments, expected just the path"));
+    let raw = match input.get(1).map(|s| s.as_str()) {
+        None => false,
+        Some("raw") => true,
+        Some(option) => return Err(format!("Unknown option <{}>, expected raw", option)),
+    };
+    if input.is_empty() || input.len() > 2 {
+        return Err(String::from("Wrong arguments, expected the path and optionally raw"));
     }
-    let _ = sender.send(Command::StreamLogs(true, input[0].clone()));
+    let _ = sender.send(Command::StartStream(input[0].clone(), raw));
     Ok(())
 }
 
-/// Stop streaming into a file
+/// Stop streaming into a file, or into all of them if no path is given
 pub fn stream_stop(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
-    if input.len() != 0 {
+    if input.len() > 1 {
         return Err(String::from("Too many arguments"));
     }
-    let _ = sender.send(Command::StreamLogs(false, String::new()));
+    let _ = sender.send(Command::StopStream(input.first().cloned()));
     Ok(())
 }
 
-/// Stop streaming into a file
+/// Write the logs stored so far into a file:
+/// `:export <path> [--format <syslog|json|raw>] [--view]`
+///
+/// Without format the messages are written alone. With `--view` the logs written are the ones
+/// the log view shows, instead of the ones `:stream_out` would write
+pub fn export(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    let usage = "Wrong arguments, expected the path and optionally --format <syslog|json|raw> and --view";
+    let Some(path) = input.first() else {
+        return Err(String::from(usage));
+    };
+
+    let mut format = ExportFormat::Raw;
+    let mut view = false;
+    let mut options = input[1..].iter();
+    while let Some(option) = options.next() {
+        match (option.as_str(), options.clone().next()) {
+            ("--format", Some(name)) => {
+                format = ExportFormat::parse(name)
+                    .ok_or(format!("Unknown format <{}>, expected syslog, json or raw", name))?;
+                options.next();
+            }
+            ("--view", _) => view = true,
+            _ => return Err(String::from(usage)),
+        }
+    }
+    let _ = sender.send(Command::ExportLogs(path.clone(), format, view));
+    Ok(())
+}
+
+/// Search for a log
+///
+/// Optionally a color can be provided for the search, and how many matches to jump. A named
+/// search keeps its own slot: `:find [--name <name>] word {color} {count}`
 pub fn find_log(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
-    if input.len() != 1 {
+    let mut name = None;
+    let mut args = Vec::new();
+    let mut input = input.into_iter();
+    while let Some(arg) = input.next() {
+        match arg.as_str() {
+            "--name" => match input.next() {
+                Some(value) if !value.is_empty() => name = Some(value),
+                _ => return Err(String::from("Wrong arguments, expected a name after --name")),
+            },
+            _ => args.push(arg),
+        }
+    }
+
+    if args.is_empty() {
+        return Err(String::from("Nothing to search for"));
+    }
+    if args.len() > 3 {
+        return Err(String::from("Too many arguments"));
+    }
+
+    let mut color = None;
+    let mut count = 1;
+    for arg in &args[1..] {
+        match arg.parse::<usize>() {
+            Ok(0) => return Err(String::from("The count starts at 1")),
+            Ok(n) => count = n,
+            Err(_) => {
+                color = Some(Color::from_str(arg).map_err(|_| format!("Unknown color <{}>", arg))?)
+            }
+        }
+    }
+
+    let _ = sender.send(Command::FindLog(args[0].clone(), color, count, name));
+    Ok(())
+}
+
+/// Drop the logs hidden by filters, and the filters
+///
+/// It is destructive, so it has to be confirmed: `:compact force`
+pub fn compact(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    let force = match input.first().map(|s| s.as_str()) {
+        None => false,
+        Some("force") => true,
+        Some(option) => return Err(format!("Unknown option <{}>, expected force", option)),
+    };
+    if input.len() > 1 {
+        return Err(String::from("Too many arguments"));
+    }
+    let _ = sender.send(Command::Compact(force));
+    Ok(())
+}
+
+/// Add a note to the logs: `:note flashed new firmware`
+pub fn note(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.is_empty() {
+        return Err(String::from("Nothing to note"));
+    }
+    let _ = sender.send(Command::InsertNote(input.join(" ")));
+    Ok(())
+}
+
+/// Print the configuration in effect into the log view
+pub fn dump_config(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if !input.is_empty() {
+        return Err(String::from("Too many arguments"));
+    }
+    let _ = sender.send(Command::DumpConfig);
+    Ok(())
+}
+
+/// Show the logs containing a pattern in a temporary buffer
+pub fn grep(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.is_empty() {
         return Err(String::from("Nothing to search for"));
     }
-    let _ = sender.send(Command::FindLog(input[0].clone()));
+    if input.len() > 1 {
+        return Err(String::from("Too many arguments"));
+    }
+    let _ = sender.send(Command::Grep(input[0].clone()));
+    Ok(())
+}
+
+/// Compare two captures side by side: `:diff <file_a> <file_b>`
+pub fn diff(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() != 2 {
+        return Err(String::from("Wrong arguments, expected two files"));
+    }
+    let _ = sender.send(Command::DiffFiles(input[0].clone(), input[1].clone()));
+    Ok(())
+}
+
+/// Scroll to the first log received at or after the given `HH:MM:SS` time
+pub fn goto_time(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() != 1 {
+        return Err(String::from("Wrong arguments, expected HH:MM:SS"));
+    }
+    let timestamp = LogTimestamp::parse(&input[0])
+        .ok_or(format!("Invalid time <{}>, expected HH:MM:SS", input[0]))?;
+    let _ = sender.send(Command::GotoTime(timestamp));
+    Ok(())
+}
+
+/// Show the logs as a hex dump, or as text again: `:hex [source id]`
+///
+/// Without a source it applies to every source.
+pub fn hex(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() > 1 {
+        return Err(String::from("Too many arguments, expected the source ID at most"));
+    }
+    let id = match input.first() {
+        Some(id) => Some(id.parse::<i32>().map_err(|_| format!("Invalid source ID <{}>", id))?),
+        None => None,
+    };
+    let _ = sender.send(Command::ToggleHexView(id));
+    Ok(())
+}
+
+/// Go to the logs of the current boot: `:since_boot [hide|off]`
+///
+/// `hide` also hides the logs before the boot, `off` shows them again
+pub fn since_boot(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    let command = match input.iter().map(|s| s.as_str()).collect::<Vec<_>>().as_slice() {
+        [] => Command::SinceBoot(false),
+        ["hide"] => Command::SinceBoot(true),
+        ["off"] => Command::ShowBeforeBoot,
+        _ => return Err(String::from("Wrong arguments, expected [hide|off]")),
+    };
+    let _ = sender.send(command);
+    Ok(())
+}
+
+/// Drive the DTR/RTS line of UART targets: `:uart_line <dtr|rts> <on|off> [target]`
+///
+/// Without target, every UART target is affected
+pub fn uart_line(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() < 2 || input.len() > 3 {
+        return Err(String::from("Wrong arguments, expected <dtr|rts> <on|off> [target]"));
+    }
+    let line = match input[0].to_lowercase().as_str() {
+        "dtr" => UartControlLine::Dtr,
+        "rts" => UartControlLine::Rts,
+        other => return Err(format!("Unknown line <{}>, expected dtr or rts", other)),
+    };
+    let level = match input[1].to_lowercase().as_str() {
+        "on" | "1" => true,
+        "off" | "0" => false,
+        other => return Err(format!("Unknown level <{}>, expected on or off", other)),
+    };
+    let _ = sender.send(Command::SetUartLine(input.get(2).cloned(), line, level));
+    Ok(())
+}
+
+/// Change the verbosity of the firmware: `:loglevel <level> [target]`
+///
+/// Sends the `loglevel_command` of the target, without target to every target having one. The
+/// rest of the input is the target name, which may contain spaces
+pub fn loglevel(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    let Some((level, target)) = input.split_first() else {
+        return Err(String::from("Wrong arguments, expected <level> [target]"));
+    };
+    let target = match target.is_empty() {
+        true => None,
+        false => Some(target.join(" ")),
+    };
+    let _ = sender.send(Command::SetLogLevel(target, level.clone()));
+    Ok(())
+}
+
+/// Pick how a target able to log several ways does it: `:backend <n> <target>`
+///
+/// Backends are numbered from 0 in the order of `.gadget.yaml`, `log_backend` first
+pub fn backend(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() != 2 {
+        return Err(String::from("Wrong arguments, expected <backend> <target>"));
+    }
+    let backend = input[0]
+        .parse::<usize>()
+        .map_err(|_| format!("Invalid backend <{}>, expected a number", input[0]))?;
+    let _ = sender.send(Command::SetBackend(input[1].clone(), backend));
+    Ok(())
+}
+
+/// Pick the RTT up channels to read, as the `channels` of the configuration:
+/// `:rtt_channel <n>[,<n>...] [target]`
+pub fn rtt_channel(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.is_empty() || input.len() > 2 {
+        return Err(String::from("Wrong arguments, expected <channel>[,<channel>...] [target]"));
+    }
+    let mut channels = Vec::new();
+    for channel in input[0].split(',') {
+        let channel = channel
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid channel <{}>", channel))?;
+        if channels.contains(&channel) {
+            return Err(format!("Channel {} listed twice", channel));
+        }
+        channels.push(channel);
+    }
+    let _ = sender.send(Command::SetRttChannels(input.get(1).cloned(), channels));
+    Ok(())
+}
+
+/// Get a desktop notification when a line containing the pattern arrives: `:notify <pattern>`
+pub fn notify(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.is_empty() {
+        return Err(String::from("Nothing to notify"));
+    }
+    let _ = sender.send(Command::AddNotifyPattern(input.join(" ")));
+    Ok(())
+}
+
+/// Stop every desktop notification
+pub fn notify_clear(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if !input.is_empty() {
+        return Err(String::from("Too many arguments"));
+    }
+    let _ = sender.send(Command::ClearNotifyPatterns);
+    Ok(())
+}
+
+/// Run a shell command when a line containing the pattern arrives:
+/// `:on_match <pattern> <command...>`
+///
+/// The words of the command are given to the shell as typed, so pipes and redirections work.
+/// It has to be allowed first, once per session, with `:on_match --allow`
+pub fn on_match(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() == 1 && input[0] == "--allow" {
+        let _ = sender.send(Command::AllowMatchTriggers);
+        return Ok(());
+    }
+    if input.len() < 2 {
+        return Err(String::from("Wrong arguments, expected <pattern> <command...>"));
+    }
+    let _ = sender.send(Command::AddMatchTrigger(
+        input[0].clone(),
+        input[1..].join(" "),
+    ));
+    Ok(())
+}
+
+/// Remove every `:on_match` command
+pub fn on_match_clear(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if !input.is_empty() {
+        return Err(String::from("Too many arguments"));
+    }
+    let _ = sender.send(Command::ClearMatchTriggers);
+    Ok(())
+}
+
+/// Switch the active target configuration to one of the profiles: `:profile <name>`
+pub fn profile(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() != 1 {
+        return Err(String::from("Wrong arguments, expected a profile name"));
+    }
+    let _ = sender.send(Command::SwitchProfile(input[0].clone()));
+    Ok(())
+}
+
+/// Save the current filters into a file: `:filter_save <path>`
+pub fn filter_save(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() != 1 {
+        return Err(String::from("Wrong arguments, expected just the path"));
+    }
+    let _ = sender.send(Command::SaveFilters(input[0].clone()));
+    Ok(())
+}
+
+/// Replace the current filters with the ones saved into a file: `:filter_load <path>`
+pub fn filter_load(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() != 1 {
+        return Err(String::from("Wrong arguments, expected just the path"));
+    }
+    let _ = sender.send(Command::LoadFilters(input[0].clone()));
+    Ok(())
+}
+
+/// Stream the logs of an Android/embedded Linux device: `:stream_adb [serial]`
+pub fn stream_adb(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() > 1 {
+        return Err(String::from("Too many arguments"));
+    }
+    let _ = sender.send(Command::StreamAdb(input.first().cloned()));
+    Ok(())
+}
+
+/// Stream the logs sent over UDP (i.e. syslog) to a port: `:stream_udp <port>`
+pub fn stream_udp(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() != 1 {
+        return Err(String::from("Wrong arguments, expected the port"));
+    }
+    let port = input[0]
+        .parse::<u16>()
+        .map_err(|_| format!("Invalid port <{}>", input[0]))?;
+    let _ = sender.send(Command::StreamUdp(port));
+    Ok(())
+}
+
+/// Stream a named pipe, creating it if needed: `:stream_fifo <path>`
+pub fn stream_fifo(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    if input.len() != 1 {
+        return Err(String::from("Wrong arguments, expected just the path"));
+    }
+    let _ = sender.send(Command::StreamFifo(input[0].clone()));
     Ok(())
 }
 
 /// Stream an input file
+///
+/// The file is followed for new data unless `--once` is given: `:stream_in --once path`. With
+/// `-f` only what is appended from now on is read, as `tail -f` does: `:stream_in -f path`
 pub fn stream_file(sender: &Sender<Command>, input: Vec<String>) -> Result<(), String> {
+    let (follow, tail, input) = match input.first().map(|s| s.as_str()) {
+        Some("--once") => (false, false, &input[1..]),
+        Some("-f") => (true, true, &input[1..]),
+        _ => (true, false, &input[..]),
+    };
+
     if input.is_empty() {
         return Err(String::from("path no"));
     }
@@ -39,7 +391,7 @@ pub fn stream_file(sender: &Sender<Command>, input: Vec<String>) -> Result<(), S
         return Err(String::from("Too many arguments"));
     }
 
-    let _ = sender.send(Command::StreamFile(input[0].clone()));
+    let _ = sender.send(Command::StreamFile(input[0].clone(), follow, tail));
 
     Ok(())
 }
@@ -1,21 +1,94 @@
 use std::{
+    collections::HashMap,
     fs::{self, File},
     io::Write,
+    path::{Path, PathBuf},
 };
-use anyhow::Context;
+use anyhow::{Context, anyhow};
+use probe_rs::probe::WireProtocol;
+use regex::{Captures, Regex};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum LogBackend {
-    Uart { dev: String, baud: u32 },
-    Rtt { elf_path: String },
+    Uart {
+        dev: String,
+        baud: u32,
+        /// DTR/RTS level set when opening the port, left untouched if not set. Some boards
+        /// reset or enter their bootloader depending on them
+        #[serde(default)]
+        initial_dtr: Option<bool>,
+        #[serde(default)]
+        initial_rts: Option<bool>,
+    },
+    Rtt {
+        elf_path: String,
+        /// Up channels read, channel 0 if empty. Every channel after the first one shows as a
+        /// source of its own
+        #[serde(default)]
+        channels: Vec<usize>,
+    },
+    /// RTT carrying defmt frames, decoded with the defmt table of the ELF. Read from up
+    /// channel 0
+    DefmtRtt { elf_path: String },
 }
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+
+impl LogBackend {
+    /// Short description, i.e. `UART (/dev/ttyACM0 @ 115200)`
+    pub fn description(&self) -> String {
+        match self {
+            LogBackend::Rtt { elf_path, .. } => format!("RTT ({})", elf_path),
+            LogBackend::DefmtRtt { elf_path } => format!("defmt RTT ({})", elf_path),
+            LogBackend::Uart { dev, baud, .. } => format!("UART ({} @ {})", dev, baud),
+        }
+    }
+}
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Target {
     pub name: String,
     pub processor: String,
     pub log_backend: LogBackend,
+
+    /// Other ways the target can log, picked at runtime with `:backend`
+    #[serde(default)]
+    pub other_backends: Vec<LogBackend>,
+
     pub probe_id: String,
+
+    /// Debug protocol (`Swd` or `Jtag`), probe-rs default if not set
+    #[serde(default)]
+    pub protocol: Option<WireProtocol>,
+
+    /// Debug clock speed in kHz, probe-rs default if not set
+    #[serde(default)]
+    pub speed_khz: Option<u32>,
+
+    /// Hold the target in reset while attaching, for firmware that reconfigures the debug pins
+    /// right away. The target is reset on every connection
+    #[serde(default)]
+    pub attach_under_reset: bool,
+
+    /// Disconnect the source after this many seconds without data nor user interaction, so the
+    /// probe is free for others. Never if not set
+    #[serde(default)]
+    pub idle_disconnect_s: Option<u64>,
+
+    /// Text sent to the firmware by `:loglevel` to change its verbosity, `%level` being replaced
+    /// by the level given. Over UART, or RTT down channel 0
+    #[serde(default)]
+    pub loglevel_command: Option<String>,
+}
+
+impl Target {
+    /// Backends of the target, `log_backend` first
+    pub fn backends(&self) -> impl Iterator<Item = &LogBackend> {
+        std::iter::once(&self.log_backend).chain(&self.other_backends)
+    }
+
+    /// Backend number `idx`, `log_backend` if there is no such one
+    pub fn backend(&self, idx: usize) -> &LogBackend {
+        self.backends().nth(idx).unwrap_or(&self.log_backend)
+    }
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -23,12 +96,21 @@ pub enum PowerSupply {
     Dp100 { voltage: f32, current: f32 },
     None(),
 }
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TargetConfiguration {
     //pub power: PowerSupply,
     pub targets: Vec<Target>,
 }
 
+/// Logs matching `pattern` (a regex) are highlighted in `color`, as with a highlight filter
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct HighlightRule {
+    pub pattern: String,
+    pub color: String,
+    #[serde(default)]
+    pub ignore_case: bool,
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct Alias {
     pub alias: String,
@@ -41,6 +123,183 @@ pub struct ApplicationConfiguration {
 
     /// Alias list
     pub alias_list: Vec<Alias>,
+
+    /// Treat a `\r` inside a line as "go back to the beginning of the line", so progress
+    /// indicators collapse into a single updating line
+    #[serde(default = "default_true")]
+    pub collapse_carriage_return: bool,
+
+    /// Byte ending every log line (i.e. `"\n"` or `"\0"`), given as a single character
+    #[serde(default = "default_line_delimiter")]
+    pub line_delimiter: String,
+
+    /// Regex matching the timestamp printed by the targets. When set, lines that got glued
+    /// together (i.e. newlines lost in a buffer overrun) are split again before every timestamp
+    #[serde(default)]
+    pub split_on_timestamp: Option<String>,
+
+    /// Maximum bytes buffered for a line without newline before it is shown anyway, 0 means
+    /// no limit
+    #[serde(default = "default_max_line_length")]
+    pub max_line_length: usize,
+
+    /// Milliseconds without new data after which the bytes of a line without newline are shown
+    /// anyway, i.e. a shell prompt. 0 (default) means never
+    #[serde(default)]
+    pub incomplete_line_timeout_ms: u64,
+
+    /// Keep the bytes every log was decoded from, so the hex view shows exactly what was
+    /// received. Costs as much memory again as the logs themselves
+    #[serde(default)]
+    pub keep_raw_bytes: bool,
+
+    /// Logs kept in memory, the oldest ones are dropped beyond it. 0 means no limit
+    #[serde(default)]
+    pub max_log_lines: usize,
+
+    /// Period of the automatic probe refresh in milliseconds, 0 disables it
+    #[serde(default)]
+    pub probe_refresh_interval_ms: u64,
+
+    /// Consecutive probe refreshes a probe has to be missing from before its source is removed
+    #[serde(default = "default_probe_missing_refreshes")]
+    pub probe_missing_refreshes: u32,
+
+    /// UI colors
+    #[serde(default)]
+    pub theme: ThemeConfiguration,
+
+    /// Whether the log view starts following new logs
+    #[serde(default = "default_true")]
+    pub follow_logs: bool,
+
+    /// Regex with named groups splitting structured logs into aligned columns, i.e.
+    /// `(?P<time>\S+) \| (?P<level>\w+) \| (?P<msg>.*)`
+    #[serde(default)]
+    pub column_template: Option<String>,
+
+    /// Color of the columns, by group name
+    #[serde(default)]
+    pub column_colors: HashMap<String, String>,
+
+    /// Color of the logs of a source, by source name or id
+    #[serde(default)]
+    pub source_colors: HashMap<String, String>,
+
+    /// Maximum times per second the screen is redrawn, 0 means no limit
+    #[serde(default = "default_max_fps")]
+    pub max_fps: u32,
+
+    /// Keep the data of a paused source and show it when resumed, instead of dropping it
+    #[serde(default = "default_true")]
+    pub buffer_paused_sources: bool,
+
+    /// Write a header with the session information (start time, connected targets) when
+    /// starting to stream logs into a file
+    #[serde(default = "default_true")]
+    pub stream_header: bool,
+
+    /// When a stream stops, write the lines the sources are still receiving into the file,
+    /// marked as incomplete
+    #[serde(default = "default_true")]
+    pub stream_partial_lines: bool,
+
+    /// Time zone of the log timestamps: `local`, `utc` or an offset like `+02:00`
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+
+    /// Key rotating through the screens: a single character, `Tab`, `BackTab` or `F1`-`F12`
+    #[serde(default = "default_cycle_screen_key")]
+    pub cycle_screen_key: String,
+
+    /// Highlight filters added on startup
+    #[serde(default)]
+    pub highlight_rules: Vec<HighlightRule>,
+
+    /// Text of the boot banner of the targets, `:since_boot` goes to the last line containing it
+    #[serde(default)]
+    pub boot_pattern: Option<String>,
+
+    /// Timestamp every line with the time its first byte was received at, instead of the time
+    /// the data completing it was received at
+    #[serde(default)]
+    pub first_byte_timestamps: bool,
+
+    /// adb binary used by `:stream_adb`
+    #[serde(default = "default_adb_path")]
+    pub adb_path: String,
+
+    /// Serial of the device `:stream_adb` reads when none is given, needed when several are
+    /// attached
+    #[serde(default)]
+    pub adb_serial: Option<String>,
+
+    /// Named target configurations, the active one is switched at runtime with `:profile`
+    #[serde(default)]
+    pub profiles: HashMap<String, TargetConfiguration>,
+}
+
+/// UI colors
+///
+/// A built-in theme is selected by name, and then any of its colors can be overridden. Colors
+/// are given by name (`red`, `lightblue`...) or as `#rrggbb`.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ThemeConfiguration {
+    /// Built-in theme: `dark` or `light`
+    pub name: String,
+    pub border: Option<String>,
+    pub title: Option<String>,
+    pub status_line: Option<String>,
+    pub selected_row: Option<String>,
+    pub search_highlight: Option<String>,
+    pub log_text: Option<String>,
+}
+
+impl Default for ThemeConfiguration {
+    fn default() -> Self {
+        ThemeConfiguration {
+            name: String::from("dark"),
+            border: None,
+            title: None,
+            status_line: None,
+            selected_row: None,
+            search_highlight: None,
+            log_text: None,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_line_delimiter() -> String {
+    String::from("\n")
+}
+
+fn default_max_line_length() -> usize {
+    4096
+}
+
+fn default_probe_missing_refreshes() -> u32 {
+    2
+}
+
+fn default_adb_path() -> String {
+    String::from("adb")
+}
+
+fn default_max_fps() -> u32 {
+    30
+}
+
+fn default_timezone() -> String {
+    String::from("local")
+}
+
+fn default_cycle_screen_key() -> String {
+    String::from("Tab")
 }
 
 impl ApplicationConfiguration {
@@ -68,13 +327,60 @@ impl ApplicationConfiguration {
                     expanded: String::from(":filter h"),
                 },
             ],
+            collapse_carriage_return: true,
+            line_delimiter: default_line_delimiter(),
+            split_on_timestamp: None,
+            max_line_length: default_max_line_length(),
+            incomplete_line_timeout_ms: 0,
+            keep_raw_bytes: false,
+            max_log_lines: 0,
+            probe_refresh_interval_ms: 0,
+            probe_missing_refreshes: default_probe_missing_refreshes(),
+            theme: ThemeConfiguration::default(),
+            follow_logs: true,
+            column_template: None,
+            column_colors: HashMap::new(),
+            source_colors: HashMap::new(),
+            max_fps: default_max_fps(),
+            buffer_paused_sources: true,
+            stream_header: true,
+            stream_partial_lines: true,
+            timezone: default_timezone(),
+            cycle_screen_key: default_cycle_screen_key(),
+            highlight_rules: Vec::new(),
+            boot_pattern: None,
+            first_byte_timestamps: false,
+            adb_path: default_adb_path(),
+            adb_serial: None,
+            profiles: HashMap::new(),
         }
     }
 
-    pub fn load_cfg() -> ApplicationConfiguration {
+    /// Byte ending every log line, `\n` if the configured one is not a single ASCII character
+    pub fn line_delimiter_byte(&self) -> u8 {
+        match self.line_delimiter.as_bytes() {
+            [delimiter] if delimiter.is_ascii() => *delimiter,
+            _ => b'\n',
+        }
+    }
+
+    /// Location of the application configuration file
+    pub fn path() -> PathBuf {
         // Please be aware that the warning about home_dir is benign, the project doc says in a future release will be removed
         let mut p = std::env::home_dir().expect("Unable to get HOME");
         p.push(".config/uberlog/config.yaml");
+        p
+    }
+
+    /// Location of the filters of the last session, saved as they change
+    pub fn session_filters_path() -> PathBuf {
+        let mut p = ApplicationConfiguration::path();
+        p.set_file_name("session_filters");
+        p
+    }
+
+    pub fn load_cfg() -> Result<ApplicationConfiguration, anyhow::Error> {
+        let p = ApplicationConfiguration::path();
 
         // If config does not exist, create it
         if !p.exists() {
@@ -90,15 +396,97 @@ impl ApplicationConfiguration {
             let _ = file.write_all(yaml_contents.as_bytes());
         }
 
-        let cfg_string = fs::read_to_string(p).expect("Unable to read configuration file");
-        let cfg: ApplicationConfiguration = serde_yaml::from_str(&cfg_string).expect("Bad");
-        cfg
+        let cfg_string = read_cfg_file(&p)?;
+        let cfg: ApplicationConfiguration = serde_yaml::from_str(&cfg_string)
+            .with_context(|| format!("Failed to parse {}", p.display()))?;
+        Ok(cfg)
+    }
+}
+
+/// Read a configuration file as text
+///
+/// A UTF-8 byte order mark (added by some editors) is skipped, other encodings are reported
+/// instead of failing to parse later with a cryptic error.
+fn read_cfg_file(path: &Path) -> Result<String, anyhow::Error> {
+    let bytes = fs::read(path).with_context(|| format!("Unable to read {}", path.display()))?;
+
+    if bytes.starts_with(&[0xFF, 0xFE]) || bytes.starts_with(&[0xFE, 0xFF]) {
+        return Err(anyhow!("{} is UTF-16 encoded, save it as UTF-8", path.display()));
+    }
+    let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(&bytes);
+
+    String::from_utf8(bytes.to_vec()).map_err(|e| {
+        anyhow!(
+            "{} is not valid UTF-8 (byte {}), save it as UTF-8",
+            path.display(),
+            e.utf8_error().valid_up_to()
+        )
+    })
+}
+
+/// Replace the `${VAR}` and `${VAR:-default}` references with the environment variables
+///
+/// The default is used when the variable is not set or is empty. A variable without default
+/// that is not set is an error, every missing one is reported at once.
+fn expand_env_vars(text: &str) -> Result<String, anyhow::Error> {
+    let reference = Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)(:-([^}]*))?\}").unwrap();
+
+    let mut missing = Vec::new();
+    let expanded = reference.replace_all(text, |caps: &Captures| {
+        let value = std::env::var(&caps[1]).ok().filter(|v| !v.is_empty());
+        match (value, caps.get(3)) {
+            (Some(value), _) => value,
+            (None, Some(default)) => default.as_str().to_string(),
+            (None, None) => {
+                missing.push(caps[1].to_string());
+                String::new()
+            }
+        }
+    });
+
+    if !missing.is_empty() {
+        return Err(anyhow!("environment variables not set: {}", missing.join(", ")));
+    }
+    Ok(expanded.into_owned())
+}
+
+/// Replace the environment variable references of the strings of a YAML value
+fn expand_value_env_vars(value: &mut serde_yaml::Value) -> Result<(), anyhow::Error> {
+    match value {
+        serde_yaml::Value::String(text) => *text = expand_env_vars(text)?,
+        serde_yaml::Value::Sequence(items) => {
+            for item in items {
+                expand_value_env_vars(item)?;
+            }
+        }
+        serde_yaml::Value::Mapping(entries) => {
+            for (_, item) in entries.iter_mut() {
+                expand_value_env_vars(item)?;
+            }
+        }
+        serde_yaml::Value::Tagged(tagged) => expand_value_env_vars(&mut tagged.value)?,
+        _ => (),
     }
+    Ok(())
 }
 
+/// Expand the environment variables of a profile, as done for `.gadget.yaml` when loading it
+///
+/// Profiles are part of the application configuration, they are expanded when activated so
+/// only the variables of the profile in use need to be set.
+pub fn expand_profile_env_vars(profile: &TargetConfiguration) -> Result<TargetConfiguration, anyhow::Error> {
+    let mut value = serde_yaml::to_value(profile)?;
+    expand_value_env_vars(&mut value)?;
+    Ok(serde_yaml::from_value(value)?)
+}
+
+/// Location of the target configuration file, relative to the working directory
+pub const TARGET_CFG_PATH: &str = ".gadget.yaml";
+
 pub fn load_target_cfg() -> Result<TargetConfiguration, anyhow::Error> {
-    let cfg_string = fs::read_to_string(".gadget.yaml").context("Unable to open .gadget.yaml file")?;
-    let cfg: TargetConfiguration = serde_yaml::from_str(&cfg_string).context("Failed to parse file")?;
+    let cfg_string = read_cfg_file(Path::new(TARGET_CFG_PATH))?;
+    let cfg_string = expand_env_vars(&cfg_string).context("Unable to load .gadget.yaml")?;
+    let cfg: TargetConfiguration = serde_yaml::from_str(&cfg_string).context("Failed to parse .gadget.yaml")?;
 
     tracing::info!("All good");
     Ok(cfg)
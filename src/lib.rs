@@ -1,5 +1,9 @@
-use chrono::Timelike;
-use ratatui::style::Style;
+use std::{str::FromStr, sync::{Arc, OnceLock}};
+
+use chrono::{FixedOffset, NaiveDateTime, Timelike};
+use ratatui::style::{Color, Modifier, Style};
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 pub mod command_parser;
 pub mod commander;
@@ -7,7 +11,58 @@ pub mod configuration;
 pub mod log_source;
 pub mod tui;
 
-#[derive(Clone, Copy)]
+/// Time zone the log timestamps are expressed in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimestampZone {
+    /// Host time zone
+    Local,
+    /// Fixed offset from UTC, UTC itself being a zero offset
+    Fixed(FixedOffset),
+}
+
+/// Time zone used by `LogTimestamp::now()`, set once at startup
+static TIMESTAMP_ZONE: OnceLock<TimestampZone> = OnceLock::new();
+
+impl TimestampZone {
+    /// Parse `local`, `utc` or an offset like `+02:00`
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.to_lowercase().as_str() {
+            "local" => Some(TimestampZone::Local),
+            "utc" | "z" => Some(TimestampZone::Fixed(FixedOffset::east_opt(0)?)),
+            offset => FixedOffset::from_str(offset).ok().map(TimestampZone::Fixed),
+        }
+    }
+
+    /// Current date and time in this zone
+    fn now(&self) -> NaiveDateTime {
+        match self {
+            TimestampZone::Local => chrono::Local::now().naive_local(),
+            TimestampZone::Fixed(offset) => chrono::Utc::now().with_timezone(offset).naive_local(),
+        }
+    }
+
+    /// Current date and time in this zone, in RFC 3339 format (offset included)
+    pub fn now_rfc3339(&self) -> String {
+        match self {
+            TimestampZone::Local => chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            TimestampZone::Fixed(offset) => chrono::Utc::now()
+                .with_timezone(offset)
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        }
+    }
+
+    /// Human readable description, i.e. `UTC` or `local (+02:00)`
+    pub fn description(&self) -> String {
+        match self {
+            TimestampZone::Local => format!("local ({})", chrono::Local::now().offset()),
+            TimestampZone::Fixed(offset) if offset.local_minus_utc() == 0 => String::from("UTC"),
+            TimestampZone::Fixed(offset) => format!("UTC{}", offset),
+        }
+    }
+}
+
+/// Timestamps are ordered chronologically within a day
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 pub struct LogTimestamp {
     hour: u32,
     minute: u32,
@@ -24,20 +79,90 @@ impl LogTimestamp {
         )
     }
 
-    /// Get current timestamp
+    /// Get current timestamp, in the configured time zone
     pub fn now() -> Self {
-        let now = chrono::Local::now();
+        let now = Self::zone().now();
         Self {
             hour: now.hour(),
             minute: now.minute(),
             second: now.second(),
-            ms: now.timestamp_subsec_millis(),
+            ms: (now.nanosecond() / 1_000_000).min(999),
         }
     }
 
+    /// Set the time zone timestamps are taken in, only the first call has effect
+    pub fn set_zone(zone: TimestampZone) {
+        let _ = TIMESTAMP_ZONE.set(zone);
+    }
+
+    /// Time zone timestamps are taken in, the host one unless configured otherwise
+    pub fn zone() -> TimestampZone {
+        TIMESTAMP_ZONE.get().copied().unwrap_or(TimestampZone::Local)
+    }
+
+    /// Date and time as `YYYY-MM-DD HH:MM:SS.mmm`
+    ///
+    /// Only the time of the day is kept, so the date is the last day that time was reached:
+    /// today, or yesterday for times later than now.
+    pub fn to_datetime_string(&self) -> String {
+        let now = Self::zone().now();
+        let mut date = now.date();
+        if self.second_count() > now.num_seconds_from_midnight() {
+            date = date.pred_opt().unwrap_or(date);
+        }
+        format!("{} {}", date.format("%Y-%m-%d"), self.to_string())
+    }
+
+    /// Date and time in RFC 3339 format, i.e. `2024-05-01T10:00:00.123+02:00`
+    ///
+    /// The date is found as in `to_datetime_string`, the offset is the one of the configured
+    /// time zone.
+    pub fn to_rfc3339_string(&self) -> String {
+        let offset = match Self::zone() {
+            TimestampZone::Local => *chrono::Local::now().offset(),
+            TimestampZone::Fixed(offset) => offset,
+        };
+        format!("{}{}", self.to_datetime_string().replacen(' ', "T", 1), offset)
+    }
+
     pub fn second_count(&self) -> u32 {
         self.hour * 3600 + self.minute * 60 + self.second
     }
+
+    /// Whether this timestamp is at or after `other`, with one second resolution
+    pub fn is_at_or_after(&self, other: &LogTimestamp) -> bool {
+        self.second_count() >= other.second_count()
+    }
+
+    /// Whether this timestamp is at or before `other`, with one second resolution
+    pub fn is_at_or_before(&self, other: &LogTimestamp) -> bool {
+        self.second_count() <= other.second_count()
+    }
+
+    /// Parse a `HH:MM:SS` (or `HH:MM`) string into a timestamp
+    pub fn parse(text: &str) -> Option<Self> {
+        let fields: Vec<u32> = text
+            .split(':')
+            .map(|f| f.parse::<u32>().ok())
+            .collect::<Option<Vec<u32>>>()?;
+
+        let (hour, minute, second) = match fields.as_slice() {
+            [hour, minute] => (*hour, *minute, 0),
+            [hour, minute, second] => (*hour, *minute, *second),
+            _ => return None,
+        };
+
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+
+        Some(Self {
+            hour,
+            minute,
+            second,
+            ms: 0,
+        })
+    }
 }
 
 #[derive(Clone)]
@@ -46,18 +171,235 @@ pub struct LogMessage {
     pub source_id: i32,
     pub message: String,
     pub style: Style,
+
+    /// Matched by a highlight filter, the log view can show where they are in the minimap
+    pub highlighted: bool,
+
+    /// Matched by a focus highlight filter, the log view can fold the logs that are not
+    pub focused: bool,
+
+    /// Bytes the message was decoded from, only kept if `keep_raw_bytes` is set
+    pub raw: Option<Arc<[u8]>>,
 }
 
-#[derive(Clone, PartialEq, Debug)]
+/// Source id of the notes added by the user with `:note`
+pub const NOTE_SOURCE_ID: i32 = -1;
+
+/// Source id of the information printed by uberlog itself into the log view (i.e. `:config`)
+pub const APP_SOURCE_ID: i32 = -2;
+
+/// Source id of the events uberlog records in the timeline (i.e. a source connecting)
+pub const STATUS_SOURCE_ID: i32 = -3;
+
+/// Start of the status lines, so they can be filtered like any other log (i.e. `e @status`)
+pub const STATUS_LINE_PREFIX: &str = "@status ";
+
+impl LogMessage {
+    /// Style of the logs not affected by any filter, the UI renders it using its theme
+    pub fn default_style() -> Style {
+        Style::default().add_modifier(Modifier::DIM)
+    }
+
+    /// Style of the notes added by the user
+    pub fn note_style() -> Style {
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::ITALIC)
+    }
+
+    /// Style of the events recorded by uberlog
+    pub fn status_style() -> Style {
+        Style::default().fg(Color::Magenta)
+    }
+
+    /// Style of the logs of a source not affected by any filter
+    pub fn base_style(source_id: i32) -> Style {
+        match source_id {
+            NOTE_SOURCE_ID => LogMessage::note_style(),
+            STATUS_SOURCE_ID => LogMessage::status_style(),
+            _ => LogMessage::default_style(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum LogFilterType {
     Exclusion,
     Inclusion,
     Highlighter,
+    /// Only keep lines with more characters than the given amount
+    LongerThan(usize),
+    /// Only keep lines with less characters than the given amount
+    ShorterThan(usize),
+    /// Only keep lines received at or after the given time
+    After(LogTimestamp),
+    /// Only keep lines received at or before the given time
+    Before(LogTimestamp),
+    /// Keep every line, only count the ones matching
+    Counter,
 }
 
-#[derive(Clone, Debug)]
+/// Where a filter is applied
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum LogFilterScope {
+    /// Only the logs shown on screen
+    #[default]
+    View,
+    /// Only the logs written to files
+    Export,
+    /// Everywhere
+    Both,
+}
+
+impl LogFilterScope {
+    /// Check if a filter with this scope is to be applied in `target`
+    pub fn applies_to(&self, target: LogFilterScope) -> bool {
+        *self == LogFilterScope::Both || *self == target
+    }
+}
+
+/// Two filters are equal if every field, style included, matches
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct LogFilter {
     pub kind: LogFilterType,
     pub msg: String,
+    #[serde(with = "filter_color")]
     pub style: Style,
+    pub scope: LogFilterScope,
+
+    /// Only for highlight filters, fold the logs not matched by them in the log view
+    pub focus: bool,
+
+    /// Group the filter belongs to. The filters of a group are applied together, as a single
+    /// step of the pipeline
+    pub group: Option<String>,
+
+    /// Set for regex filters, `msg` compiled once so it is not done for every log
+    #[serde(default)]
+    pub regex: Option<FilterRegex>,
+
+    /// Match regardless of the case, regexes are compiled accordingly
+    #[serde(default)]
+    pub ignore_case: bool,
+
+    /// Loaded from the `highlight_rules` of the configuration, instead of added by the user
+    #[serde(default)]
+    pub from_config: bool,
+
+    /// Only for counter filters, logs matched so far
+    #[serde(skip)]
+    pub match_count: MatchCount,
+
+    /// Disabled filters are kept in the list, but do nothing
+    #[serde(default = "filter_enabled_default")]
+    pub enabled: bool,
+
+    /// Only look for the keyword within these columns (characters) of the logs, first and
+    /// last, starting at 1
+    #[serde(default)]
+    pub columns: Option<(usize, usize)>,
+
+    /// Only apply to the logs of the source with this name, on top of the filters applying to
+    /// every source. The name, unlike the ID, is the same from one session to the next
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+fn filter_enabled_default() -> bool {
+    true
+}
+
+/// Runtime count of a counter filter, not part of what the filter is: two filters differing
+/// only in it are equal
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MatchCount(pub usize);
+
+impl PartialEq for MatchCount {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl LogFilter {
+    /// Whether the filter keyword (or pattern, for regex filters) is found in `message`
+    ///
+    /// With a column range only that part of the message is looked at, a message too short to
+    /// reach its end does not match.
+    pub fn matches(&self, message: &str) -> bool {
+        let message = match self.columns {
+            Some((first, last)) => {
+                let mut chars = message.char_indices().map(|(idx, _)| idx).chain([message.len()]);
+                match (chars.nth(first - 1), chars.nth(last - first)) {
+                    (Some(start), Some(end)) => &message[start..end],
+                    _ => return false,
+                }
+            }
+            None => message,
+        };
+
+        match &self.regex {
+            Some(regex) => regex.0.is_match(message),
+            None if self.msg.is_empty() => false,
+            None if self.ignore_case => message.to_lowercase().contains(&self.msg.to_lowercase()),
+            None => message.contains(&self.msg),
+        }
+    }
+}
+
+/// Compiled pattern of a regex filter, two of them are equal if their patterns are
+///
+/// Case-insensitivity is part of the pattern (`(?i)`), so it is saved along with it
+#[derive(Clone, Debug)]
+pub struct FilterRegex(pub Regex);
+
+impl FilterRegex {
+    pub fn new(pattern: &str, ignore_case: bool) -> Result<FilterRegex, regex::Error> {
+        match ignore_case {
+            true => Regex::new(&format!("(?i){}", pattern)).map(FilterRegex),
+            false => Regex::new(pattern).map(FilterRegex),
+        }
+    }
+}
+
+impl PartialEq for FilterRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Serialize for FilterRegex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for FilterRegex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<FilterRegex, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern)
+            .map(FilterRegex)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Filters only set the text color, saved by name (or as `#rrggbb`)
+mod filter_color {
+    use std::str::FromStr;
+
+    use ratatui::style::{Color, Style};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(style: &Style, serializer: S) -> Result<S::Ok, S::Error> {
+        match style.fg {
+            Some(color) => serializer.serialize_some(&color.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Style, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(color) => Color::from_str(&color)
+                .map(|color| Style::default().fg(color))
+                .map_err(|_| serde::de::Error::custom(format!("unknown color <{}>", color))),
+            None => Ok(Style::default()),
+        }
+    }
 }
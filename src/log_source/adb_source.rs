@@ -0,0 +1,330 @@
+use tracing::{debug, error, info, warn};
+
+use crate::commander::Command;
+
+use super::{LogSourceError, LogSourceTrait};
+
+use core::time;
+use std::{
+    io::Read,
+    process::{Child, Stdio},
+    sync::{Arc, Mutex, mpsc::Sender},
+    thread::{self, JoinHandle},
+};
+
+/// Time between checks for the device while it is missing
+const RETRY_PERIOD: time::Duration = time::Duration::from_secs(1);
+
+/// Length of the `MM-DD hh:mm:ss.mmm` timestamp logcat starts its lines with
+const LOGCAT_TIMESTAMP_LEN: usize = 18;
+
+pub struct AdbSource {
+    /// Handle of the thread reading data
+    handle: Option<JoinHandle<()>>,
+
+    /// Send channel to gracefully shutdown the thread
+    thread_control_tx: Option<Sender<bool>>,
+
+    /// Send channel to Commander
+    command_tx: Sender<Command>,
+
+    /// Holds state
+    is_connected: bool,
+
+    /// adb binary to run
+    adb_path: String,
+
+    /// Serial of the device, needed when several are attached
+    serial: Option<String>,
+
+    /// Identifier of this source
+    id: u32,
+
+    /// Log processing storage
+    storage: Option<Vec<u8>>,
+}
+
+impl AdbSource {
+    pub fn new(
+        id: u32,
+        adb_path: String,
+        serial: Option<String>,
+        command_tx: Sender<Command>,
+    ) -> AdbSource {
+        AdbSource {
+            handle: None,
+            thread_control_tx: None,
+            command_tx,
+            is_connected: false,
+            adb_path,
+            serial,
+            id,
+            storage: None,
+        }
+    }
+}
+
+/// Whether the device is attached and ready
+fn device_state(adb_path: &str, serial: Option<&String>) -> std::io::Result<bool> {
+    let mut command = std::process::Command::new(adb_path);
+    if let Some(serial) = serial {
+        command.args(["-s", serial]);
+    }
+    let output = command.arg("get-state").stdin(Stdio::null()).output()?;
+    Ok(output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "device")
+}
+
+/// Timestamp of a logcat line, if it starts with one
+fn logcat_timestamp(line: &[u8]) -> Option<String> {
+    let line = line.get(..LOGCAT_TIMESTAMP_LEN)?;
+    let is_timestamp = line.iter().enumerate().all(|(i, b)| match i {
+        2 => *b == b'-',
+        5 => *b == b' ',
+        8 | 11 => *b == b':',
+        14 => *b == b'.',
+        _ => b.is_ascii_digit(),
+    });
+    is_timestamp.then(|| String::from_utf8_lossy(line).into_owned())
+}
+
+/// Start `adb logcat`, with its output read by a thread of its own until it exits
+///
+/// `last_timestamp` holds the timestamp of the last line read. When the device comes back,
+/// logcat is started from it so the lines already shown are not read again from its buffer.
+fn spawn_logcat(
+    adb_path: &str,
+    serial: Option<&String>,
+    id: u32,
+    command_tx: Sender<Command>,
+    last_timestamp: Arc<Mutex<Option<String>>>,
+    reconnect: bool,
+) -> std::io::Result<Child> {
+    let mut command = std::process::Command::new(adb_path);
+    if let Some(serial) = serial {
+        command.args(["-s", serial]);
+    }
+    command.arg("logcat");
+    if reconnect {
+        // Without a timestamp, at least do not dump the whole buffer again
+        let since = last_timestamp.lock().ok().and_then(|ts| ts.clone());
+        command.args(["-T", since.as_deref().unwrap_or("1")]);
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Reading blocks, so it can not be done by the thread watching for the stop request.
+    // Killing the process ends it
+    if let Some(mut stdout) = child.stdout.take() {
+        thread::spawn(move || {
+            let mut buf = [0; 4096];
+            // Only the start of the line being read is kept, to get its timestamp
+            let mut line_start = Vec::with_capacity(LOGCAT_TIMESTAMP_LEN);
+            loop {
+                match stdout.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(count) => {
+                        debug!("Read {} bytes", count);
+                        for &b in &buf[..count] {
+                            if b == b'\n' {
+                                if let Some(ts) = logcat_timestamp(&line_start)
+                                    && let Ok(mut last) = last_timestamp.lock()
+                                {
+                                    *last = Some(ts);
+                                }
+                                line_start.clear();
+                            } else if line_start.len() < LOGCAT_TIMESTAMP_LEN {
+                                line_start.push(b);
+                            }
+                        }
+                        if command_tx.send(Command::ParseLogBytes(id, buf[..count].to_vec())).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        error!("adb read error <{}>", e);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+    Ok(child)
+}
+
+impl LogSourceTrait for AdbSource {
+    fn reflash(&mut self) -> Result<(), LogSourceError> {
+        Err(LogSourceError::NotImplemented)
+    }
+
+    fn connect(&mut self) {
+        // Validate status
+        if self.is_connected {
+            warn!("Already connected!");
+            return;
+        }
+
+        // Populate thread control channel
+        let (control_tx, control_rx) = std::sync::mpsc::channel();
+        self.thread_control_tx = Some(control_tx);
+
+        // Copy data for the thread to use
+        let command_tx = self.command_tx.clone();
+        let id = self.id;
+        let adb_path = self.adb_path.clone();
+        let serial = self.serial.clone();
+        let name = self.id_string();
+
+        // Define the thread
+        let handle = std::thread::spawn(move || {
+            info!("Thread started - AdbSource \"{}\" (ID {})", name, id);
+
+            // `adb logcat` exits when the device goes away, so it is started again until the
+            // source is disconnected. The user is only told about the changes
+            let mut child: Option<Child> = None;
+            let mut waiting_reported = false;
+            let last_timestamp = Arc::new(Mutex::new(None));
+            let mut reconnect = false;
+            loop {
+                // Check no message was received
+                if let Ok(false) = control_rx.try_recv() {
+                    info!("Stop streaming thread");
+                    break;
+                }
+
+                let exited = match child.as_mut().map(|c| c.try_wait()) {
+                    None => true,
+                    Some(Ok(status)) => status.is_some(),
+                    Some(Err(e)) => {
+                        error!("Unable to check adb: {}", e);
+                        true
+                    }
+                };
+                if !exited {
+                    thread::sleep(time::Duration::from_millis(100));
+                    continue;
+                }
+
+                if child.take().is_some() {
+                    info!("{} went away", name);
+                    let _ = command_tx.send(Command::PrintMessage(format!(
+                        "{}: device disconnected, waiting for it",
+                        name
+                    )));
+                    waiting_reported = true;
+                    thread::sleep(RETRY_PERIOD);
+                }
+
+                // `adb logcat` would wait for the device by itself, but then there is no telling
+                // whether it is streaming
+                let started = device_state(&adb_path, serial.as_ref()).and_then(|present| match present {
+                    true => spawn_logcat(
+                        &adb_path,
+                        serial.as_ref(),
+                        id,
+                        command_tx.clone(),
+                        last_timestamp.clone(),
+                        reconnect,
+                    )
+                    .map(Some),
+                    false => Ok(None),
+                });
+                match started {
+                    Ok(Some(new_child)) => {
+                        let _ = command_tx.send(Command::PrintMessage(format!("{}: streaming", name)));
+                        waiting_reported = false;
+                        reconnect = true;
+                        child = Some(new_child);
+                    }
+                    Ok(None) => {
+                        if !waiting_reported {
+                            let _ = command_tx.send(Command::PrintMessage(format!(
+                                "{}: no device found, waiting for it",
+                                name
+                            )));
+                            waiting_reported = true;
+                        }
+                        thread::sleep(RETRY_PERIOD);
+                    }
+                    Err(e) => {
+                        error!("Unable to run {}: {}", adb_path, e);
+                        let _ = command_tx.send(Command::PrintError(format!(
+                            "Unable to run `{}`: {}",
+                            adb_path, e
+                        )));
+                        // Nothing is streamed anymore, let the source be marked as disconnected
+                        let _ = command_tx.send(Command::SourceCompleted(id));
+                        break;
+                    }
+                }
+            }
+
+            if let Some(mut child) = child {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        });
+        self.handle = Some(handle);
+        self.is_connected = true;
+    }
+
+    fn disconnect(&mut self) {
+        info!("Disconnecting {}", self.id_string());
+
+        if let Some(channel) = self.thread_control_tx.take() {
+            match channel.send(false) {
+                Ok(_) => (),
+                Err(e) => error!("{:?}", e),
+            }
+        } else {
+            error!("Thread control channel is None");
+        }
+
+        // Wait for the thread to die, and remove the session
+        if let Some(t_handle) = self.handle.take() {
+            match t_handle.join() {
+                Ok(_) => (),
+                Err(e) => error!("{:?}", e),
+            }
+        } else {
+            error!("Thread handle is None");
+        }
+
+        self.is_connected = false;
+    }
+
+    fn is_connected(&self) -> bool {
+        self.is_connected
+    }
+
+    /// logcat can not be rewound, just drop the incomplete line
+    fn reset(&mut self) -> Result<(), LogSourceError> {
+        self.storage = None;
+        Ok(())
+    }
+
+    fn id_eq(&self, id: u32) -> bool {
+        self.id == id
+    }
+
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn id_string(&self) -> String {
+        match &self.serial {
+            Some(serial) => format!("ADB ({})", serial),
+            None => String::from("ADB"),
+        }
+    }
+
+    fn take_storage(&mut self) -> Option<Vec<u8>> {
+        self.storage.take()
+    }
+
+    fn set_storage(&mut self, bytes: Vec<u8>) {
+        self.storage = Some(bytes);
+    }
+}
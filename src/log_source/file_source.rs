@@ -6,8 +6,9 @@ use super::{LogSourceError, LogSourceTrait};
 
 use core::time;
 use std::{
-    io::{BufRead, BufReader},
-    path::PathBuf,
+    fs::{File, Metadata},
+    io::{self, BufRead, BufReader, Seek, SeekFrom},
+    path::{Path, PathBuf},
     sync::mpsc::Sender,
     thread::{self, JoinHandle},
 };
@@ -28,6 +29,15 @@ pub struct FileSource {
     /// File that is opened
     file_name: String,
 
+    /// Keep waiting for new data at the end of the file, instead of completing
+    follow: bool,
+
+    /// Start at the end of the file, only reading what is appended (`tail -f`)
+    tail: bool,
+
+    /// Byte ending every line
+    delimiter: u8,
+
     /// Identifier of this source
     id: u32,
 
@@ -36,12 +46,22 @@ pub struct FileSource {
 }
 
 impl FileSource {
-    pub fn new(id: u32, file_name: String, command_tx: Sender<Command>) -> FileSource {
+    pub fn new(
+        id: u32,
+        file_name: String,
+        follow: bool,
+        tail: bool,
+        delimiter: u8,
+        command_tx: Sender<Command>,
+    ) -> FileSource {
         FileSource {
             handle: None,
             thread_control_tx: None,
             command_tx,
             file_name,
+            follow,
+            tail,
+            delimiter,
             is_connected: false,
             id,
             storage: None,
@@ -49,6 +69,60 @@ impl FileSource {
     }
 }
 
+/// Open a file for reading without preventing other processes from writing to it
+///
+/// Named pipes are opened in non-blocking mode, so opening does not wait for a writer and
+/// reading does not prevent the thread from being stopped.
+fn open_shared(path: &Path) -> io::Result<File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.read(true);
+
+    #[cfg(unix)]
+    if is_fifo(path) {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.custom_flags(libc::O_NONBLOCK);
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        // FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE
+        options.share_mode(0x1 | 0x2 | 0x4);
+    }
+
+    options.open(path)
+}
+
+/// Whether the path is a named pipe (FIFO)
+fn is_fifo(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        std::fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo())
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+/// Identify the file behind a path, used to detect it being replaced (i.e. log rotation)
+fn file_identity(metadata: &Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.ino()
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        0
+    }
+}
+
 impl LogSourceTrait for FileSource {
     fn reflash(&mut self) -> Result<(), LogSourceError> {
         Ok(())
@@ -68,14 +142,36 @@ impl LogSourceTrait for FileSource {
         let file_path = PathBuf::from(&self.file_name);
         if !file_path.exists() {
             error!("File {} does not exist", self.file_name);
-            let _ = self.command_tx.send(Command::PrintMessage(format!(
+            let _ = self.command_tx.send(Command::PrintError(format!(
                 "`{}` does not exist",
                 self.file_name
             )));
+            return;
         }
-        let file = std::fs::File::open(file_path).expect("Something real wrong happened");
+        let file = match open_shared(&file_path) {
+            Ok(file) => file,
+            Err(e) => {
+                error!("Unable to open {}: {}", self.file_name, e);
+                let _ = self.command_tx.send(Command::PrintError(format!(
+                    "Unable to open `{}`: {}",
+                    self.file_name, e
+                )));
+                return;
+            }
+        };
+        let mut identity = file.metadata().map(|m| file_identity(&m)).unwrap_or(0);
+        let fifo = is_fifo(&file_path);
         let mut buffered_reader = BufReader::new(file);
 
+        // Skip what the file already has, a named pipe has nothing to skip
+        let mut start: u64 = 0;
+        if self.tail && !fifo {
+            match buffered_reader.seek(SeekFrom::End(0)) {
+                Ok(end) => start = end,
+                Err(e) => error!("Unable to seek to the end of {}: {}", self.file_name, e),
+            }
+        }
+
         // Populate thread control channel
         let (control_tx, control_rx) = std::sync::mpsc::channel();
         self.thread_control_tx = Some(control_tx);
@@ -84,18 +180,28 @@ impl LogSourceTrait for FileSource {
         let command_tx = self.command_tx.clone();
 
         let _ = command_tx.send(Command::PrintMessage(format!(
-            "Streaming from `{}`",
-            self.file_name
+            "Streaming from `{}`{}",
+            self.file_name,
+            match (fifo, start) {
+                (true, _) => String::from(" (named pipe)"),
+                (false, 0) => String::new(),
+                (false, start) => format!(", skipped the {} bytes already in it", start),
+            }
         )));
 
         // Copy data for the thread to use
         let id = self.id;
         let file_name = self.file_name.clone();
+        let follow = self.follow;
+        let delimiter = self.delimiter;
 
         // Define the thread
         let handle = std::thread::spawn(move || {
             info!("Thread started - FileSource \"{}\" (ID {})", file_name, id);
 
+            // Bytes read so far, to detect truncation
+            let mut position: u64 = start;
+
             loop {
                 // Check no message was received
                 if let Ok(response) = control_rx.try_recv() {
@@ -108,8 +214,9 @@ impl LogSourceTrait for FileSource {
                 // Fill vector
                 loop {
                     let mut out_bytes = Vec::new();
-                    match buffered_reader.read_until(0xA, &mut out_bytes) {
+                    match buffered_reader.read_until(delimiter, &mut out_bytes) {
                         Ok(nbytes) => {
+                            position += nbytes as u64;
                             if nbytes > 0 {
                                 // Send the message
                                 debug!("Sending: <-- {:?} -->", out_bytes);
@@ -119,7 +226,7 @@ impl LogSourceTrait for FileSource {
                                     Ok(_) => (),
                                     Err(e) => {
                                         error!("Send error: {}", e);
-                                        let _ = command_tx.send(Command::PrintMessage(
+                                        let _ = command_tx.send(Command::PrintError(
                                             "Internal error!!".to_string(),
                                         ));
                                         continue;
@@ -130,16 +237,67 @@ impl LogSourceTrait for FileSource {
                             }
                         }
                         Err(e) => {
-                            error!("File read error <{}>", e);
+                            // Data read before the error is not lost, the rest of the line
+                            // comes later
+                            if !out_bytes.is_empty() {
+                                let _ = command_tx.send(Command::ParseLogBytes(id, out_bytes));
+                            }
+
+                            // A named pipe without data available, wait for it
+                            if e.kind() != io::ErrorKind::WouldBlock {
+                                error!("File read error <{}>", e);
+                            }
                             break;
                         }
                     }
                 }
 
+                // A named pipe has no end, its writers come and go
+                if fifo {
+                    thread::sleep(time::Duration::from_millis(100));
+                    continue;
+                }
+
+                // At EOF, the file was fully read unless following it
+                if !follow {
+                    info!("Finished reading {}", file_name);
+                    let _ = command_tx.send(Command::SourceCompleted(id));
+                    break;
+                }
+
+                // At EOF, check whether the writer replaced or truncated the file
+                if let Ok(metadata) = std::fs::metadata(&file_path) {
+                    if file_identity(&metadata) != identity {
+                        match open_shared(&file_path) {
+                            Ok(file) => {
+                                info!("{} was replaced, reopening", file_name);
+                                identity = file_identity(&metadata);
+                                buffered_reader = BufReader::new(file);
+                                position = 0;
+                                let _ = command_tx.send(Command::PrintMessage(format!(
+                                    "`{}` was replaced, reopened it",
+                                    file_name
+                                )));
+                            }
+                            Err(e) => error!("Unable to reopen {}: {}", file_name, e),
+                        }
+                    } else if metadata.len() < position {
+                        info!("{} was truncated, reading from the beginning", file_name);
+                        if buffered_reader.seek(SeekFrom::Start(0)).is_ok() {
+                            position = 0;
+                        }
+                        let _ = command_tx.send(Command::PrintMessage(format!(
+                            "`{}` was truncated, reading from the beginning",
+                            file_name
+                        )));
+                    }
+                }
+
                 thread::sleep(time::Duration::from_millis(100));
             }
         });
         self.handle = Some(handle);
+        self.is_connected = true;
     }
 
     fn disconnect(&mut self) {
@@ -163,10 +321,32 @@ impl LogSourceTrait for FileSource {
         } else {
             error!("Thread handle is None");
         }
+
+        self.is_connected = false;
     }
 
+    fn is_connected(&self) -> bool {
+        self.is_connected
+    }
+
+    /// Read the file again from the beginning
+    ///
+    /// Any incomplete line is dropped, it will be read again. Tailed files are read from the
+    /// beginning as well, the point of resetting is getting what was skipped.
     fn reset(&mut self) -> Result<(), LogSourceError> {
-        Ok(())
+        self.storage = None;
+        if self.is_connected {
+            self.disconnect();
+        }
+        let tail = std::mem::replace(&mut self.tail, false);
+        self.connect();
+        self.tail = tail;
+
+        // `connect` already told why, i.e. the file was deleted
+        match self.is_connected {
+            true => Ok(()),
+            false => Err(LogSourceError::ReconnectionFailed),
+        }
     }
 
     fn id_eq(&self, id: u32) -> bool {
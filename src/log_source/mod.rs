@@ -2,6 +2,8 @@ pub mod file_source;
 pub mod rtt_source;
 pub mod uart_source;
 pub mod stdin_source;
+pub mod adb_source;
+pub mod udp_source;
 
 use thiserror::Error;
 
@@ -15,11 +17,19 @@ pub enum LogSourceError {
     FlashingError(#[from] probe_rs::flashing::FileDownloadError),
     #[error("This function is not implemented")]
     NotImplemented,
+    #[error("unable to connect it again")]
+    ReconnectionFailed,
 }
 
+/// Behavior of a log source
+///
+/// The sources push the bytes they receive to the commander as `Command::ParseLogBytes`, along
+/// with their id, and the commander keeps the incomplete line in their storage. Besides the
+/// built-in ones, sources implementing it can be added with `Commander::add_custom_source`.
 pub trait LogSourceTrait {
     fn connect(&mut self);
     fn disconnect(&mut self);
+    fn is_connected(&self) -> bool;
     fn reset(&mut self) -> Result<(), LogSourceError>;
     fn id_eq(&self, id: u32) -> bool;
     fn id(&self) -> u32;
@@ -33,7 +43,14 @@ pub enum LogSource {
     FileSource(FileSource),
     UartSource(UartSource),
     RttSource(RttSource),
+    /// Extra RTT up channel of a target, read by its `RttSource`
+    RttChannel(RttChannelSource),
     StdinSource(StdinSource),
+    AdbSource(AdbSource),
+    UdpSource(UdpSource),
+
+    /// Source defined outside of uberlog (i.e. by a crate using `uberlog_lib`)
+    Custom(Box<dyn LogSourceTrait + Send>),
 }
 
 impl LogSourceTrait for LogSource {
@@ -42,7 +59,11 @@ impl LogSourceTrait for LogSource {
             LogSource::FileSource(s) => s.connect(),
             LogSource::UartSource(s) => s.connect(),
             LogSource::RttSource(s) => s.connect(),
+            LogSource::RttChannel(s) => s.connect(),
             LogSource::StdinSource(s) => s.connect(),
+            LogSource::AdbSource(s) => s.connect(),
+            LogSource::UdpSource(s) => s.connect(),
+            LogSource::Custom(s) => s.connect(),
         }
     }
     fn disconnect(&mut self) {
@@ -50,7 +71,23 @@ impl LogSourceTrait for LogSource {
             LogSource::FileSource(s) => s.disconnect(),
             LogSource::UartSource(s) => s.disconnect(),
             LogSource::RttSource(s) => s.disconnect(),
+            LogSource::RttChannel(s) => s.disconnect(),
             LogSource::StdinSource(s) => s.disconnect(),
+            LogSource::AdbSource(s) => s.disconnect(),
+            LogSource::UdpSource(s) => s.disconnect(),
+            LogSource::Custom(s) => s.disconnect(),
+        }
+    }
+    fn is_connected(&self) -> bool {
+        match self {
+            LogSource::FileSource(s) => s.is_connected(),
+            LogSource::UartSource(s) => s.is_connected(),
+            LogSource::RttSource(s) => s.is_connected(),
+            LogSource::RttChannel(s) => s.is_connected(),
+            LogSource::StdinSource(s) => s.is_connected(),
+            LogSource::AdbSource(s) => s.is_connected(),
+            LogSource::UdpSource(s) => s.is_connected(),
+            LogSource::Custom(s) => s.is_connected(),
         }
     }
     fn reset(&mut self) -> Result<(), LogSourceError> {
@@ -58,7 +95,11 @@ impl LogSourceTrait for LogSource {
             LogSource::FileSource(s) => s.reset(),
             LogSource::UartSource(s) => s.reset(),
             LogSource::RttSource(s) => s.reset(),
+            LogSource::RttChannel(s) => s.reset(),
             LogSource::StdinSource(s) => s.reset(),
+            LogSource::AdbSource(s) => s.reset(),
+            LogSource::UdpSource(s) => s.reset(),
+            LogSource::Custom(s) => s.reset(),
         }
     }
     fn id_eq(&self, id: u32) -> bool {
@@ -66,7 +107,11 @@ impl LogSourceTrait for LogSource {
             LogSource::FileSource(s) => s.id_eq(id),
             LogSource::UartSource(s) => s.id_eq(id),
             LogSource::RttSource(s) => s.id_eq(id),
+            LogSource::RttChannel(s) => s.id_eq(id),
             LogSource::StdinSource(s) => s.id_eq(id),
+            LogSource::AdbSource(s) => s.id_eq(id),
+            LogSource::UdpSource(s) => s.id_eq(id),
+            LogSource::Custom(s) => s.id_eq(id),
         }
     }
     fn id(&self) -> u32 {
@@ -74,7 +119,11 @@ impl LogSourceTrait for LogSource {
             LogSource::FileSource(s) => s.id(),
             LogSource::UartSource(s) => s.id(),
             LogSource::RttSource(s) => s.id(),
+            LogSource::RttChannel(s) => s.id(),
             LogSource::StdinSource(s) => s.id(),
+            LogSource::AdbSource(s) => s.id(),
+            LogSource::UdpSource(s) => s.id(),
+            LogSource::Custom(s) => s.id(),
         }
     }
     fn id_string(&self) -> String {
@@ -82,7 +131,11 @@ impl LogSourceTrait for LogSource {
             LogSource::FileSource(s) => s.id_string(),
             LogSource::UartSource(s) => s.id_string(),
             LogSource::RttSource(s) => s.id_string(),
+            LogSource::RttChannel(s) => s.id_string(),
             LogSource::StdinSource(s) => s.id_string(),
+            LogSource::AdbSource(s) => s.id_string(),
+            LogSource::UdpSource(s) => s.id_string(),
+            LogSource::Custom(s) => s.id_string(),
         }
     }
     fn take_storage(&mut self) -> Option<Vec<u8>> {
@@ -90,7 +143,11 @@ impl LogSourceTrait for LogSource {
             LogSource::FileSource(s) => s.take_storage(),
             LogSource::UartSource(s) => s.take_storage(),
             LogSource::RttSource(s) => s.take_storage(),
+            LogSource::RttChannel(s) => s.take_storage(),
             LogSource::StdinSource(s) => s.take_storage(),
+            LogSource::AdbSource(s) => s.take_storage(),
+            LogSource::UdpSource(s) => s.take_storage(),
+            LogSource::Custom(s) => s.take_storage(),
         }
     }
     fn set_storage(&mut self, bytes: Vec<u8>) {
@@ -98,7 +155,11 @@ impl LogSourceTrait for LogSource {
             LogSource::FileSource(s) => s.set_storage(bytes),
             LogSource::UartSource(s) => s.set_storage(bytes),
             LogSource::RttSource(s) => s.set_storage(bytes),
+            LogSource::RttChannel(s) => s.set_storage(bytes),
             LogSource::StdinSource(s) => s.set_storage(bytes),
+            LogSource::AdbSource(s) => s.set_storage(bytes),
+            LogSource::UdpSource(s) => s.set_storage(bytes),
+            LogSource::Custom(s) => s.set_storage(bytes),
         }
     }
     fn reflash(&mut self) -> Result<(), LogSourceError> {
@@ -106,12 +167,18 @@ impl LogSourceTrait for LogSource {
             LogSource::FileSource(s) => Err(LogSourceError::NotImplemented),
             LogSource::UartSource(s) => s.reflash(),
             LogSource::RttSource(s) => s.reflash(),
+            LogSource::RttChannel(s) => s.reflash(),
             LogSource::StdinSource(s) => Err(LogSourceError::NotImplemented),
+            LogSource::AdbSource(s) => s.reflash(),
+            LogSource::UdpSource(s) => s.reflash(),
+            LogSource::Custom(s) => s.reflash(),
         }
     }
 }
 
 pub use file_source::FileSource;
-pub use rtt_source::RttSource;
-pub use uart_source::UartSource;
+pub use rtt_source::{RttChannelSource, RttSource};
+pub use uart_source::{UartControlLine, UartSource};
 pub use stdin_source::StdinSource;
+pub use adb_source::AdbSource;
+pub use udp_source::UdpSource;
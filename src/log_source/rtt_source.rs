@@ -9,7 +9,11 @@ use super::{LogSourceError, LogSourceTrait};
 
 use core::time;
 use std::{
-    sync::mpsc::Sender,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+    },
     thread::{self, JoinHandle},
 };
 
@@ -34,6 +38,18 @@ pub struct RttSource {
 
     /// Log processing storage
     storage: Option<Vec<u8>>,
+
+    /// RTT up channel read, chosen by the user or configured. Channel 0 until then
+    up_channel: Option<usize>,
+
+    /// Other up channels read, along with the id of the source their logs go to
+    extra_channels: Vec<(usize, u32)>,
+
+    /// Connection state, shared with the sources of the extra channels
+    channels_connected: Arc<AtomicBool>,
+
+    /// Send channel to the thread, with bytes to write into down channel 0
+    down_tx: Option<Sender<Vec<u8>>>,
 }
 
 impl RttSource {
@@ -50,9 +66,51 @@ impl RttSource {
             thread_control_tx: None,
             is_connected: false,
             storage: None,
+            up_channel: None,
+            extra_channels: Vec::new(),
+            channels_connected: Arc::new(AtomicBool::new(false)),
+            down_tx: None,
+        }
+    }
+
+    /// Send `bytes` to the target through RTT down channel 0, only possible while connected
+    ///
+    /// They are written by the thread reading the logs, which reports any error.
+    pub fn write(&self, bytes: Vec<u8>) -> Result<(), String> {
+        let down_tx = self.down_tx.as_ref().ok_or(String::from("not connected"))?;
+        down_tx.send(bytes).map_err(|_| String::from("not connected"))
+    }
+
+    /// Also read `channel`, its logs going to a source of their own with id `id`
+    ///
+    /// Returns that source, to be stored along with the rest.
+    pub fn add_channel_source(&mut self, channel: usize, id: u32) -> RttChannelSource {
+        self.extra_channels.push((channel, id));
+        RttChannelSource {
+            id,
+            parent_id: self.id,
+            name: format!("{} ch{}", self.mcu_info.name, channel),
+            probe_info: self.mcu_info.probe_info.clone(),
+            connected: self.channels_connected.clone(),
+            storage: None,
         }
     }
 
+    /// Stop reading the extra channels, their sources are to be removed. Applied when connecting
+    pub fn clear_channel_sources(&mut self) {
+        self.extra_channels.clear();
+    }
+
+    /// Ids of the sources of the extra channels
+    pub fn channel_source_ids(&self) -> Vec<u32> {
+        self.extra_channels.iter().map(|(_, id)| *id).collect()
+    }
+
+    /// Read `channel` from now on, it is applied when connecting
+    pub fn set_up_channel(&mut self, channel: usize) {
+        self.up_channel = Some(channel);
+    }
+
     pub fn get_probe_state(&mut self) -> &DebugProbeInfo {
         &self.mcu_info.probe_info
     }
@@ -65,7 +123,7 @@ impl LogSourceTrait for RttSource {
         let path = "/home/diego/Documents/tasks/elbereth-repo/elbereth/build/zephyr/zephyr.elf";
         // In order to interact with a device using probe-rs a probe/session are needed
         info!("Opening probe...");
-        let probe = self.mcu_info.probe_info.open()?;
+        let probe = self.mcu_info.open_probe()?;
         let mut session = probe.attach_under_reset(self.mcu_info.mcu.clone(), Permissions::default())?;
         info!("All ok");
         //flashing::download_file(&mut session, path, probe_rs::flashing::Format::Elf)?;
@@ -85,30 +143,50 @@ impl LogSourceTrait for RttSource {
             self.disconnect();
         }
 
-        // Update RAM state
-        self.is_connected = true;
-
         // In order to interact with a device using probe-rs a probe/session are needed
         info!("Opening probe...");
-        let probe = match self.mcu_info.probe_info.open() {
+        let probe = match self.mcu_info.open_probe() {
             Err(e) => {
                 error!("{}", e);
+                let _ = self.command_tx.send(Command::PrintError(format!(
+                    "Unable to open the probe of {}: {}",
+                    self.mcu_info.name, e
+                )));
                 return;
             }
             Ok(val) => val,
         };
+        let probe_settings = format!(
+            "{}, {} kHz{}",
+            probe.protocol().map_or(String::from("default protocol"), |p| p.to_string()),
+            probe.speed_khz(),
+            if self.mcu_info.attach_under_reset { ", attached under reset" } else { "" }
+        );
 
         info!("Session...");
-        let mut session = match probe.attach(self.mcu_info.mcu.clone(), Permissions::default()) {
+        let attach_result = match self.mcu_info.attach_under_reset {
+            true => probe.attach_under_reset(self.mcu_info.mcu.clone(), Permissions::default()),
+            false => probe.attach(self.mcu_info.mcu.clone(), Permissions::default()),
+        };
+        let mut session = match attach_result {
             Err(e) => {
                 error!("{}", e);
+                // Firmware taking over the debug pins is a common cause, point to the fix
+                let hint = match self.mcu_info.attach_under_reset {
+                    true => "",
+                    false => ", if it keeps failing try `attach_under_reset: true`",
+                };
+                let _ = self.command_tx.send(Command::PrintError(format!(
+                    "Unable to attach to {}: {}{}",
+                    self.mcu_info.name, e, hint
+                )));
                 return;
             }
             Ok(val) => val,
         };
         let rtt_address = match self.mcu_info.backend {
             LogBackendInformation::Rtt(addr) => addr,
-            LogBackendInformation::Uart(_, _) => {
+            LogBackendInformation::Uart(..) => {
                 error!("Trying to connect to RTT a target that uses UART");
                 return;
             }
@@ -117,12 +195,16 @@ impl LogSourceTrait for RttSource {
         // Create communication channel for sending data to the thread
         let (tx, rx) = std::sync::mpsc::channel();
         self.thread_control_tx = Some(tx);
+        let (down_tx, down_rx) = std::sync::mpsc::channel::<Vec<u8>>();
+        self.down_tx = Some(down_tx);
 
         // Copy data that is to be used by the thread
         let id = self.id;
         let commander_tx = self.command_tx.clone();
         let thread_rx = rx;
         let source_name = self.id_string();
+        let up_channel = self.up_channel;
+        let extra_channels = self.extra_channels.clone();
 
         let handle = std::thread::spawn(move || {
             info!("Thread started - RttSource \"{}\"", source_name);
@@ -136,13 +218,60 @@ impl LogSourceTrait for RttSource {
                 Ok(val) => val,
                 Err(e) => {
                     error!("Attach region error: {}", e);
+                    let _ = commander_tx.send(Command::PrintError(format!(
+                        "Unable to attach to the RTT block of {}: {}",
+                        source_name, e
+                    )));
                     return;
                 }
             };
             info!("Region attached");
             info!("There are {} channels", rtt.up_channels().len());
 
-            let input = &mut rtt.up_channels()[0];
+            // Firmware often logs on a channel other than 0, let the user know which ones there are
+            let channel_list = rtt
+                .up_channels()
+                .iter()
+                .map(|c| format!("{} ({})", c.number(), c.name().unwrap_or("unnamed")))
+                .collect::<Vec<String>>()
+                .join(", ");
+            let channel = up_channel.unwrap_or(0);
+            let channel_count = rtt.up_channels().len();
+            if channel >= channel_count {
+                let _ = commander_tx.send(Command::PrintError(format!(
+                    "{} has no RTT up channel {}, its channels: {}",
+                    source_name, channel, channel_list
+                )));
+                return;
+            }
+            let hint = match (up_channel, channel_count) {
+                (None, count) if count > 1 => format!(
+                    ", pick another one with `:rtt_channel <n> {}`",
+                    shell_words::quote(&source_name)
+                ),
+                _ => String::new(),
+            };
+            let _ = commander_tx.send(Command::PrintMessage(format!(
+                "{} RTT up channels: {}. Reading channel {}{}",
+                source_name, channel_list, channel, hint
+            )));
+
+            // Channels read, along with the source their logs go to. The missing ones are skipped
+            let mut readers = vec![(channel, id)];
+            for (extra_channel, extra_id) in extra_channels {
+                if extra_channel < channel_count {
+                    readers.push((extra_channel, extra_id));
+                } else {
+                    let _ = commander_tx.send(Command::PrintMessage(format!(
+                        "{} has no RTT up channel {}, not reading it",
+                        source_name, extra_channel
+                    )));
+                }
+            }
+
+            // Bytes waiting to be written into the down channel, it may not take them all at once
+            let mut down_pending: Vec<u8> = Vec::new();
+
             loop {
                 // Check no message was received
                 if let Ok(response) = thread_rx.try_recv() {
@@ -152,40 +281,76 @@ impl LogSourceTrait for RttSource {
                     }
                 }
 
-                // Read as much data as available
-                let mut buf: [u8; 200] = [0; 200];
-                let count = match input.read(&mut core, &mut buf) {
-                    Ok(val) => val,
-                    Err(e) => {
-                        error!("Port read error: {}", e);
-                        let _ = commander_tx
-                            .send(Command::PrintMessage(format!("Error reading port {}", e)));
-                        continue;
+                while let Ok(bytes) = down_rx.try_recv() {
+                    down_pending.extend(bytes);
+                }
+                if !down_pending.is_empty() {
+                    let written = match rtt.down_channel(0) {
+                        Some(down_channel) => down_channel
+                            .write(&mut core, &down_pending)
+                            .map_err(|e| e.to_string()),
+                        None => Err(String::from("it has no RTT down channel")),
+                    };
+                    match written {
+                        Ok(count) => {
+                            debug!("Wrote {} bytes into the down channel", count);
+                            down_pending.drain(..count);
+                        }
+                        Err(e) => {
+                            error!("Down channel write error: {}", e);
+                            let _ = commander_tx.send(Command::PrintError(format!(
+                                "Unable to write to {}: {}",
+                                source_name, e
+                            )));
+                            down_pending.clear();
+                        }
                     }
-                };
-
-                // If there is data, clean and send it
-                if count > 0 {
-                    debug!("Read {} bytes", count);
-                    // Take the part with data
-                    let (buf, _) = buf.split_at(count);
+                }
 
-                    // Send the message
-                    debug!("Sending: <-- {:?} -->", buf);
-                    match commander_tx.send(Command::ParseLogBytes(id, Vec::from(buf))) {
-                        Ok(_) => (),
+                for (channel, channel_id) in &readers {
+                    // Read as much data as available
+                    let mut buf: [u8; 200] = [0; 200];
+                    let count = match rtt.up_channels()[*channel].read(&mut core, &mut buf) {
+                        Ok(val) => val,
                         Err(e) => {
-                            error!("Send error: {}", e);
+                            error!("Port read error: {}", e);
                             let _ = commander_tx
-                                .send(Command::PrintMessage(String::from("Internal error!")));
+                                .send(Command::PrintError(format!("Error reading port {}", e)));
                             continue;
                         }
+                    };
+
+                    // If there is data, clean and send it
+                    if count > 0 {
+                        debug!("Read {} bytes", count);
+                        // Take the part with data
+                        let (buf, _) = buf.split_at(count);
+
+                        // Send the message
+                        debug!("Sending: <-- {:?} -->", buf);
+                        match commander_tx.send(Command::ParseLogBytes(*channel_id, Vec::from(buf))) {
+                            Ok(_) => (),
+                            Err(e) => {
+                                error!("Send error: {}", e);
+                                let _ = commander_tx
+                                    .send(Command::PrintError(String::from("Internal error!")));
+                                continue;
+                            }
+                        }
                     }
                 }
                 thread::sleep(time::Duration::from_millis(10));
             }
         });
         self.handle = Some(handle);
+
+        // Update RAM state
+        self.is_connected = true;
+        self.channels_connected.store(true, Ordering::Relaxed);
+        let _ = self.command_tx.send(Command::PrintMessage(format!(
+            "Connected to {} ({})",
+            self.mcu_info.name, probe_settings
+        )));
     }
 
     fn disconnect(&mut self) {
@@ -209,10 +374,18 @@ impl LogSourceTrait for RttSource {
         } else {
             error!("Thread handle is None");
         }
+
+        self.is_connected = false;
+        self.channels_connected.store(false, Ordering::Relaxed);
+        self.down_tx = None;
+    }
+
+    fn is_connected(&self) -> bool {
+        self.is_connected
     }
 
     fn reset(&mut self) -> Result<(), LogSourceError> {
-        let probe = self.mcu_info.probe_info.open()?;
+        let probe = self.mcu_info.open_probe()?;
         let mut session = probe.attach_under_reset(self.mcu_info.mcu.clone(), Permissions::default())?;
         let mut core = session.core(0)?;
 
@@ -241,3 +414,81 @@ impl LogSourceTrait for RttSource {
         self.storage = Some(bytes);
     }
 }
+
+/// Extra RTT up channel of a target, configured with `channels`
+///
+/// Its data is read by the `RttSource` of the target, which it is connected and disconnected
+/// along with. It only keeps its incomplete line, so the logs of every channel can be colored
+/// and filtered separately.
+pub struct RttChannelSource {
+    /// Identifier of this source
+    id: u32,
+
+    /// Identifier of the `RttSource` reading it
+    parent_id: u32,
+
+    /// Target name followed by the channel number
+    name: String,
+
+    /// Probe of the target, the source goes away with it
+    probe_info: DebugProbeInfo,
+
+    /// Connection state of the `RttSource` reading it
+    connected: Arc<AtomicBool>,
+
+    /// Log processing storage
+    storage: Option<Vec<u8>>,
+}
+
+impl RttChannelSource {
+    pub fn get_probe_state(&self) -> &DebugProbeInfo {
+        &self.probe_info
+    }
+
+    /// Identifier of the `RttSource` reading the channel
+    pub fn parent_id(&self) -> u32 {
+        self.parent_id
+    }
+}
+
+impl LogSourceTrait for RttChannelSource {
+    fn reflash(&mut self) -> Result<(), LogSourceError> {
+        Err(LogSourceError::NotImplemented)
+    }
+
+    fn connect(&mut self) {
+        warn!("{} is connected along with its target", self.name);
+    }
+
+    fn disconnect(&mut self) {
+        warn!("{} is disconnected along with its target", self.name);
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    fn reset(&mut self) -> Result<(), LogSourceError> {
+        Err(LogSourceError::NotImplemented)
+    }
+
+    fn id_eq(&self, id: u32) -> bool {
+        self.id == id
+    }
+
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn id_string(&self) -> String {
+        self.name.clone()
+    }
+
+    fn take_storage(&mut self) -> Option<Vec<u8>> {
+        self.storage.take()
+    }
+
+    fn set_storage(&mut self, bytes: Vec<u8>) {
+        self.storage = Some(bytes);
+    }
+}
@@ -95,7 +95,7 @@ impl LogSourceTrait for StdinSource {
                                     Ok(_) => (),
                                     Err(e) => {
                                         error!("Send error: {}", e);
-                                        let _ = command_tx.send(Command::PrintMessage(
+                                        let _ = command_tx.send(Command::PrintError(
                                             "Internal error!!".to_string(),
                                         ));
                                         continue;
@@ -116,6 +116,7 @@ impl LogSourceTrait for StdinSource {
             }
         });
         self.handle = Some(handle);
+        self.is_connected = true;
     }
 
     fn disconnect(&mut self) {
@@ -139,9 +140,17 @@ impl LogSourceTrait for StdinSource {
         } else {
             error!("Thread handle is None");
         }
+
+        self.is_connected = false;
+    }
+
+    fn is_connected(&self) -> bool {
+        self.is_connected
     }
 
+    /// A pipe can not be rewound, just drop the incomplete line
     fn reset(&mut self) -> Result<(), LogSourceError> {
+        self.storage = None;
         Ok(())
     }
 
@@ -6,12 +6,30 @@ use crate::commander::{Command, LogBackendInformation, TargetMcu, UiCommand};
 use super::{LogSourceError, LogSourceTrait};
 
 use core::time;
+use serialport::SerialPort;
 use std::{
-    io::Read,
+    fmt,
+    io::{Read, Write},
     sync::mpsc::Sender,
     thread::{self, JoinHandle},
 };
 
+/// Serial port control lines that can be driven at runtime
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UartControlLine {
+    Dtr,
+    Rts,
+}
+
+impl fmt::Display for UartControlLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UartControlLine::Dtr => write!(f, "DTR"),
+            UartControlLine::Rts => write!(f, "RTS"),
+        }
+    }
+}
+
 pub struct UartSource {
     /// Handle of the thread reading data
     handle: Option<JoinHandle<()>>,
@@ -38,6 +56,9 @@ pub struct UartSource {
 
     /// Session contains the debug session state
     current_session: Option<Session>,
+
+    /// Handle to the open serial port, used to drive the control lines while the thread reads
+    port: Option<Box<dyn SerialPort>>,
 }
 
 impl UartSource {
@@ -52,22 +73,47 @@ impl UartSource {
             is_connected: false,
             storage: None,
             current_session: None,
+            port: None,
         }
     }
 
     pub fn get_probe_state(&mut self) -> &DebugProbeInfo {
         &self.mcu_info.probe_info
     }
+
+    /// Drive a control line of the serial port, only possible while connected
+    pub fn set_control_line(&mut self, line: UartControlLine, level: bool) -> Result<(), String> {
+        let port = self.port.as_mut().ok_or(String::from("not connected"))?;
+        let result = match line {
+            UartControlLine::Dtr => port.write_data_terminal_ready(level),
+            UartControlLine::Rts => port.write_request_to_send(level),
+        };
+        result.map_err(|e| e.to_string())
+    }
+
+    /// probe-rs session of the target, the probe is opened if there is none yet
+    fn session(&mut self) -> Result<&mut Session, LogSourceError> {
+        let session = match self.current_session.take() {
+            Some(session) => session,
+            None => {
+                info!("Opening probe...");
+                let probe = self.mcu_info.open_probe()?;
+                probe.attach(self.mcu_info.mcu.clone(), Permissions::default())?
+            }
+        };
+        Ok(self.current_session.insert(session))
+    }
+
+    /// Send `bytes` to the target, only possible while connected
+    pub fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let port = self.port.as_mut().ok_or(String::from("not connected"))?;
+        port.write_all(bytes).map_err(|e| e.to_string())
+    }
 }
 
 impl LogSourceTrait for UartSource {
     fn reflash(&mut self) -> Result<(), LogSourceError> {
 
-        if self.current_session.is_none() {
-            error!("Session was not created");
-            return Err(LogSourceError::NotImplemented);
-        }
-
         // Use Arc<Mutex<>> to share state between the closure calls
         let total_erase_size = std::sync::Arc::new(std::sync::Mutex::new(0u64));
         let erased_bytes = std::sync::Arc::new(std::sync::Mutex::new(0u64));
@@ -155,7 +201,7 @@ impl LogSourceTrait for UartSource {
         let mut options = flashing::DownloadOptions::default();
         options.progress = Some(progress);
 
-        flashing::download_file_with_options(self.current_session.as_mut().unwrap(), "/home/diego/Documents/tasks/elbereth-repo/elbereth/build/zephyr/zephyr.elf", probe_rs::flashing::Format::Elf, options)?;
+        flashing::download_file_with_options(self.session()?, "/home/diego/Documents/tasks/elbereth-repo/elbereth/build/zephyr/zephyr.elf", probe_rs::flashing::Format::Elf, options)?;
         self.reset()?;
         Ok(())
     }
@@ -175,7 +221,7 @@ impl LogSourceTrait for UartSource {
         // Init of current_session
         if self.current_session.is_none() {
             info!("Opening probe...");
-            let probe = match self.mcu_info.probe_info.open() {
+            let probe = match self.mcu_info.open_probe() {
                 Ok(probe) => probe,
                 Err(e) => {
                     error!("{}", e.to_string());
@@ -189,6 +235,37 @@ impl LogSourceTrait for UartSource {
             }
         }
 
+        let (dev_path, baud, initial_dtr, initial_rts) = match &self.mcu_info.backend {
+            LogBackendInformation::Uart(path, baud, dtr, rts) => (path.clone(), *baud, *dtr, *rts),
+            _ => {
+                error!("UART source with RTT backend");
+                return;
+            }
+        };
+
+        // Open the port, setting DTR already while opening so the board does not see a glitch
+        let mut port_builder = serialport::new(&dev_path, baud).timeout(std::time::Duration::from_secs(1));
+        if let Some(dtr) = initial_dtr {
+            port_builder = port_builder.dtr_on_open(dtr);
+        }
+        let mut port = match port_builder.open() {
+            Ok(port) => port,
+            Err(e) => {
+                error!("Failed to open port {}: {}", dev_path, e);
+                let _ = self
+                    .command_tx
+                    .send(Command::PrintError(format!("Failed to open {}: {}", dev_path, e)));
+                return;
+            }
+        };
+        if let Some(rts) = initial_rts
+            && let Err(e) = port.write_request_to_send(rts)
+        {
+            warn!("Unable to set RTS on {}: {}", dev_path, e);
+        }
+        self.port = port.try_clone().ok();
+        info!("Serial port opened");
+
         // Create communication channel for sending data to the thread
         let (tx, rx) = std::sync::mpsc::channel();
         self.thread_control_tx = Some(tx);
@@ -201,24 +278,9 @@ impl LogSourceTrait for UartSource {
         let commander_tx = self.command_tx.clone();
         let thread_rx = rx;
 
-        let (dev_path, baud) = match &self.mcu_info.backend {
-            LogBackendInformation::Uart(path, baud) => (path.clone(), *baud),
-            _ => {
-                error!("UART source with RTT backend");
-                return;
-            }
-        };
-
         let handle = std::thread::spawn(move || {
             info!("Thread started - UartSource \"{} - {}\"", dev_path, baud);
 
-            let mut port = serialport::new(dev_path, baud)
-                .timeout(std::time::Duration::from_secs(1))
-                .open()
-                .expect("Failed to open port");
-
-            info!("Serial port opened");
-
             loop {
                 // Check no message was received
                 if let Ok(response) = thread_rx.try_recv() {
@@ -243,7 +305,7 @@ impl LogSourceTrait for UartSource {
                             error!("Serial port connection error");
                             let _ = commander_tx.send(Command::DisconnectLogSource(id));
                             let _ = commander_tx.send(Command::RefreshProbeInfo);
-                            let _ = commander_tx.send(Command::PrintMessage(String::from(
+                            let _ = commander_tx.send(Command::PrintError(String::from(
                                 "Serial port connection error",
                             )));
                             break;
@@ -252,7 +314,7 @@ impl LogSourceTrait for UartSource {
                         // Otherwise report it
                         error!("Port read error: {}", e);
                         let _ = commander_tx
-                            .send(Command::PrintMessage(format!("Error reading port {}", e)));
+                            .send(Command::PrintError(format!("Error reading port {}", e)));
 
                         continue;
                     }
@@ -272,7 +334,7 @@ impl LogSourceTrait for UartSource {
                         Err(e) => {
                             error!("Send error: {}", e);
                             let _ = commander_tx
-                                .send(Command::PrintMessage(String::from("Internal error!")));
+                                .send(Command::PrintError(String::from("Internal error!")));
                             continue;
                         }
                     }
@@ -304,15 +366,17 @@ impl LogSourceTrait for UartSource {
         } else {
             error!("Thread handle is None");
         }
+
+        self.port = None;
+        self.is_connected = false;
     }
 
-    fn reset(&mut self) -> Result<(), LogSourceError> {
-        if self.current_session.is_none() {
-            error!("Session was not created");
-            return Err(LogSourceError::NotImplemented);
-        }
+    fn is_connected(&self) -> bool {
+        self.is_connected
+    }
 
-        let session = self.current_session.as_mut().unwrap();
+    fn reset(&mut self) -> Result<(), LogSourceError> {
+        let session = self.session()?;
         let mut core = session.core(0)?;
         core.reset()?;
 
@@ -0,0 +1,194 @@
+use tracing::{debug, error, info, warn};
+
+use crate::commander::Command;
+
+use super::{LogSourceError, LogSourceTrait};
+
+use core::time;
+use std::{
+    io::ErrorKind,
+    net::UdpSocket,
+    sync::mpsc::Sender,
+    thread::JoinHandle,
+};
+
+/// How long a read waits for a datagram, the thread checks for the stop request in between
+const READ_TIMEOUT: time::Duration = time::Duration::from_millis(100);
+
+pub struct UdpSource {
+    /// Handle of the thread reading data
+    handle: Option<JoinHandle<()>>,
+
+    /// Send channel to gracefully shutdown the thread
+    thread_control_tx: Option<Sender<bool>>,
+
+    /// Send channel to Commander
+    command_tx: Sender<Command>,
+
+    /// Holds state
+    is_connected: bool,
+
+    /// Port listened on, every interface
+    port: u16,
+
+    /// End of the lines, added to the datagrams that do not end with it
+    line_delimiter: u8,
+
+    /// Identifier of this source
+    id: u32,
+
+    /// Log processing storage
+    storage: Option<Vec<u8>>,
+}
+
+impl UdpSource {
+    pub fn new(id: u32, port: u16, line_delimiter: u8, command_tx: Sender<Command>) -> UdpSource {
+        UdpSource {
+            handle: None,
+            thread_control_tx: None,
+            command_tx,
+            is_connected: false,
+            port,
+            line_delimiter,
+            id,
+            storage: None,
+        }
+    }
+}
+
+impl LogSourceTrait for UdpSource {
+    fn reflash(&mut self) -> Result<(), LogSourceError> {
+        Err(LogSourceError::NotImplemented)
+    }
+
+    /// Bind the port, it is released when disconnecting so another tool can use it
+    fn connect(&mut self) {
+        // Validate status
+        if self.is_connected {
+            warn!("Already connected!");
+            return;
+        }
+
+        let socket = match UdpSocket::bind(("0.0.0.0", self.port)) {
+            Ok(socket) => socket,
+            Err(e) => {
+                error!("Unable to bind UDP port {}: {}", self.port, e);
+                let _ = self.command_tx.send(Command::PrintError(format!(
+                    "Unable to listen on UDP port {}: {}",
+                    self.port, e
+                )));
+                return;
+            }
+        };
+        if let Err(e) = socket.set_read_timeout(Some(READ_TIMEOUT)) {
+            error!("Unable to set the UDP read timeout: {}", e);
+            return;
+        }
+
+        // Populate thread control channel
+        let (control_tx, control_rx) = std::sync::mpsc::channel();
+        self.thread_control_tx = Some(control_tx);
+
+        // Copy data for the thread to use
+        let command_tx = self.command_tx.clone();
+        let id = self.id;
+        let line_delimiter = self.line_delimiter;
+        let name = self.id_string();
+
+        // Define the thread
+        let handle = std::thread::spawn(move || {
+            info!("Thread started - UdpSource \"{}\" (ID {})", name, id);
+
+            // Largest possible datagram
+            let mut buf = vec![0; 65536];
+            loop {
+                // Check no message was received
+                if let Ok(false) = control_rx.try_recv() {
+                    info!("Stop streaming thread");
+                    break;
+                }
+
+                let count = match socket.recv_from(&mut buf) {
+                    Ok((count, _)) => count,
+                    Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+                    Err(e) => {
+                        error!("UDP read error <{}>", e);
+                        let _ = command_tx.send(Command::PrintError(format!("{}: {}", name, e)));
+                        break;
+                    }
+                };
+                if count == 0 {
+                    continue;
+                }
+
+                // A datagram is a whole message, which senders (i.e. syslog) usually do not
+                // end. Otherwise it would wait in the storage for the next datagram
+                debug!("Received {} bytes", count);
+                let mut bytes = buf[..count].to_vec();
+                if bytes.last() != Some(&line_delimiter) {
+                    bytes.push(line_delimiter);
+                }
+                if command_tx.send(Command::ParseLogBytes(id, bytes)).is_err() {
+                    break;
+                }
+            }
+        });
+        self.handle = Some(handle);
+        self.is_connected = true;
+    }
+
+    fn disconnect(&mut self) {
+        info!("Disconnecting {}", self.id_string());
+
+        if let Some(channel) = self.thread_control_tx.take() {
+            match channel.send(false) {
+                Ok(_) => (),
+                Err(e) => error!("{:?}", e),
+            }
+        } else {
+            error!("Thread control channel is None");
+        }
+
+        // Wait for the thread to die, which closes the socket
+        if let Some(t_handle) = self.handle.take() {
+            match t_handle.join() {
+                Ok(_) => (),
+                Err(e) => error!("{:?}", e),
+            }
+        } else {
+            error!("Thread handle is None");
+        }
+
+        self.is_connected = false;
+    }
+
+    fn is_connected(&self) -> bool {
+        self.is_connected
+    }
+
+    /// Datagrams can not be received again, just drop the incomplete line
+    fn reset(&mut self) -> Result<(), LogSourceError> {
+        self.storage = None;
+        Ok(())
+    }
+
+    fn id_eq(&self, id: u32) -> bool {
+        self.id == id
+    }
+
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn id_string(&self) -> String {
+        format!("UDP :{}", self.port)
+    }
+
+    fn take_storage(&mut self) -> Option<Vec<u8>> {
+        self.storage.take()
+    }
+
+    fn set_storage(&mut self, bytes: Vec<u8>) {
+        self.storage = Some(bytes);
+    }
+}
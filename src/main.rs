@@ -1,27 +1,37 @@
-use std::{fs::OpenOptions, io::IsTerminal, time::Duration};
+use std::{
+    fs::OpenOptions,
+    io::IsTerminal,
+    path::Path,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
 
 use tracing::{Level, error, info, span};
 use tracing_subscriber::{Registry, fmt, prelude::*};
 use uberlog_lib::{
     command_parser::CommandParser, commander::{self, add_filter, Command, Commander, UiCommand}, configuration::{self, ApplicationConfiguration}, tui::{
-        section_filters::SectionFilters, section_logs::SectionLogs, section_sources::SectionSources, LayoutSection,
-    }, LogMessage
+        log_columns::LogColumns, section_dashboard::SectionDashboard, section_diff::SectionDiff, section_filters::SectionFilters, section_grep::SectionGrep, section_logs::SectionLogs, section_sources::SectionSources, theme::Theme, LayoutSection,
+    }, LogMessage, LogTimestamp, TimestampZone
 };
 
 use std::sync::mpsc::{Receiver, Sender};
 use tokio::runtime::Runtime;
 
 use color_eyre::Result;
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyModifiers, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+    PushKeyboardEnhancementFlags,
+};
 use ratatui::crossterm::event::DisableMouseCapture;
 use ratatui::crossterm::execute;
 use ratatui::crossterm::terminal::{EnterAlternateScreen, enable_raw_mode};
 use ratatui::crossterm::terminal::{LeaveAlternateScreen, disable_raw_mode};
 use ratatui::{
     Frame, Terminal,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     prelude::{Backend, CrosstermBackend},
-    text::Line,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Paragraph},
 };
 use std::{error::Error, io};
@@ -36,6 +46,13 @@ pub struct App {
     // Top section
     pub section_probes: SectionSources,
     pub section_filters: SectionFilters,
+    pub section_dashboard: SectionDashboard,
+
+    // Temporary `:grep` buffer, shown instead of the logs while open
+    pub section_grep: Option<SectionGrep>,
+
+    // Temporary `:diff` buffer, shown instead of the logs (and the grep buffer) while open
+    pub section_diff: Option<SectionDiff>,
 
     // Log section
     pub section_logs: SectionLogs,
@@ -44,6 +61,48 @@ pub struct App {
     pub command_parser: CommandParser,
 
     pub message: String,
+
+    /// Last error reported, can be recalled after other messages replaced it
+    last_error: Option<String>,
+
+    /// An error was reported and the user has not acknowledged it yet
+    error_pending: bool,
+
+    /// UI colors
+    pub theme: Theme,
+
+    /// Key rotating through the screens
+    cycle_screen_key: KeyCode,
+
+    /// Minimum time between two redraws, from `max_fps`
+    frame_interval: Duration,
+}
+
+/// Command line arguments
+#[derive(Default)]
+struct CliArguments {
+    /// Target (name from `.gadget.yaml`) to connect to on startup
+    target: Option<String>,
+
+    /// Validate `.gadget.yaml` and exit, without starting the UI
+    check: bool,
+}
+
+impl CliArguments {
+    fn parse() -> Result<CliArguments, String> {
+        let mut cli_args = CliArguments::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--target" => {
+                    cli_args.target = Some(args.next().ok_or("--target requires a name")?);
+                }
+                "--check" => cli_args.check = true,
+                _ => return Err(format!("Unknown argument <{}>\n\nUsage: uberlog [--target <name>] [--check]", arg)),
+            }
+        }
+        Ok(cli_args)
+    }
 }
 
 #[derive(Debug, Default)]
@@ -53,9 +112,120 @@ pub enum CurrentScreen {
     Live,
     Filters,
     Probes,
+    // Read-only overview of the sources
+    Dashboard,
+}
+
+impl CurrentScreen {
+    /// Screen shown after this one when cycling through them
+    fn next(&self) -> CurrentScreen {
+        match self {
+            CurrentScreen::Live => CurrentScreen::Filters,
+            CurrentScreen::Filters => CurrentScreen::Probes,
+            CurrentScreen::Probes => CurrentScreen::Dashboard,
+            CurrentScreen::Dashboard => CurrentScreen::Live,
+        }
+    }
+}
+
+/// Whether the key only moves around, and so it is fine to repeat it while held
+fn is_navigation_key(key: KeyEvent) -> bool {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        return matches!(key.code, KeyCode::Char('d' | 'u'));
+    }
+
+    matches!(
+        key.code,
+        KeyCode::Char('j' | 'k' | 'n' | 'N')
+            | KeyCode::Up
+            | KeyCode::Down
+            | KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::PageUp
+            | KeyCode::PageDown
+            | KeyCode::Home
+            | KeyCode::End
+    )
+}
+
+/// Parse a key name from the configuration: a single character, `Tab`, `BackTab` or `F1`-`F12`
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    match name.to_lowercase().as_str() {
+        "tab" => Some(KeyCode::Tab),
+        "backtab" => Some(KeyCode::BackTab),
+        function => match function.strip_prefix('f')?.parse() {
+            Ok(n @ 1..=12) => Some(KeyCode::F(n)),
+            _ => None,
+        },
+    }
+}
+
+/// Validate `.gadget.yaml`, printing a report
+///
+/// Every target is checked for a known processor and, for RTT ones, an elf file containing the
+/// RTT control block. Returns whether everything is fine.
+fn check_target_cfg() -> bool {
+    let target_cfg = match configuration::load_target_cfg() {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            println!("{:#}", e);
+            return false;
+        }
+    };
+
+    let mut all_ok = true;
+    for target in &target_cfg.targets {
+        let mut errors = Vec::new();
+        if let Err(e) = probe_rs::config::get_target_by_name(&target.processor) {
+            errors.push(format!("unknown processor <{}>: {}", target.processor, e));
+        }
+        for backend in target.backends() {
+            if let configuration::LogBackend::Rtt { elf_path, .. } = backend
+                && let Err(e) = Commander::rtt_block_from_elf(elf_path)
+            {
+                errors.push(e);
+            }
+            if let configuration::LogBackend::DefmtRtt { elf_path } = backend {
+                if let Err(e) = Commander::rtt_block_from_elf(elf_path) {
+                    errors.push(e);
+                }
+                if let Err(e) = Commander::defmt_table_from_elf(elf_path) {
+                    errors.push(e);
+                }
+            }
+        }
+
+        match errors.is_empty() {
+            true => println!("{}: ok", target.name),
+            false => {
+                all_ok = false;
+                for error in errors {
+                    println!("{}: {}", target.name, error);
+                }
+            }
+        }
+    }
+    all_ok
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let cli_args = match CliArguments::parse() {
+        Ok(cli_args) => cli_args,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if cli_args.check {
+        std::process::exit(if check_target_cfg() { 0 } else { 1 });
+    }
+
     let log_file = OpenOptions::new()
         .write(true)
         .truncate(true)
@@ -75,11 +245,27 @@ fn main() -> Result<(), Box<dyn Error>> {
     info!("Starting app");
 
     // Load configuration files
-    let app_cfg = ApplicationConfiguration::load_cfg();
+    let app_cfg = match ApplicationConfiguration::load_cfg() {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            error!("{:#}", e);
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+    };
+    match TimestampZone::parse(&app_cfg.timezone) {
+        Some(zone) => LogTimestamp::set_zone(zone),
+        None => error!("Invalid timezone <{}>, using the local one", app_cfg.timezone),
+    }
+    // Not having a `.gadget.yaml` is fine, but the user must know why an existing one is ignored
+    let mut target_cfg_error = None;
     let target_cfg = match configuration::load_target_cfg() {
         Ok(cfg) => Some(cfg),
         Err(e) => {
-            info!("{}", e);
+            info!("{:#}", e);
+            if Path::new(configuration::TARGET_CFG_PATH).exists() {
+                target_cfg_error = Some(format!("{:#}", e));
+            }
             None
         }
     };
@@ -88,6 +274,9 @@ fn main() -> Result<(), Box<dyn Error>> {
     let (commander_tx, commander_rx) = std::sync::mpsc::channel();
     let (commander_responwe_tx, commander_response_rx) = std::sync::mpsc::channel();
     let (rtt_data_tx, rtt_data_rx) = std::sync::mpsc::channel();
+    if let Some(message) = target_cfg_error {
+        let _ = commander_responwe_tx.send(UiCommand::ErrorMessage { message });
+    }
 
     // Instantiate application and commander
     let mut app = App::new(
@@ -108,16 +297,68 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Register commands -- File
     app.command_parser
         .register_instruction(String::from(":stream_in"), commander::stream_file);
+    app.command_parser
+        .register_instruction(String::from(":stream_adb"), commander::stream_adb);
+    app.command_parser
+        .register_instruction(String::from(":stream_udp"), commander::stream_udp);
+    app.command_parser
+        .register_instruction(String::from(":stream_fifo"), commander::stream_fifo);
     app.command_parser
         .register_instruction(String::from(":stream_out"), commander::stream_start);
     app.command_parser
         .register_instruction(String::from(":stream_out_stop"), commander::stream_stop);
+    app.command_parser
+        .register_instruction(String::from(":export"), commander::export);
     // Register commands -- Internal
     app.command_parser
         .register_instruction(String::from(":find"), commander::find_log);
+    app.command_parser
+        .register_instruction(String::from(":goto_time"), commander::goto_time);
+    app.command_parser
+        .register_instruction(String::from(":hex"), commander::hex);
+    app.command_parser
+        .register_instruction(String::from(":since_boot"), commander::since_boot);
+    app.command_parser
+        .register_instruction(String::from(":grep"), commander::grep);
+    app.command_parser
+        .register_instruction(String::from(":diff"), commander::diff);
     // Register commands -- Filter
     app.command_parser
         .register_instruction(String::from(":filter"), commander::add_filter);
+    app.command_parser
+        .register_instruction(String::from(":filter_file"), commander::add_filter_file);
+    app.command_parser
+        .register_instruction(String::from(":recover"), commander::recover_filters);
+    app.command_parser
+        .register_instruction(String::from(":filter_group"), commander::filter_group);
+    app.command_parser
+        .register_instruction(String::from(":filter_save"), commander::filter_save);
+    app.command_parser
+        .register_instruction(String::from(":filter_load"), commander::filter_load);
+    app.command_parser
+        .register_instruction(String::from(":compact"), commander::compact);
+    app.command_parser
+        .register_instruction(String::from(":uart_line"), commander::uart_line);
+    app.command_parser
+        .register_instruction(String::from(":loglevel"), commander::loglevel);
+    app.command_parser
+        .register_instruction(String::from(":rtt_channel"), commander::rtt_channel);
+    app.command_parser
+        .register_instruction(String::from(":backend"), commander::backend);
+    app.command_parser
+        .register_instruction(String::from(":profile"), commander::profile);
+    app.command_parser
+        .register_instruction(String::from(":note"), commander::note);
+    app.command_parser
+        .register_instruction(String::from(":notify"), commander::notify);
+    app.command_parser
+        .register_instruction(String::from(":notify_clear"), commander::notify_clear);
+    app.command_parser
+        .register_instruction(String::from(":on_match"), commander::on_match);
+    app.command_parser
+        .register_instruction(String::from(":on_match_clear"), commander::on_match_clear);
+    app.command_parser
+        .register_instruction(String::from(":config"), commander::dump_config);
 
     
     // If STDIN is tty, create the log source for it
@@ -125,10 +366,15 @@ fn main() -> Result<(), Box<dyn Error>> {
         let _ = commander_tx.send(Command::StreamStdin);
     }
 
+    // Connect to the requested target, probes were already refreshed when creating the commander
+    if let Some(target) = cli_args.target {
+        let _ = commander_tx.send(Command::ConnectLogSourceByName(target));
+    }
+
     // Commander main loop
     let rt = Runtime::new().expect("Unable to create Runtime");
     let _enter = rt.enter();
-    std::thread::spawn(move || {
+    std::thread::Builder::new().name(String::from(COMMANDER_THREAD)).spawn(move || {
         rt.block_on(async {
             loop {
                 let _span = span!(Level::DEBUG, "Commander cmd process").entered();
@@ -136,7 +382,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                     Ok(_) => (),
                     Err(e) => {
                         error!("Commander error: {}", e);
-                        let _ = commander.command_response_tx.send(UiCommand::TextMessage {
+                        let _ = commander.command_response_tx.send(UiCommand::ErrorMessage {
                             message: "Internal error".to_string(),
                         });
                         break;
@@ -144,13 +390,24 @@ fn main() -> Result<(), Box<dyn Error>> {
                 }
             }
         });
-    });
+    })?;
 
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
 
+    // Ask the terminal to report held keys as repeats, if it is able to
+    let keyboard_enhancement = crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false);
+    if keyboard_enhancement {
+        execute!(
+            stdout,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )?;
+    }
+
+    restore_terminal_on_panic(keyboard_enhancement);
+
     // create the backend/terminal
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
@@ -159,6 +416,9 @@ fn main() -> Result<(), Box<dyn Error>> {
     let res = run_app(&mut terminal, &mut app);
 
     // restore terminal
+    if keyboard_enhancement {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
@@ -167,6 +427,10 @@ fn main() -> Result<(), Box<dyn Error>> {
     )?;
     terminal.show_cursor()?;
 
+    if let Some(message) = COMMANDER_PANIC.get() {
+        eprintln!("{message}");
+        std::process::exit(1);
+    }
     if let Err(err) = res {
         println!("{err:?}");
     }
@@ -174,23 +438,87 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Name of the thread running the commander
+const COMMANDER_THREAD: &str = "commander";
+
+/// Panic message of the commander, set when it dies. The UI can not do anything without it
+static COMMANDER_PANIC: OnceLock<String> = OnceLock::new();
+
+/// Leave the terminal usable if the UI panics, before the panic message is printed
+///
+/// The UI runs in the main thread and stops by itself. A panic in the commander is recorded
+/// for the UI to stop too, one in any other worker thread is only logged.
+fn restore_terminal_on_panic(keyboard_enhancement: bool) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        error!("{}", panic_info);
+        if std::thread::current().name() == Some(COMMANDER_THREAD) {
+            let _ = COMMANDER_PANIC.set(format!("Commander stopped: {}", panic_info));
+        }
+        if std::thread::current().name() == Some("main") {
+            let mut stdout = io::stdout();
+            if keyboard_enhancement {
+                let _ = execute!(stdout, PopKeyboardEnhancementFlags);
+            }
+            let _ = disable_raw_mode();
+            let _ = execute!(stdout, LeaveAlternateScreen, DisableMouseCapture, crossterm::cursor::Show);
+        }
+        default_hook(panic_info);
+    }));
+}
+
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<bool> {
+    let mut last_draw: Option<Instant> = None;
     loop {
         // TODO: Try to fix this
         //app.section_logs.vertical_scroll_state = app.section_logs.vertical_scroll_state.content_length(app.section_logs.logs.len());
 
-        terminal.draw(|f| ui(f, app))?;
+        // Redraw at most at `max_fps`, whatever arrives in between is shown in the next frame
+        if last_draw.is_none_or(|t| t.elapsed() >= app.frame_interval) {
+            terminal.draw(|f| ui(f, app))?;
+            last_draw = Some(Instant::now());
+        }
 
         if event::poll(Duration::from_millis(10))? {
-            if let Event::Key(key) = event::read()? {
+            let event = event::read()?;
+
+            // Recompute the log view state right away, do not wait for the next render
+            if let Event::Resize(_, height) = event {
+                let log_lines = (height as usize).saturating_sub(top_section_lines(app) + 1);
+                app.section_logs.update_scroll_state(log_lines);
+            }
+
+            if let Event::Key(key) = event {
                 // Skip events that are not KeyEventKind::Press
                 if key.kind == event::KeyEventKind::Release {
                     continue;
                 }
 
+                // A held key only repeats navigation, so actions (quit, clear, reset...) are
+                // not fired several times by accident. Typing a command repeats everything.
+                if key.kind == event::KeyEventKind::Repeat
+                    && app.command_parser.is_idle()
+                    && !is_navigation_key(key)
+                {
+                    continue;
+                }
+
                 // If command parser is processing a command, append char and skip further processing
                 if !app.command_parser.is_idle() {
-                    app.command_parser.process_key(key.code);
+                    app.command_parser.process_key(key);
+                } else if key.code == app.cycle_screen_key {
+                    app.current_screen = app.current_screen.next();
+
+                    // Refresh the information shown in the new screen
+                    match app.current_screen {
+                        CurrentScreen::Live | CurrentScreen::Dashboard => (),
+                        CurrentScreen::Filters => {
+                            let _ = app.command_tx.send(Command::GetFilters);
+                        }
+                        CurrentScreen::Probes => {
+                            let _ = app.command_tx.send(Command::RefreshProbeInfo);
+                        }
+                    }
                 } else {
                     match app.current_screen {
                         CurrentScreen::Live => {
@@ -210,16 +538,56 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<
                                     app.current_screen = CurrentScreen::Probes;
                                 }
 
+                                // Switch to the dashboard
+                                KeyCode::Char('D') => {
+                                    app.current_screen = CurrentScreen::Dashboard;
+                                }
+
                                 // So far only process comands in `Live` screen
                                 KeyCode::Char(':') | KeyCode::Char('/') => {
                                     app.message.clear();
-                                    app.command_parser.process_key(key.code);
+                                    app.error_pending = false;
+                                    app.command_parser.process_key(key);
                                 }
 
-                                // Otherwise forward to sub-views
-                                key => {
-                                    app.section_logs.process_key(key);
+                                // Show the last error again, acknowledging it
+                                KeyCode::Char('E') => {
+                                    if let Some(error) = &app.last_error {
+                                        app.message = error.clone();
+                                    }
+                                    app.error_pending = false;
+                                }
+
+                                // Half page scroll, vim style
+                                KeyCode::Char('d')
+                                    if key.modifiers.contains(KeyModifiers::CONTROL)
+                                        && app.section_grep.is_none()
+                                        && app.section_diff.is_none() =>
+                                {
+                                    app.section_logs.scroll_half_page_down();
                                 }
+                                KeyCode::Char('u')
+                                    if key.modifiers.contains(KeyModifiers::CONTROL)
+                                        && app.section_grep.is_none()
+                                        && app.section_diff.is_none() =>
+                                {
+                                    app.section_logs.scroll_half_page_up();
+                                }
+
+                                // Close the diff or grep buffer, going back to the full view
+                                KeyCode::Esc if app.section_diff.is_some() => {
+                                    app.section_diff = None;
+                                }
+                                KeyCode::Esc if app.section_grep.is_some() => {
+                                    app.section_grep = None;
+                                }
+
+                                // Otherwise forward to sub-views
+                                key => match (&mut app.section_diff, &mut app.section_grep) {
+                                    (Some(section_diff), _) => section_diff.process_key(key),
+                                    (None, Some(section_grep)) => section_grep.process_key(key),
+                                    (None, None) => app.section_logs.process_key(key),
+                                },
                             }
                         }
                         CurrentScreen::Filters => {
@@ -260,11 +628,39 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<
                                 }
                             }
                         }
+                        CurrentScreen::Dashboard => {
+                            match key.code {
+                                KeyCode::Char('q') | KeyCode::Esc => {
+                                    app.current_screen = CurrentScreen::Live;
+                                }
+
+                                // Switch to Filter view
+                                KeyCode::Char('F') => {
+                                    let _ = app.command_tx.send(Command::GetFilters);
+                                    app.current_screen = CurrentScreen::Filters;
+                                }
+
+                                // Switch to Probe view
+                                KeyCode::Char('P') => {
+                                    let _ = app.command_tx.send(Command::RefreshProbeInfo);
+                                    app.current_screen = CurrentScreen::Probes;
+                                }
+
+                                key => {
+                                    app.section_dashboard.process_key(key);
+                                }
+                            }
+                        }
                     }
                 }
             }
         }
 
+        // Nothing works without the commander, leave for main to restore the terminal
+        if COMMANDER_PANIC.get().is_some() {
+            return Ok(false);
+        }
+
         // Check for command responses
         if let Ok(response) = app.command_response_rx.try_recv() {
             info!("Ui Processing {}", response);
@@ -273,23 +669,65 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<
                     app.command_parser.cancel_parsing();
                     app.message = message;
                 }
+                UiCommand::ErrorMessage { message } => {
+                    app.command_parser.cancel_parsing();
+                    app.message = message.clone();
+                    app.last_error = Some(message);
+                    app.error_pending = true;
+                }
                 UiCommand::UpdateFilterList(filters) => {
                     app.section_filters.set_filters(filters);
                 }
                 UiCommand::UpdateLogs(logs) => {
                     app.section_logs.update_logs(logs);
                 }
-                UiCommand::UpdateSearchLog(log) => {
-                    app.section_logs.update_search_log(log);
+                UiCommand::UpdatePartialLog(id, log) => {
+                    app.section_logs.update_partial_log(id, log);
+                }
+                UiCommand::UpdateSearchLog(log, color, count, name) => {
+                    app.section_logs.update_search_log(log, color, count, name);
+                }
+                UiCommand::ShowGrep(pattern, logs) => {
+                    app.section_grep = Some(SectionGrep::new(pattern, logs, app.theme.clone()));
+                }
+                UiCommand::ShowDiff(path_a, path_b, rows, exact) => {
+                    app.section_diff = Some(SectionDiff::new(path_a, path_b, rows, exact, app.theme.clone()));
+                }
+                UiCommand::GotoTime(timestamp) => {
+                    app.section_logs.goto_time(timestamp);
+                }
+                UiCommand::GotoLog(idx) => {
+                    app.section_logs.goto_log(idx);
+                }
+                UiCommand::ToggleHexView(id) => {
+                    app.section_logs.toggle_hex_view(id);
                 }
                 UiCommand::AddNewSource(id, display_text) => {
+                    app.section_dashboard.add_source(id, display_text.clone());
+                    app.section_logs.add_source(id, display_text.clone());
+                    app.section_filters.add_source(id, display_text.clone());
                     app.section_probes.add_source(id, display_text);
                 }
                 UiCommand::SetConnectionSource(id, is_connected) => {
                     app.section_probes.set_connected(id, is_connected);
+                    app.section_dashboard.set_connected(id, is_connected);
+                }
+                UiCommand::SetSourceStats(id, lines) => {
+                    app.section_probes.set_line_count(id, lines);
+                    app.section_dashboard.set_line_count(id, lines);
+                }
+                UiCommand::SetSourceCompleted(id) => {
+                    app.section_probes.set_completed(id);
+                    app.section_dashboard.set_completed(id);
+                }
+                UiCommand::SetSourcePaused(id, is_paused) => {
+                    app.section_probes.set_paused(id, is_paused);
+                    app.section_dashboard.set_paused(id, is_paused);
                 }
                 UiCommand::RemoveSource(id) => {
                     app.section_probes.delete_source(id);
+                    app.section_dashboard.delete_source(id);
+                    app.section_filters.delete_source(id);
                 }
                 UiCommand::SetProgress(id, progress, progress_stage) => {
                     app.section_probes.source_set_progress(id, progress, progress_stage);
@@ -304,13 +742,18 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<
     }
 }
 
-pub fn ui(frame: &mut Frame, app: &mut App) {
-    // Depending on the current view allocate some lines on top
-    let top_side_lines = match app.current_screen {
+/// Depending on the current view allocate some lines on top
+fn top_section_lines(app: &App) -> usize {
+    match app.current_screen {
         CurrentScreen::Live => 0,
         CurrentScreen::Filters => app.section_filters.min_lines(),
         CurrentScreen::Probes => app.section_probes.min_lines(),
-    };
+        CurrentScreen::Dashboard => app.section_dashboard.min_lines(),
+    }
+}
+
+pub fn ui(frame: &mut Frame, app: &mut App) {
+    let top_side_lines = top_section_lines(app);
 
     // Prepare chunks
     let chunks = Layout::default()
@@ -326,18 +769,47 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
     match app.current_screen {
         CurrentScreen::Probes => app.section_probes.ui(frame, chunks[0]),
         CurrentScreen::Filters => app.section_filters.ui(frame, chunks[0]),
+        CurrentScreen::Dashboard => app.section_dashboard.ui(frame, chunks[0]),
         CurrentScreen::Live => (),
     }
 
-    // Show Logs section
-    app.section_logs.ui(frame, chunks[1]);
+    // Show Logs section, or the diff/grep buffer on top of it
+    match (&mut app.section_diff, &mut app.section_grep) {
+        (Some(section_diff), _) => section_diff.ui(frame, chunks[1]),
+        (None, Some(section_grep)) => section_grep.ui(frame, chunks[1]),
+        (None, None) => app.section_logs.ui(frame, chunks[1]),
+    }
+
+    // Keep an indicator on screen until the last error is acknowledged
+    if app.error_pending && chunks[1].width > 4 {
+        let indicator_area = Rect::new(chunks[1].right() - 4, chunks[1].y, 3, 1);
+        let indicator = Paragraph::new(" ! ").style(
+            Style::default()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+        );
+        frame.render_widget(indicator, indicator_area);
+    }
 
-    // And Status line
-    let text_to_print = match app.command_parser.is_idle() {
-        true => app.message.clone(),
-        false => app.command_parser.get_parsed_cmd(),
+    // And Status line, make it obvious when a command is being typed
+    let status_text = match app.command_parser.is_idle() {
+        true => Line::from(app.message.clone()),
+        false => {
+            let command = app.command_parser.get_parsed_cmd();
+            let (prompt, content) = command.split_at(command.chars().next().map_or(0, |c| c.len_utf8()));
+            Line::from(vec![
+                Span::styled(
+                    format!(" {} ", prompt),
+                    Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                ),
+                Span::raw(format!(" {}", content)),
+            ])
+        }
     };
-    let status_line = Paragraph::new(Line::from(text_to_print)).block(Block::default());
+    let status_line = Paragraph::new(status_text)
+        .style(app.theme.status_line)
+        .block(Block::default());
     frame.render_widget(status_line, chunks[2]);
 }
 
@@ -349,16 +821,41 @@ impl App {
         cfg: &ApplicationConfiguration,
     ) -> App {
         let aliases = cfg.alias_list.clone();
+        let theme = Theme::from_cfg(&cfg.theme);
+        let mut message = String::new();
+        let columns = cfg.column_template.as_ref().and_then(|template| {
+            LogColumns::from_cfg(template, &cfg.column_colors)
+                .inspect_err(|e| {
+                    error!("{}", e);
+                    message = e.clone();
+                })
+                .ok()
+        });
+        let cycle_screen_key = parse_key(&cfg.cycle_screen_key).unwrap_or_else(|| {
+            error!("Invalid cycle_screen_key <{}>, using Tab", cfg.cycle_screen_key);
+            KeyCode::Tab
+        });
         App {
             command_tx: command_tx.clone(),
             command_response_rx,
             rtt_data_rx,
             current_screen: CurrentScreen::Live,
-            section_logs: SectionLogs::new(command_tx.clone()),
-            section_probes: SectionSources::new(command_tx.clone()),
-            section_filters: SectionFilters::new(command_tx.clone()),
+            section_logs: SectionLogs::new(command_tx.clone(), theme.clone(), cfg.follow_logs, columns, cfg.max_log_lines),
+            section_probes: SectionSources::new(command_tx.clone(), theme.clone()),
+            section_filters: SectionFilters::new(command_tx.clone(), theme.clone()),
+            section_dashboard: SectionDashboard::new(theme.clone()),
+            section_grep: None,
+            section_diff: None,
             command_parser: CommandParser::new(command_tx, aliases),
-            message: String::new(),
+            message,
+            last_error: None,
+            error_pending: false,
+            theme,
+            cycle_screen_key,
+            frame_interval: match cfg.max_fps {
+                0 => Duration::ZERO,
+                fps => Duration::from_secs(1) / fps,
+            },
         }
     }
 }
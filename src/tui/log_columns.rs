@@ -0,0 +1,79 @@
+use std::{collections::HashMap, str::FromStr};
+
+use ratatui::style::{Color, Style};
+use regex::Regex;
+use unicode_width::UnicodeWidthStr;
+
+/// Splits structured logs (i.e. `time | level | module | msg`) into columns
+///
+/// Every named group of the template is a column, in the order they appear. Lines not matching
+/// the template are not split.
+pub struct LogColumns {
+    /// Template the lines are matched against
+    regex: Regex,
+
+    /// Name and style of every column
+    columns: Vec<(String, Style)>,
+}
+
+impl LogColumns {
+    /// Build the columns from the configured template and colors (by column name)
+    pub fn from_cfg(template: &str, colors: &HashMap<String, String>) -> Result<Self, String> {
+        let regex = Regex::new(template).map_err(|e| format!("Invalid column_template: {}", e))?;
+
+        let mut columns = Vec::new();
+        for name in regex.capture_names().flatten() {
+            let style = match colors.get(name) {
+                Some(color) => Style::default().fg(
+                    Color::from_str(color)
+                        .map_err(|_| format!("Unknown color <{}> for column <{}>", color, name))?,
+                ),
+                None => Style::default(),
+            };
+            columns.push((name.to_string(), style));
+        }
+        if columns.is_empty() {
+            return Err(String::from("column_template has no named groups"));
+        }
+
+        Ok(LogColumns { regex, columns })
+    }
+
+    /// Split a line into its columns, `None` if it does not match the template
+    ///
+    /// ANSI codes are dropped, the columns have their own colors.
+    pub fn split(&self, line: &str) -> Option<Vec<String>> {
+        let line = strip_ansi_escapes::strip_str(line);
+        let line = line.replace('\t', "    ");
+        let captures = self.regex.captures(line.trim_end_matches(['\r', '\n']))?;
+
+        Some(
+            self.columns
+                .iter()
+                .map(|(name, _)| captures.name(name).map_or("", |m| m.as_str()).to_string())
+                .collect(),
+        )
+    }
+
+    /// `field` padded to `width` terminal cells, wide characters (i.e. CJK, emoji) taking two
+    pub fn pad(field: &str, width: usize) -> String {
+        let padding = width.saturating_sub(field.width());
+        format!("{}{}", field, " ".repeat(padding))
+    }
+
+    /// Style of a column
+    pub fn style(&self, column: usize) -> Style {
+        self.columns.get(column).map_or(Style::default(), |(_, style)| *style)
+    }
+
+    /// Width of every column, in terminal cells, so all the given lines are aligned
+    pub fn widths<'a>(&self, lines: impl Iterator<Item = &'a str>) -> Vec<usize> {
+        let mut widths = vec![0; self.columns.len()];
+        for fields in lines.filter_map(|line| self.split(line)) {
+            for (width, field) in widths.iter_mut().zip(fields) {
+                *width = (*width).max(field.width());
+            }
+        }
+        widths
+    }
+}
@@ -1,6 +1,11 @@
+pub mod log_columns;
+pub mod section_dashboard;
+pub mod section_diff;
 pub mod section_filters;
+pub mod section_grep;
 pub mod section_logs;
 pub mod section_sources;
+pub mod theme;
 
 
 use ratatui::{Frame, layout::Rect};
@@ -0,0 +1,194 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Block, BorderType, Borders, Paragraph},
+};
+
+use super::{LayoutSection, theme::Theme};
+
+/// Seconds of throughput history shown, one sparkline bar each
+const HISTORY_SECONDS: usize = 30;
+
+/// Sparkline bars, from lowest to highest
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+struct SourceSummary {
+    id: u32,
+    name: String,
+    connected: bool,
+    completed: bool,
+    paused: bool,
+    line_count: usize,
+
+    /// When the source last sent data
+    last_data: Option<Instant>,
+
+    /// Lines received, along with when the statistics reported them
+    history: VecDeque<(Instant, usize)>,
+}
+
+impl SourceSummary {
+    fn state(&self) -> &'static str {
+        match (self.connected, self.completed, self.paused) {
+            (true, _, true) => "Paused",
+            (true, _, false) => "Connected",
+            (false, true, _) => "Finished",
+            (false, false, _) => "Not connected",
+        }
+    }
+
+    /// Lines received on every second of the history, oldest first
+    fn throughput(&self, now: Instant) -> [usize; HISTORY_SECONDS] {
+        let mut buckets = [0; HISTORY_SECONDS];
+        for (time, lines) in &self.history {
+            let age = now.duration_since(*time).as_secs() as usize;
+            if age < HISTORY_SECONDS {
+                buckets[HISTORY_SECONDS - 1 - age] += lines;
+            }
+        }
+        buckets
+    }
+}
+
+/// Read-only overview of every source, one line each
+///
+/// Unlike the Log source view, nothing can be done from here, it is meant to keep an eye on
+/// several boards at once (i.e. during a soak test).
+pub struct SectionDashboard {
+    sources: Vec<SourceSummary>,
+
+    /// UI colors
+    theme: Theme,
+}
+
+impl SectionDashboard {
+    pub fn new(theme: Theme) -> Self {
+        SectionDashboard {
+            sources: Vec::new(),
+            theme,
+        }
+    }
+
+    fn get_source(&mut self, id: u32) -> Option<&mut SourceSummary> {
+        self.sources.iter_mut().find(|s| s.id == id)
+    }
+
+    pub fn add_source(&mut self, id: u32, name: String) {
+        self.sources.push(SourceSummary {
+            id,
+            name,
+            connected: false,
+            completed: false,
+            paused: false,
+            line_count: 0,
+            last_data: None,
+            history: VecDeque::new(),
+        });
+    }
+
+    pub fn delete_source(&mut self, id: u32) {
+        self.sources.retain(|s| s.id != id);
+    }
+
+    pub fn set_connected(&mut self, id: u32, is_connected: bool) {
+        if let Some(source) = self.get_source(id) {
+            source.connected = is_connected;
+            source.completed = false;
+        }
+    }
+
+    pub fn set_completed(&mut self, id: u32) {
+        if let Some(source) = self.get_source(id) {
+            source.connected = false;
+            source.completed = true;
+        }
+    }
+
+    pub fn set_paused(&mut self, id: u32, is_paused: bool) {
+        if let Some(source) = self.get_source(id) {
+            source.paused = is_paused;
+        }
+    }
+
+    /// Record the line count reported for a source, the difference with the last one is its
+    /// throughput. A count going down means the logs were cleared
+    pub fn set_line_count(&mut self, id: u32, line_count: usize) {
+        if let Some(source) = self.get_source(id) {
+            let now = Instant::now();
+            if line_count > source.line_count {
+                source.history.push_back((now, line_count - source.line_count));
+                source.last_data = Some(now);
+            }
+            source.line_count = line_count;
+
+            let window = Duration::from_secs(HISTORY_SECONDS as u64);
+            while source
+                .history
+                .front()
+                .is_some_and(|(time, _)| now.duration_since(*time) >= window)
+            {
+                source.history.pop_front();
+            }
+        }
+    }
+}
+
+impl LayoutSection for SectionDashboard {
+    fn ui(&mut self, frame: &mut Frame, area: Rect) {
+        let now = Instant::now();
+        let name_width = self.sources.iter().map(|s| s.name.chars().count()).max().unwrap_or(0);
+
+        let mut lines = Vec::new();
+        for source in &self.sources {
+            let age = match source.last_data {
+                Some(time) => format!("{}s ago", now.duration_since(time).as_secs()),
+                None => String::from("never"),
+            };
+
+            let throughput = source.throughput(now);
+            let peak = throughput.iter().copied().max().unwrap_or(0);
+            let sparkline: String = throughput
+                .iter()
+                .map(|lines| match (peak, lines) {
+                    (0, _) | (_, 0) => ' ',
+                    (peak, lines) => BARS[(lines * (BARS.len() - 1)).div_ceil(peak)],
+                })
+                .collect();
+
+            lines.push(Line::from(format!(
+                " {:<name_width$} | {:<13} | {:>8} lines | last data {:>8} | {} {:.1} lines/s",
+                source.name,
+                source.state(),
+                source.line_count,
+                age,
+                sparkline,
+                throughput.iter().sum::<usize>() as f32 / HISTORY_SECONDS as f32,
+                name_width = name_width,
+            )));
+        }
+
+        let dashboard_block = Block::default()
+            .title(Line::from(format!("Dashboard (last {}s)", HISTORY_SECONDS)))
+            .title_style(self.theme.title)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(self.theme.border)
+            .style(Style::default());
+
+        frame.render_widget(Paragraph::new(lines).block(dashboard_block), area);
+    }
+
+    fn process_key(&mut self, _key: KeyCode) {}
+
+    fn min_lines(&self) -> usize {
+        2 /* borders */ + self.sources.len().max(1)
+    }
+}
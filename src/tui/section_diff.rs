@@ -0,0 +1,187 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::commander::{DiffKind, DiffRow};
+
+use super::{LayoutSection, section_logs::sanitize_log_msg, theme::Theme};
+
+/// Two captures compared line by line, opened with `:diff`
+///
+/// Like the grep buffer it is shown on top of the log view until it is closed. Both files
+/// are shown side by side and scroll together, lines only in one of them leave a gap in the
+/// other.
+pub struct SectionDiff {
+    /// Paths of the files, first and second
+    paths: (String, String),
+
+    rows: Vec<DiffRow>,
+
+    /// Whether the diff is the smallest one, or the files differed too much to find it
+    exact: bool,
+
+    /// Row offset, shared by both files
+    vertical_scroll: usize,
+
+    /// Maximum offset
+    vertical_scroll_limit: usize,
+
+    /// How many lines are displayed in a page, depends on screen size
+    page_size: usize,
+
+    /// UI colors
+    theme: Theme,
+}
+
+impl SectionDiff {
+    pub fn new(path_a: String, path_b: String, rows: Vec<DiffRow>, exact: bool, theme: Theme) -> SectionDiff {
+        SectionDiff {
+            paths: (path_a, path_b),
+            rows,
+            exact,
+            vertical_scroll: 0,
+            vertical_scroll_limit: 0,
+            page_size: 0,
+            theme,
+        }
+    }
+
+    /// Scroll to the next (or previous) run of differences, keeping a few lines of context
+    fn find_difference(&mut self, forward: bool) {
+        let current = self.vertical_scroll + 3;
+        let is_start = |idx: usize| {
+            self.rows[idx].kind != DiffKind::Same
+                && (idx == 0 || self.rows[idx - 1].kind == DiffKind::Same)
+        };
+        let found = match forward {
+            true => (current + 1..self.rows.len()).find(|idx| is_start(*idx)),
+            false => (0..current.min(self.rows.len())).rev().find(|idx| is_start(*idx)),
+        };
+        if let Some(idx) = found {
+            self.vertical_scroll = idx.saturating_sub(3).min(self.vertical_scroll_limit);
+        }
+    }
+
+    /// Style of a line, depending on how it differs
+    fn style(&self, kind: DiffKind) -> Style {
+        match kind {
+            DiffKind::Same => self.theme.log_text,
+            DiffKind::Removed => Style::default().fg(Color::Red),
+            DiffKind::Added => Style::default().fg(Color::Green),
+            DiffKind::Changed => Style::default().fg(Color::Yellow),
+        }
+    }
+
+    /// Lines of one of the files for the rows on screen, gaps left empty
+    fn pane_lines(&self, rows: &[DiffRow], left: bool, number_width: usize) -> Vec<Line<'static>> {
+        let marker = match left {
+            true => "-",
+            false => "+",
+        };
+        rows.iter()
+            .map(|row| {
+                let line = match left {
+                    true => &row.left,
+                    false => &row.right,
+                };
+                match line {
+                    Some((number, text)) => {
+                        let marker = match row.kind {
+                            DiffKind::Same => " ",
+                            _ => marker,
+                        };
+                        let text = sanitize_log_msg(&strip_ansi_escapes::strip_str(text));
+                        Line::from(vec![
+                            Span::raw(format!("{:>width$} {} ", number, marker, width = number_width)),
+                            Span::styled(text, self.style(row.kind)),
+                        ])
+                    }
+                    None => Line::from(""),
+                }
+            })
+            .collect()
+    }
+}
+
+impl LayoutSection for SectionDiff {
+    fn ui(&mut self, frame: &mut Frame, area: Rect) {
+        // Update scroll state (+2 to take into account borders)
+        let height = area.height as usize;
+        self.vertical_scroll_limit = (self.rows.len() + 2).saturating_sub(height);
+        self.vertical_scroll = self.vertical_scroll.min(self.vertical_scroll_limit);
+        self.page_size = height;
+
+        let changes = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(idx, row)| {
+                row.kind != DiffKind::Same && (*idx == 0 || self.rows[idx - 1].kind == DiffKind::Same)
+            })
+            .count();
+        let inexact = match self.exact {
+            true => "",
+            false => ", too many to find the smallest diff",
+        };
+
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+
+        let end = (self.vertical_scroll + height.saturating_sub(2)).min(self.rows.len());
+        let rows = &self.rows[self.vertical_scroll..end];
+        let number_width = self.rows.len().to_string().len();
+        for (left, chunk, path) in [(true, chunks[0], &self.paths.0), (false, chunks[1], &self.paths.1)] {
+            let title = match left {
+                true => format!("Diff [{}] {} differences{} (Esc to close)", path, changes, inexact),
+                false => format!("[{}]", path),
+            };
+            let block = Block::default()
+                .title(Line::from(title))
+                .title_style(self.theme.title)
+                .borders(Borders::ALL)
+                .border_style(self.theme.border)
+                .style(Style::default());
+            let content = Paragraph::new(self.pane_lines(rows, left, number_width)).block(block);
+            frame.render_widget(content, chunk);
+        }
+    }
+
+    fn process_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.vertical_scroll = (self.vertical_scroll + 1).min(self.vertical_scroll_limit);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.vertical_scroll = self.vertical_scroll.saturating_sub(1);
+            }
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.vertical_scroll = 0;
+            }
+            KeyCode::End | KeyCode::Char('G') => {
+                self.vertical_scroll = self.vertical_scroll_limit;
+            }
+            KeyCode::PageDown => {
+                self.vertical_scroll =
+                    (self.vertical_scroll + self.page_size).min(self.vertical_scroll_limit);
+            }
+            KeyCode::PageUp => {
+                self.vertical_scroll = self.vertical_scroll.saturating_sub(self.page_size);
+            }
+            // Jump between differences
+            KeyCode::Char('n') => self.find_difference(true),
+            KeyCode::Char('N') => self.find_difference(false),
+            _ => (),
+        }
+    }
+
+    fn min_lines(&self) -> usize {
+        self.rows.len().max(1)
+    }
+}
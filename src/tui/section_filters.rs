@@ -9,26 +9,162 @@ use ratatui::{
     widgets::{Block, BorderType, Borders, Paragraph},
 };
 
-use crate::{LogFilter, LogFilterType, commander::Command};
+use crate::{LogFilter, LogFilterScope, LogFilterType, commander::Command};
 
-use super::LayoutSection;
+use super::{LayoutSection, theme::Theme};
+
+/// Set of filters shown, and edited, in the list
+#[derive(Clone, Copy, PartialEq)]
+enum FilterSet {
+    /// Every filter, whatever the source they apply to
+    All,
+    /// The filters applied to every source
+    Global,
+    /// The filters bound to a single source
+    Source(i32),
+}
 
 pub struct SectionFilters {
     filters: Vec<LogFilter>,
+
+    /// Position of the selected filter among the ones shown
     selected_filter: usize,
+
+    /// Filters shown
+    filter_set: FilterSet,
+
+    /// Known sources, ID and name, to pick their filters
+    sources: Vec<(i32, String)>,
+
     command_tx: Sender<Command>,
+    theme: Theme,
 }
 
 impl SectionFilters {
-    pub fn new(command_tx: Sender<Command>) -> SectionFilters {
+    pub fn new(command_tx: Sender<Command>, theme: Theme) -> SectionFilters {
         SectionFilters {
             filters: Vec::new(),
             selected_filter: 0,
+            filter_set: FilterSet::All,
+            sources: Vec::new(),
             command_tx,
+            theme,
+        }
+    }
+
+    pub fn add_source(&mut self, id: u32, name: String) {
+        self.sources.push((id as i32, name));
+    }
+
+    /// Forget a source, its filters are still shown with the rest
+    pub fn delete_source(&mut self, id: u32) {
+        self.sources.retain(|(source, _)| *source != id as i32);
+        if self.filter_set == FilterSet::Source(id as i32) {
+            self.filter_set = FilterSet::All;
+            self.selected_filter = 0;
         }
     }
+
+    /// Show the next set of filters: all of them, the global ones and those of every source
+    fn next_filter_set(&mut self) {
+        let sets: Vec<FilterSet> = [FilterSet::All, FilterSet::Global]
+            .into_iter()
+            .chain(self.sources.iter().map(|(id, _)| FilterSet::Source(*id)))
+            .collect();
+        let current = sets.iter().position(|set| *set == self.filter_set).unwrap_or(0);
+        self.filter_set = sets[(current + 1) % sets.len()];
+        self.selected_filter = 0;
+    }
+
+    /// Indexes of the filters in the selected set
+    fn shown_filters(&self) -> Vec<usize> {
+        self.filters
+            .iter()
+            .enumerate()
+            .filter(|(_, filter)| match self.filter_set {
+                FilterSet::All => true,
+                FilterSet::Global => filter.source.is_none(),
+                FilterSet::Source(id) => filter.source.as_ref() == Some(&self.source_name(id)),
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    fn source_name(&self, id: i32) -> String {
+        match self.sources.iter().find(|(source, _)| *source == id) {
+            Some((_, name)) => name.clone(),
+            None => format!("ID {}", id),
+        }
+    }
+
+    /// Move the selected filter one position earlier (`up`) or later in the pipeline
+    ///
+    /// The filters of a group stay together: a grouped filter only moves within its group, and
+    /// an ungrouped one jumps over a whole group. Only the filters shown are taken into
+    /// account, so it moves within its set.
+    fn move_selected_filter(&mut self, up: bool) {
+        let shown = self.shown_filters();
+        let Some(&idx) = shown.get(self.selected_filter) else {
+            return;
+        };
+        let pos = self.selected_filter;
+        let neighbor = match up {
+            true => pos.checked_sub(1),
+            false => Some(pos + 1).filter(|i| *i < shown.len()),
+        };
+        let Some(neighbor) = neighbor else {
+            return;
+        };
+
+        let group = self.filters[idx].group.clone();
+        let neighbor_group = self.filters[shown[neighbor]].group.clone();
+        let new_pos = match (&group, &neighbor_group) {
+            (Some(_), _) if group != neighbor_group => {
+                let _ = self.command_tx.send(Command::PrintMessage(String::from(
+                    "Filters stay in their group, use :filter_group to move the whole group",
+                )));
+                return;
+            }
+            (None, Some(_)) => {
+                // Land right past the neighboring group
+                let mut new_pos = neighbor;
+                while let Some(next) = match up {
+                    true => new_pos.checked_sub(1),
+                    false => Some(new_pos + 1).filter(|i| *i < shown.len()),
+                } && self.filters[shown[next]].group == neighbor_group
+                {
+                    new_pos = next;
+                }
+                new_pos
+            }
+            _ => neighbor,
+        };
+
+        let filter = self.filters.remove(idx);
+        self.filters.insert(shown[new_pos], filter);
+        self.selected_filter = new_pos;
+
+        // Send all of them again, in the new order
+        let _ = self.command_tx.send(Command::SetFilters(self.filters.clone()));
+    }
+
+    /// Enable/disable the selected filter, keeping it in the list
+    fn toggle_selected_filter(&mut self) {
+        let Some(&idx) = self.shown_filters().get(self.selected_filter) else {
+            return;
+        };
+        let filter = &mut self.filters[idx];
+        filter.enabled = !filter.enabled;
+
+        // Send all of them again, the logs are filtered again with the change
+        let _ = self.command_tx.send(Command::SetFilters(self.filters.clone()));
+    }
+
     pub fn set_filters(&mut self, filters: Vec<LogFilter>) {
         self.filters = filters;
+        self.selected_filter = self
+            .selected_filter
+            .min(self.shown_filters().len().saturating_sub(1));
     }
 }
 
@@ -36,30 +172,112 @@ impl LayoutSection for SectionFilters {
     fn ui(&mut self, frame: &mut Frame, area: Rect) {
         // Print filters
         let mut filter_list_lines = Vec::new();
-        for (idx, filter) in self.filters.iter().enumerate() {
+        let shown = self.shown_filters();
+        for (pos, &idx) in shown.iter().enumerate() {
+            let filter = &self.filters[idx];
+
+            // Filters of a group go indented under its name
+            let indent = match &filter.group {
+                Some(group) => {
+                    if pos == 0 || self.filters[shown[pos - 1]].group.as_ref() != Some(group) {
+                        filter_list_lines
+                            .push(Line::from(format!("Group {}", group)).style(self.theme.title));
+                    }
+                    "  "
+                }
+                None => "",
+            };
+
             // Map kind to text
             let type_text = match filter.kind {
                 LogFilterType::Exclusion => "Exclusion",
                 LogFilterType::Inclusion => "Inclusion",
                 LogFilterType::Highlighter => "Highlight",
+                LogFilterType::LongerThan(_) | LogFilterType::ShorterThan(_) => "Length",
+                LogFilterType::After(_) | LogFilterType::Before(_) => "Time",
+                LogFilterType::Counter => "Counter",
             };
 
-            let mut line_style = filter.style;
-            if idx == self.selected_filter {
-                line_style = line_style.add_modifier(Modifier::BOLD);
+            let count_text = match filter.kind {
+                LogFilterType::Counter => format!(" ({} matches)", filter.match_count.0),
+                _ => String::new(),
+            };
+
+            // Map scope to text, view is the default so omit it
+            let scope_text = match filter.scope {
+                LogFilterScope::View => "",
+                LogFilterScope::Export => " (export)",
+                LogFilterScope::Both => " (view+export)",
+            };
+
+            let focus_text = match filter.focus {
+                true => " (focus)",
+                false => "",
+            };
+            let regex_text = match filter.regex {
+                Some(_) => " (regex)",
+                None => "",
+            };
+            let case_text = match filter.ignore_case {
+                true => " (ignore case)",
+                false => "",
+            };
+            let columns_text = match filter.columns {
+                Some((first, last)) => format!(" (columns {}-{})", first, last),
+                None => String::new(),
+            };
+            // Within the set of a source it goes without saying
+            let source_text = match (&filter.source, self.filter_set) {
+                (Some(name), FilterSet::All) => format!(" (source {})", name),
+                _ => String::new(),
+            };
+            let origin_text = match filter.from_config {
+                true => " (from config)",
+                false => "",
+            };
+
+            let (enabled_text, mut line_style) = match filter.enabled {
+                true => ("", filter.style),
+                false => ("(off) ", filter.style.add_modifier(Modifier::DIM)),
+            };
+            if pos == self.selected_filter {
+                line_style = line_style.patch(self.theme.selected_row);
             }
 
             // Print the line
             filter_list_lines.push(
-                Line::from(format!("[{}] {} <{}>", idx, type_text, filter.msg)).style(line_style),
+                Line::from(format!(
+                    "{}[{}] {}{} <{}>{}{}{}{}{}{}{}{}",
+                    indent,
+                    idx,
+                    enabled_text,
+                    type_text,
+                    filter.msg,
+                    scope_text,
+                    focus_text,
+                    regex_text,
+                    case_text,
+                    columns_text,
+                    source_text,
+                    origin_text,
+                    count_text
+                ))
+                .style(line_style),
             );
         }
 
-        let filters_block_title = Line::from("Filters");
+        let set_text = match self.filter_set {
+            FilterSet::All => String::from("all"),
+            FilterSet::Global => String::from("every source"),
+            FilterSet::Source(id) => self.source_name(id),
+        };
+        let filters_block_title = Line::from(format!("Filters [{}] (s to switch)", set_text));
         let filters_block = Block::default()
             .title(filters_block_title)
+            .title_style(self.theme.title)
             .borders(Borders::ALL)
             .border_type(BorderType::Double)
+            .border_style(self.theme.border)
             .style(Style::default());
 
         let filters_list = Paragraph::new(filter_list_lines).block(filters_block);
@@ -70,13 +288,11 @@ impl LayoutSection for SectionFilters {
         match key {
             KeyCode::Char('j') | KeyCode::Down => {
                 // Make sure there is a filter
-                if self.filters.is_empty() {
+                let shown = self.shown_filters().len();
+                if shown == 0 {
                     return;
                 }
-                self.selected_filter = self
-                    .selected_filter
-                    .saturating_add(1)
-                    .min(self.filters.len() - 1);
+                self.selected_filter = self.selected_filter.saturating_add(1).min(shown - 1);
             }
             KeyCode::Char('k') | KeyCode::Up => {
                 self.selected_filter = self.selected_filter.saturating_sub(1);
@@ -86,30 +302,35 @@ impl LayoutSection for SectionFilters {
             }
             KeyCode::End => {
                 // Make sure there is a filter
-                if self.filters.is_empty() {
+                let shown = self.shown_filters().len();
+                if shown == 0 {
                     return;
                 }
-                self.selected_filter = self.filters.len() - 1;
+                self.selected_filter = shown - 1;
+            }
+            KeyCode::Char('K') => {
+                self.move_selected_filter(true);
+            }
+            KeyCode::Char('J') => {
+                self.move_selected_filter(false);
+            }
+            KeyCode::Char(' ') => {
+                self.toggle_selected_filter();
+            }
+            KeyCode::Char('s') => {
+                self.next_filter_set();
             }
             KeyCode::Char('d') => {
                 // Make sure there is a filter
-                if self.filters.is_empty() {
+                let Some(&idx) = self.shown_filters().get(self.selected_filter) else {
                     return;
-                }
+                };
 
                 // Remove selected filter
-                self.filters.remove(self.selected_filter);
-
-                // Query a filter cleanup
-                let _ = self.command_tx.send(Command::ClearFilters);
-
-                // Send all of them again
-                for filter in &self.filters {
-                    let _ = self.command_tx.send(Command::AddFilter(filter.clone()));
-                }
+                self.filters.remove(idx);
 
-                // Request list udpate
-                let _ = self.command_tx.send(Command::GetFilters);
+                // Send all of them again, the commander keeps the list updated
+                let _ = self.command_tx.send(Command::SetFilters(self.filters.clone()));
 
                 // Update current index
                 self.selected_filter = self.selected_filter.saturating_sub(1);
@@ -119,6 +340,11 @@ impl LayoutSection for SectionFilters {
     }
 
     fn min_lines(&self) -> usize {
-        return 2 /*borders */ + self.filters.len().max(1);
+        let shown: Vec<&LogFilter> = self.shown_filters().into_iter().map(|idx| &self.filters[idx]).collect();
+        let group_count = shown
+            .chunk_by(|a, b| a.group.is_some() && a.group == b.group)
+            .filter(|step| step[0].group.is_some())
+            .count();
+        return 2 /*borders */ + (shown.len() + group_count).max(1);
     }
 }
@@ -0,0 +1,131 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::LogMessage;
+use ansi_to_tui::IntoText;
+
+use super::{LayoutSection, section_logs::sanitize_log_msg, theme::Theme};
+
+/// Temporary buffer with the logs matching a `:grep` pattern
+///
+/// It is shown on top of the log view, which keeps receiving logs underneath, and can be
+/// scrolled independently until it is closed.
+pub struct SectionGrep {
+    /// Pattern the logs were matched against
+    pattern: String,
+
+    /// Matching logs, along with their line number in the full log
+    logs: Vec<(usize, LogMessage)>,
+
+    /// Log offset
+    vertical_scroll: usize,
+
+    /// Maximum offset
+    vertical_scroll_limit: usize,
+
+    /// How many lines are displayed in a page, depends on screen size
+    page_size: usize,
+
+    /// UI colors
+    theme: Theme,
+}
+
+impl SectionGrep {
+    pub fn new(pattern: String, logs: Vec<(usize, LogMessage)>, theme: Theme) -> SectionGrep {
+        SectionGrep {
+            pattern,
+            logs,
+            vertical_scroll: 0,
+            vertical_scroll_limit: 0,
+            page_size: 0,
+            theme,
+        }
+    }
+}
+
+impl LayoutSection for SectionGrep {
+    fn ui(&mut self, frame: &mut Frame, area: Rect) {
+        // Update scroll state (+2 to take into account borders)
+        let height = area.height as usize;
+        self.vertical_scroll_limit = (self.logs.len() + 2).saturating_sub(height);
+        self.vertical_scroll = self.vertical_scroll.min(self.vertical_scroll_limit);
+        self.page_size = height;
+
+        // Width of the line number column
+        let number_width = self.logs.last().map_or(1, |(idx, _)| (idx + 1).to_string().len());
+
+        let mut log_lines = Vec::new();
+        for (idx, log) in &self.logs {
+            let log_style = match log.style == LogMessage::default_style() {
+                true => self.theme.log_text,
+                false => log.style,
+            };
+
+            let sanitized_line = sanitize_log_msg(&log.message);
+            let mut line = sanitized_line.into_text().unwrap().lines[0].clone();
+            for internal_span in &mut line.spans {
+                internal_span.style = internal_span.style.remove_modifier(Modifier::all());
+                internal_span.style = internal_span.style.patch(log_style);
+            }
+
+            // Prepend the original line number
+            line.spans.insert(
+                0,
+                format!("{:>width$} | ", idx + 1, width = number_width).into(),
+            );
+            log_lines.push(line);
+        }
+
+        let grep_block = Block::default()
+            .title(Line::from(format!(
+                "Grep [{}] {} matches (Esc to close)",
+                self.pattern,
+                self.logs.len()
+            )))
+            .title_style(self.theme.title)
+            .borders(Borders::ALL)
+            .border_style(self.theme.border)
+            .style(Style::default());
+
+        let grep_content = Paragraph::new(log_lines)
+            .block(grep_block)
+            .scroll((self.vertical_scroll as u16, 0));
+
+        frame.render_widget(grep_content, area);
+    }
+
+    fn process_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.vertical_scroll = (self.vertical_scroll + 1).min(self.vertical_scroll_limit);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.vertical_scroll = self.vertical_scroll.saturating_sub(1);
+            }
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.vertical_scroll = 0;
+            }
+            KeyCode::End | KeyCode::Char('G') => {
+                self.vertical_scroll = self.vertical_scroll_limit;
+            }
+            KeyCode::PageDown => {
+                self.vertical_scroll =
+                    (self.vertical_scroll + self.page_size).min(self.vertical_scroll_limit);
+            }
+            KeyCode::PageUp => {
+                self.vertical_scroll = self.vertical_scroll.saturating_sub(self.page_size);
+            }
+            _ => (),
+        }
+    }
+
+    fn min_lines(&self) -> usize {
+        self.logs.len().max(1)
+    }
+}
@@ -1,25 +1,92 @@
-use std::sync::mpsc::Sender;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::mpsc::Sender,
+};
 
 use crossterm::event::KeyCode;
 use ratatui::{
     Frame,
-    layout::Rect,
-    style::{self, Modifier, Style},
-    text::Line,
-    widgets::{Block, Borders, Paragraph},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{self, Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
 };
 use tracing::debug;
 
-use crate::{LogMessage, LogTimestamp, commander::Command};
+use crate::{
+    APP_SOURCE_ID, LogMessage, LogTimestamp, NOTE_SOURCE_ID, STATUS_SOURCE_ID, commander::Command,
+};
 use ansi_to_tui::IntoText;
 
-use super::LayoutSection;
+use super::{LayoutSection, log_columns::LogColumns, theme::Theme};
 
 enum SearchDirection {
     FOWARD,
     BACKWARD,
 }
 
+/// Colors used by default for each search slot
+const SEARCH_COLORS: [Color; 9] = [
+    Color::Yellow,
+    Color::Cyan,
+    Color::Magenta,
+    Color::Green,
+    Color::Red,
+    Color::Blue,
+    Color::LightYellow,
+    Color::LightCyan,
+    Color::LightMagenta,
+];
+
+/// Width of the minimap, borders included
+const MINIMAP_WIDTH: u16 = 4;
+
+/// Minimap cells, from no highlighted log to all of them
+const MINIMAP_DENSITY: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// A search, with its own color and current match
+struct LogSearch {
+    /// Given with `:find --name`, the search is then found by it instead of its slot
+    name: Option<String>,
+
+    /// Text being searched for, empty means disabled
+    text: String,
+
+    /// Color used to show its matches
+    color: Color,
+
+    /// Index of the current match
+    log_idx: usize,
+}
+
+impl LogSearch {
+    fn new(color: Color) -> Self {
+        LogSearch {
+            name: None,
+            text: String::new(),
+            color,
+            log_idx: 0,
+        }
+    }
+}
+
+/// How the source of every log is shown in front of it, `s` rotates through them
+#[derive(Clone, Copy, PartialEq)]
+enum SourcePrefix {
+    Hidden,
+    Name,
+    Id,
+}
+
+/// A row of the log view
+#[derive(Clone, Copy)]
+enum LogRow {
+    /// Index of the log shown
+    Log(usize),
+    /// Run of logs collapsed into a single row: first log index and count
+    Fold(usize, usize),
+}
+
 pub struct SectionLogs {
     /// Log offset
     pub vertical_scroll: usize,
@@ -28,93 +95,543 @@ pub struct SectionLogs {
     vertical_scroll_limit: usize,
 
     /// Log message storage
-    pub logs: Vec<LogMessage>,
+    pub logs: VecDeque<LogMessage>,
 
-    /// Log search feature
-    search_string: String,
-    search_string_log_idx: usize,
+    /// Logs kept, the oldest ones are dropped beyond it. 0 means no limit
+    max_logs: usize,
+
+    /// Lines still being received (i.e. progress bars updated with `\r`), one per source
+    partial_logs: Vec<LogMessage>,
+
+    /// Log search feature, one entry per search slot
+    searches: Vec<LogSearch>,
+    active_search: usize,
+
+    /// Count typed before `n`/`N`, along with the search slot active before typing it (digits
+    /// also select the slot)
+    pending_count: Option<(usize, usize)>,
 
     /// How many lines are displayed in a page, depends on screen size
     page_size: usize,
 
+    /// Width available for the logs, depends on screen size
+    page_width: u16,
+
+    /// Wrap the logs wider than the view instead of cutting them, a log can then take several
+    /// lines
+    wrap: bool,
+
+    /// Show a strip beside the logs with where the highlighted ones are
+    minimap: bool,
+
+    /// Show the logs as a hex dump instead of text, of every source or only of some
+    hex_all: bool,
+    hex_sources: HashSet<i32>,
+
+    /// Row of the selected log, moved with `j`/`k`. It stays on screen, scrolling drags it along
+    cursor: usize,
+
     /// Should the offset be updated automatically when a new log message comes
+    ///
+    /// Only changed explicitly, so it never flips behind the user's back:
+    /// - `G`/End jumps to the bottom and enables it, `f` toggles it
+    /// - Moving up (`k`, PageUp, `g`/Home, searches, `goto_time`) disables it
+    /// - Moving down (`j`, PageDown) leaves it as it is, even when reaching the bottom
     sticky: bool,
 
     /// Send commands to the Commander
     command_tx: Sender<Command>,
 
-    /// Should the source be shown
-    show_source_id: bool,
+    /// Should the source be shown, and how
+    source_prefix: SourcePrefix,
+
+    /// Names of the sources, by id. They are kept after the source is removed, its logs are
+    /// still there (and ids are not reused)
+    source_names: HashMap<i32, String>,
 
     /// Should the timestamp be shown
     show_timestamp: bool,
 
     /// Timestamp of last received log
     last_log_ts: LogTimestamp,
+
+    /// Split structured logs into aligned columns, if a template is configured
+    columns: Option<LogColumns>,
+    show_columns: bool,
+
+    /// Fold the logs not matched by a focus filter, and the folds opened by the user (first
+    /// log index)
+    fold: bool,
+    expanded_folds: HashSet<usize>,
+
+    /// UI colors
+    theme: Theme,
 }
 
 impl SectionLogs {
-    pub fn new(command_tx: Sender<Command>) -> SectionLogs {
+    pub fn new(
+        command_tx: Sender<Command>,
+        theme: Theme,
+        follow: bool,
+        columns: Option<LogColumns>,
+        max_logs: usize,
+    ) -> SectionLogs {
         SectionLogs {
+            theme,
             command_tx,
-            logs: Vec::new(),
-            search_string: String::new(),
-            search_string_log_idx: 0,
+            logs: VecDeque::new(),
+            max_logs,
+            partial_logs: Vec::new(),
+            searches: SEARCH_COLORS.iter().map(|c| LogSearch::new(*c)).collect(),
+            active_search: 0,
+            pending_count: None,
             page_size: 0,
-            sticky: true,
+            page_width: 0,
+            wrap: false,
+            minimap: false,
+            hex_all: false,
+            hex_sources: HashSet::new(),
+            cursor: 0,
+            sticky: follow,
             vertical_scroll: 0,
             vertical_scroll_limit: 0,
-            show_source_id: false,
+            source_prefix: SourcePrefix::Hidden,
+            source_names: HashMap::from([
+                (NOTE_SOURCE_ID, String::from("note")),
+                (APP_SOURCE_ID, String::from("uberlog")),
+                (STATUS_SOURCE_ID, String::from("status")),
+            ]),
             show_timestamp: false,
             last_log_ts: LogTimestamp::now(),
+            columns,
+            show_columns: true,
+            fold: true,
+            expanded_folds: HashSet::new(),
         }
     }
 
+    pub fn add_source(&mut self, id: u32, name: String) {
+        self.source_names.insert(id as i32, name);
+    }
+
     pub fn append_log(&mut self, log: LogMessage) {
-        self.logs.push(log);
+        self.logs.push_back(log);
         self.last_log_ts = LogTimestamp::now();
+        self.drop_oldest_logs();
+    }
+
+    /// Keep at most `max_logs` logs, shifting what refers to them so the view does not jump
+    fn drop_oldest_logs(&mut self) {
+        if self.max_logs == 0 || self.logs.len() <= self.max_logs {
+            return;
+        }
+        let dropped = self.logs.len() - self.max_logs;
+        self.logs.drain(..dropped);
+
+        // Rows only map one to one to logs without folds, then the position is approximate
+        self.vertical_scroll = self.vertical_scroll.saturating_sub(dropped);
+        self.cursor = self.cursor.saturating_sub(dropped);
+        for search in &mut self.searches {
+            search.log_idx = search.log_idx.saturating_sub(dropped);
+        }
+        self.expanded_folds = self
+            .expanded_folds
+            .iter()
+            .filter_map(|start| start.checked_sub(dropped))
+            .collect();
     }
 
     pub fn clear_logs(&mut self) {
         self.logs.clear();
+        self.partial_logs.clear();
+        self.expanded_folds.clear();
         self.vertical_scroll = 0;
+        self.cursor = 0;
     }
 
     pub fn update_logs(&mut self, new_logs: Vec<LogMessage>) {
-        self.logs = new_logs;
+        self.logs = new_logs.into();
+        self.expanded_folds.clear();
+        self.drop_oldest_logs();
+    }
+
+    /// Rows of the view
+    ///
+    /// When a focus filter matches some log, the runs of logs it does not match are collapsed
+    /// into a single row, unless the user opened them. Lines still being received are never
+    /// folded.
+    fn rows(&self) -> Vec<LogRow> {
+        let total = self.logs.len() + self.partial_logs.len();
+        if !self.is_folding() {
+            return (0..total).map(LogRow::Log).collect();
+        }
+
+        let mut rows = Vec::new();
+        let push_run = |rows: &mut Vec<LogRow>, start: usize, end: usize| {
+            match self.expanded_folds.contains(&start) {
+                true => rows.extend((start..end).map(LogRow::Log)),
+                false => rows.push(LogRow::Fold(start, end - start)),
+            }
+        };
+
+        let mut run_start = None;
+        for (idx, log) in self.logs.iter().enumerate() {
+            if log.focused {
+                if let Some(start) = run_start.take() {
+                    push_run(&mut rows, start, idx);
+                }
+                rows.push(LogRow::Log(idx));
+            } else if run_start.is_none() {
+                run_start = Some(idx);
+            }
+        }
+        if let Some(start) = run_start {
+            push_run(&mut rows, start, self.logs.len());
+        }
+        rows.extend((self.logs.len()..total).map(LogRow::Log));
+        rows
+    }
+
+    /// Whether some logs are currently collapsed into folds
+    fn is_folding(&self) -> bool {
+        self.fold && self.logs.iter().any(|log| log.focused)
+    }
+
+    /// Number of rows of the view
+    fn row_count(&self) -> usize {
+        match self.is_folding() {
+            true => self.rows().len(),
+            false => self.logs.len() + self.partial_logs.len(),
+        }
+    }
+
+    /// Up to `count` rows of the view starting at `start`
+    ///
+    /// Without folds rows map one to one to logs, so they are computed directly instead of
+    /// walking the whole buffer.
+    fn visible_rows(&self, start: usize, count: usize) -> Vec<LogRow> {
+        match self.is_folding() {
+            true => self.rows().into_iter().skip(start).take(count).collect(),
+            false => {
+                let total = self.logs.len() + self.partial_logs.len();
+                (start.min(total)..(start + count).min(total))
+                    .map(LogRow::Log)
+                    .collect()
+            }
+        }
+    }
+
+    /// Log by index, lines still being received come after the rest
+    fn log(&self, idx: usize) -> &LogMessage {
+        match self.logs.get(idx) {
+            Some(log) => log,
+            None => &self.partial_logs[idx - self.logs.len()],
+        }
+    }
+
+    /// Index of the (first) log shown in a row
+    fn log_at_row(&self, row: usize) -> usize {
+        match self.rows().get(row) {
+            Some(LogRow::Log(idx)) | Some(LogRow::Fold(idx, _)) => *idx,
+            None => self.logs.len().saturating_sub(1),
+        }
+    }
+
+    /// Row showing a log, opening the fold it is in if needed
+    fn row_of_log(&mut self, idx: usize) -> usize {
+        for (row, log_row) in self.rows().iter().enumerate() {
+            match *log_row {
+                LogRow::Log(log_idx) if log_idx == idx => return row,
+                LogRow::Fold(start, count) if (start..start + count).contains(&idx) => {
+                    self.expanded_folds.insert(start);
+                    return row + idx - start;
+                }
+                _ => (),
+            }
+        }
+        idx
+    }
+
+    /// Open the folds currently on screen
+    fn open_visible_folds(&mut self) {
+        let visible = self.visible_rows(self.vertical_scroll, self.page_size);
+        for log_row in visible {
+            if let LogRow::Fold(start, _) = log_row {
+                self.expanded_folds.insert(start);
+            }
+        }
+    }
+
+    /// Set (or remove, if `None`) the in-progress line of a source
+    pub fn update_partial_log(&mut self, id: u32, log: Option<LogMessage>) {
+        self.partial_logs.retain(|l| l.source_id != id as i32);
+        if let Some(log) = log {
+            self.partial_logs.push(log);
+        }
+    }
+
+    /// Set the text (and optionally the color) of the active search, and jump to its
+    /// `count`th match
+    ///
+    /// A named search becomes the active one first, taking a free slot the first time.
+    pub fn update_search_log(&mut self, log: String, color: Option<Color>, count: usize, name: Option<String>) {
+        if let Some(name) = name {
+            let slot = self
+                .searches
+                .iter()
+                .position(|s| s.name.as_ref() == Some(&name))
+                .or_else(|| self.searches.iter().position(|s| s.name.is_none() && s.text.is_empty()));
+            let Some(slot) = slot else {
+                let _ = self.command_tx.send(Command::PrintError(format!(
+                    "No free search slot for <{}>, clear one with an empty search",
+                    name
+                )));
+                return;
+            };
+            self.active_search = slot;
+            self.searches[slot].name = Some(name);
+        }
+
+        let search = &mut self.searches[self.active_search];
+        search.text = log;
+        if let Some(color) = color {
+            search.color = color;
+        }
+        for _ in 0..count {
+            self.find_log(SearchDirection::FOWARD);
+        }
+    }
+
+    /// Recompute the scroll state for a log area of `height` lines
+    ///
+    /// Called on every render, and also right away when the terminal is resized so the scroll
+    /// position is never out of range.
+    pub fn update_scroll_state(&mut self, height: usize) {
+        // Update scroll limit value (+2 to take into account borders)
+        let line_count = self.row_count();
+        if self.wrap {
+            self.vertical_scroll_limit = self.wrapped_scroll_limit(line_count, height.saturating_sub(2));
+        } else if height <= line_count {
+            self.vertical_scroll_limit = 2 + line_count - height;
+        } else {
+            self.vertical_scroll_limit = 0
+        }
+
+        // Update page size
+        self.page_size = height;
+
+        // Keep the offset within range
+        self.vertical_scroll = self.vertical_scroll.min(self.vertical_scroll_limit);
+
+        // Scroll to bottom if sticky
+        if self.sticky {
+            self.vertical_scroll = self.vertical_scroll_limit;
+        }
+
+        // Keep the cursor on screen, on the newest log while following them
+        if self.sticky {
+            self.cursor = line_count.saturating_sub(1);
+        }
+        let last_visible = self.vertical_scroll + self.visible_row_count() - 1;
+        self.cursor = self
+            .cursor
+            .clamp(self.vertical_scroll, last_visible)
+            .min(line_count.saturating_sub(1));
+    }
+
+    /// Rows shown whole in the view, when wrapping some of them take more lines and less fit
+    fn visible_row_count(&self) -> usize {
+        let height = self.page_size.saturating_sub(2).max(1);
+        if !self.wrap {
+            return height;
+        }
+
+        let lines = self.row_lines(&self.visible_rows(self.vertical_scroll, height));
+        let mut used = 0;
+        let mut fitting = 0;
+        for line in lines {
+            used += Paragraph::new(line)
+                .wrap(Wrap { trim: false })
+                .line_count(self.page_width);
+            if used > height {
+                break;
+            }
+            fitting += 1;
+        }
+
+        // A row taller than the view is shown anyway, from its start
+        fitting.max(1)
+    }
+
+    /// Indexes of the first and last logs in the view
+    fn visible_logs(&self) -> (usize, usize) {
+        let first = self.log_at_row(self.vertical_scroll);
+        let last = self.log_at_row(self.vertical_scroll + self.visible_row_count() - 1);
+        (first, last)
+    }
+
+    /// Move the cursor a row down, scrolling if it leaves the view
+    fn cursor_down(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.row_count().saturating_sub(1));
+        if self.cursor >= self.vertical_scroll + self.visible_row_count() {
+            self.scroll_down(1);
+        }
+    }
+
+    /// Move the cursor a row up, scrolling if it leaves the view. It stops following new logs
+    fn cursor_up(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+        if self.cursor < self.vertical_scroll {
+            self.vertical_scroll = self.cursor;
+        }
+        self.sticky = false;
+    }
+
+    /// Copy the log under the cursor to the clipboard
+    fn yank_selected_log(&mut self) {
+        let message = match self.rows().get(self.cursor) {
+            Some(LogRow::Log(idx)) => self.log(*idx).message.trim_end_matches(['\r', '\n']).to_string(),
+            Some(LogRow::Fold(..)) => {
+                let _ = self.command_tx.send(Command::PrintMessage(String::from(
+                    "Nothing to copy in a fold, open it with `o`",
+                )));
+                return;
+            }
+            None => return,
+        };
+        let _ = self.command_tx.send(Command::CopyToClipboard(message));
+    }
+
+    /// First row showing the bottom of the logs when they are wrapped into `height` lines
+    ///
+    /// Rows take a line or more, so only the last `height` rows have to be measured.
+    fn wrapped_scroll_limit(&self, row_count: usize, height: usize) -> usize {
+        let first = row_count.saturating_sub(height);
+        let lines = self.row_lines(&self.visible_rows(first, height));
+
+        let mut used = 0;
+        let mut fitting = 0;
+        for line in lines.into_iter().rev() {
+            used += Paragraph::new(line)
+                .wrap(Wrap { trim: false })
+                .line_count(self.page_width);
+            if used > height {
+                break;
+            }
+            fitting += 1;
+        }
+
+        // A row taller than the view is shown anyway, from its start
+        row_count.saturating_sub(fitting.max(1))
+    }
+
+    /// Scroll down `lines`, stopping at the bottom
+    fn scroll_down(&mut self, lines: usize) {
+        self.vertical_scroll = self
+            .vertical_scroll
+            .saturating_add(lines)
+            .min(self.vertical_scroll_limit);
+    }
+
+    /// Scroll down half a page, following new logs is left as it is
+    pub fn scroll_half_page_down(&mut self) {
+        self.scroll_down(self.page_size / 2);
+    }
+
+    /// Scroll up half a page, it stops following new logs
+    pub fn scroll_half_page_up(&mut self) {
+        self.vertical_scroll = self.vertical_scroll.saturating_sub(self.page_size / 2);
+        self.sticky = false;
+    }
+
+    /// Scroll to the first log received at or after `timestamp`
+    pub fn goto_time(&mut self, timestamp: LogTimestamp) {
+        let target = timestamp.second_count();
+        match self
+            .logs
+            .iter()
+            .position(|log| log.timestamp.second_count() >= target)
+        {
+            Some(idx) => {
+                self.cursor = self.row_of_log(idx);
+                self.vertical_scroll = self.cursor;
+                self.sticky = false;
+            }
+            None => {
+                let _ = self.command_tx.send(Command::PrintMessage(format!(
+                    "No logs at or after {}",
+                    timestamp.to_string()
+                )));
+            }
+        }
+    }
+
+    /// Scroll to the log at `idx`
+    pub fn goto_log(&mut self, idx: usize) {
+        let idx = idx.min(self.logs.len().saturating_sub(1));
+        self.cursor = self.row_of_log(idx);
+        self.vertical_scroll = self.cursor;
+        self.sticky = false;
     }
 
-    pub fn update_search_log(&mut self, log: String) {
-        self.search_string = log.clone();
-        self.find_log(log, SearchDirection::FOWARD);
+    /// Scroll to the closest highlighted log past the view, centering it
+    fn find_highlighted_log(&mut self, direction: SearchDirection) {
+        let (first_visible, last_visible) = self.visible_logs();
+        let found = match direction {
+            SearchDirection::FOWARD => (last_visible + 1..self.logs.len()).find(|&i| self.logs[i].highlighted),
+            SearchDirection::BACKWARD => (0..first_visible).rev().find(|&i| self.logs[i].highlighted),
+        };
+
+        match found {
+            Some(idx) => {
+                self.cursor = self.row_of_log(idx);
+                self.vertical_scroll = self.cursor.saturating_sub(self.page_size / 2);
+                self.sticky = false;
+            }
+            None => {
+                let _ = self
+                    .command_tx
+                    .send(Command::PrintMessage(String::from("No more highlighted logs")));
+            }
+        }
     }
 
-    /// Search a log containing the search_string text
+    /// Select which search slot `n`/`N` and new searches operate on
+    fn set_active_search(&mut self, slot: usize) {
+        if slot < self.searches.len() {
+            self.active_search = slot;
+        }
+    }
+
+    /// Search a log containing the text of the active search
     ///
-    /// If the search_string is empty, the search is disabled
-    fn find_log(&mut self, log: String, direction: SearchDirection) {
+    /// If the search text is empty, the search is disabled
+    fn find_log(&mut self, direction: SearchDirection) {
 
         // If there is nothing just return
-        if self.logs.is_empty() {
+        if self.logs.is_empty() || self.searches[self.active_search].text.is_empty() {
             return;
         }
 
-        // If search_string_log_idx is not within view, update it
-        if self.search_string_log_idx < self.vertical_scroll
-            || self.search_string_log_idx > (self.vertical_scroll + self.page_size)
-        {
-            self.search_string_log_idx = self.vertical_scroll;
+        let page_size = self.page_size;
+        let (first_visible, last_visible) = self.visible_logs();
+        let cursor_log = self.log_at_row(self.cursor);
+        let search = &mut self.searches[self.active_search];
+
+        // If the current match is not within view, search from the cursor
+        if search.log_idx < first_visible || search.log_idx > last_visible {
+            search.log_idx = cursor_log;
         }
 
         let start_idx = match direction {
-            SearchDirection::FOWARD => self
-                .search_string_log_idx
+            SearchDirection::FOWARD => search
+                .log_idx
                 .saturating_add(1)
                 .min(self.logs.len() - 1),
-            SearchDirection::BACKWARD => self.search_string_log_idx.saturating_sub(1),
+            SearchDirection::BACKWARD => search.log_idx.saturating_sub(1),
         };
 
-        if start_idx == self.search_string_log_idx {
+        if start_idx == search.log_idx {
             return;
         }
 
@@ -123,22 +640,16 @@ impl SectionLogs {
             SearchDirection::BACKWARD => 0,
         };
 
-        if end_idx == self.search_string_log_idx {
+        if end_idx == search.log_idx {
             return;
         }
 
         let mut i = start_idx;
+        let mut found = None;
         while i != end_idx {
-            if self.logs[i].message.contains(&log) {
-                self.search_string_log_idx = i;
-
-                let offset = self.page_size / 2;
-                let offset = match offset >= i {
-                    true => i,
-                    false => offset,
-                };
-
-                self.vertical_scroll = self.search_string_log_idx - offset;
+            if self.logs[i].message.contains(&search.text) {
+                search.log_idx = i;
+                found = Some(i);
                 break;
             }
 
@@ -147,74 +658,128 @@ impl SectionLogs {
                 SearchDirection::BACKWARD => i.saturating_sub(1),
             }
         }
-    }
-}
 
-/// Ratatui tabs and Paragarphs do not play too well together (https://github.com/ratatui/ratatui/issues/876) so for every line
-/// - Replace tabs with 4 spaces (hardcoded)
-/// - Replace `\r\n` to nothing
-/// - Replace `\n` to nothing
-/// - Replace `\r` to nothing
-fn sanitize_log_msg(line: &str) -> String {
-    let line = line.replace("\t", &" ".repeat(4));
-    let line = line.trim_end_matches("\r\n");
-    let line = line.trim_end_matches("\r");
-    let line = line.trim_end_matches("\n");
-    line.to_string()
-}
-
-impl LayoutSection for SectionLogs {
-    fn ui(&mut self, frame: &mut Frame, area: Rect) {
-        // Update scroll limit value (+2 to take into account borders)
-        if area.height as usize <= self.logs.len() {
-            self.vertical_scroll_limit = 2 + self.logs.len() - area.height as usize;
-        } else {
-            self.vertical_scroll_limit = 0
+        // Center the match and select it, opening its fold
+        if let Some(idx) = found {
+            self.cursor = self.row_of_log(idx);
+            self.vertical_scroll = self.cursor.saturating_sub(page_size / 2);
         }
+    }
 
-        // Update page size
-        self.page_size = area.height as usize;
+    /// Lines showing `rows`
+    ///
+    /// Columns are sized to the logs among them.
+    fn row_lines(&self, rows: &[LogRow]) -> Vec<Line<'static>> {
+        let column_widths = match (&self.columns, self.show_columns) {
+            (Some(columns), true) => {
+                let visible_logs = rows
+                    .iter()
+                    .filter_map(|row| match row {
+                        LogRow::Log(idx) => Some(self.log(*idx).message.as_str()),
+                        LogRow::Fold(..) => None,
+                    });
+                Some((columns, columns.widths(visible_logs)))
+            }
+            _ => None,
+        };
 
-        // Scroll to bottom if sticky, otherwise check if sticky
-        if self.sticky {
-            self.vertical_scroll = self.vertical_scroll_limit;
-        } else if self.vertical_scroll == self.vertical_scroll_limit {
-            self.sticky = true;
-        }
+        let source_name_width = self
+            .source_names
+            .values()
+            .map(|name| name.chars().count())
+            .max()
+            .unwrap_or(0);
 
-        // Draw ui
         let mut log_lines = Vec::new();
-        for (idx, log) in self.logs.iter().enumerate() {
-            // Change style if it is the searched-for string
-            let log_style =
-                match idx == self.search_string_log_idx && !self.search_string.is_empty() {
-                    false => log.style,
-                    true => Style {
-                        fg: log.style.bg,
-                        bg: log.style.fg,
-                        ..Default::default()
-                    }
-                    .add_modifier(style::Modifier::BOLD),
-                };
+        for log_row in rows.iter().copied() {
+            let idx = match log_row {
+                LogRow::Log(idx) => idx,
+                LogRow::Fold(_, count) => {
+                    log_lines.push(
+                        Line::from(format!("\u{2026} {} lines \u{2026}", count))
+                            .style(self.theme.border.add_modifier(Modifier::DIM)),
+                    );
+                    continue;
+                }
+            };
+            let log = self.log(idx);
 
-            // Optionally prepend timestamp
-            let ts_string = match self.show_timestamp {
-                true => format!("{} - ", log.timestamp.to_string()),
-                false => String::new(),
+            // Change style if it matches a search, the active one takes precedence. The
+            // current match of the search stands out
+            let matching_search = self
+                .searches
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| !s.text.is_empty() && log.message.contains(&s.text))
+                .min_by_key(|(slot, _)| *slot != self.active_search);
+            let plain = matching_search.is_none() && log.style == LogMessage::default_style();
+            let log_style = match matching_search {
+                None if log.style == LogMessage::default_style() => self.theme.log_text,
+                None => log.style,
+                Some((_, search)) => {
+                    let style = self.theme.search_highlight.bg(search.color);
+                    match search.log_idx == idx {
+                        true => style.add_modifier(style::Modifier::BOLD),
+                        false => style,
+                    }
+                }
             };
 
-            // Optionally prepend source id
-            let source_id = match self.show_source_id {
-                true => format!("source_id_{} | ", log.source_id),
-                false => String::new(),
+            // Optionally prepend timestamp, dimmer than the message since it is not part of it
+            let mut prefix = Vec::new();
+            if self.show_timestamp {
+                prefix.push(Span::styled(
+                    format!("{} - ", log.timestamp.to_string()),
+                    log_style.add_modifier(Modifier::DIM),
+                ));
+            }
+
+            // Optionally prepend the source, by name or id. Padded so the messages of every
+            // source start at the same column
+            let source_id = match (self.source_prefix, self.source_names.get(&log.source_id)) {
+                (SourcePrefix::Hidden, _) => String::new(),
+                (SourcePrefix::Name, Some(name)) => {
+                    format!("{:<width$} | ", name, width = source_name_width)
+                }
+                (SourcePrefix::Name, None) | (SourcePrefix::Id, _) => {
+                    format!("source_id_{} | ", log.source_id)
+                }
             };
+            if !source_id.is_empty() {
+                prefix.push(Span::styled(source_id, log_style));
+            }
+
+            if self.is_hex(log.source_id) {
+                let mut spans = prefix;
+                spans.push(Span::styled(hex_dump(log), log_style));
+                log_lines.push(Line::from(spans));
+                continue;
+            }
 
-            // Form message
-            let line = format!("{}{}{}", ts_string, source_id, log.message);
-            debug!("line:\n{:?}", line);
+            // Structured logs are aligned in columns, with their own colors unless the line is
+            // highlighted
+            if let Some((columns, widths)) = &column_widths
+                && let Some(fields) = columns.split(&log.message)
+            {
+                let mut spans = prefix;
+                let last = fields.len() - 1;
+                for (column, field) in fields.into_iter().enumerate() {
+                    let style = match plain {
+                        true => log_style.patch(columns.style(column)),
+                        false => log_style,
+                    };
+                    let text = match column == last {
+                        true => field,
+                        false => format!("{} \u{2502} ", LogColumns::pad(&field, widths[column])),
+                    };
+                    spans.push(Span::styled(text, style));
+                }
+                log_lines.push(Line::from(spans));
+                continue;
+            }
 
             // Sanitize it
-            let sanitized_line = sanitize_log_msg(&line);
+            let sanitized_line = sanitize_log_msg(&log.message);
             debug!("sanitized_line:\n{:?}", sanitized_line);
 
             //// Try with cleaning up
@@ -233,66 +798,253 @@ impl LayoutSection for SectionLogs {
 
             debug!("processed_line:\n{:?}", line);
 
+            line.spans.splice(0..0, prefix);
             log_lines.push(line);
         }
 
+        log_lines
+    }
+
+    /// Switch between text and hex dump, for a source or for all of them (`None`)
+    ///
+    /// Only the rendering changes, so it is instant whatever the size of the buffer.
+    pub fn toggle_hex_view(&mut self, id: Option<i32>) {
+        match id {
+            Some(id) if !self.hex_sources.remove(&id) => {
+                self.hex_sources.insert(id);
+            }
+            Some(_) => (),
+            None if self.hex_all || !self.hex_sources.is_empty() => {
+                self.hex_all = false;
+                self.hex_sources.clear();
+            }
+            None => self.hex_all = true,
+        }
+    }
+
+    fn is_hex(&self, id: i32) -> bool {
+        self.hex_all || self.hex_sources.contains(&id)
+    }
+
+    /// Lines of a minimap `height` cells high
+    ///
+    /// Every cell covers the same share of the logs, and shows how many of them are
+    /// highlighted, in the color of the last one. The cells of the logs on screen are marked.
+    fn minimap_lines(&self, height: usize) -> Vec<Line<'static>> {
+        let log_count = self.logs.len();
+        if height == 0 || log_count == 0 {
+            return Vec::new();
+        }
+        let (first_visible, last_visible) = self.visible_logs();
+
+        // Fewer logs than cells leave the cells at the bottom empty
+        let cells = height.min(log_count);
+        let mut lines = Vec::with_capacity(cells);
+        for cell in 0..cells {
+            let start = cell * log_count / cells;
+            let end = ((cell + 1) * log_count / cells).max(start + 1);
+
+            let mut count = 0;
+            let mut style = Style::default();
+            for log in self.logs.range(start..end).filter(|log| log.highlighted) {
+                count += 1;
+                style = log.style;
+            }
+
+            // Any highlighted log shows, even if it is one among thousands
+            let level = match count {
+                0 => 0,
+                _ => 1 + (count * (MINIMAP_DENSITY.len() - 2)) / (end - start),
+            };
+            if start <= last_visible && first_visible < end {
+                style = style.bg(Color::DarkGray);
+            }
+            lines.push(Line::from(Span::styled(
+                MINIMAP_DENSITY[level].to_string().repeat((MINIMAP_WIDTH - 2) as usize),
+                style,
+            )));
+        }
+        lines
+    }
+}
+
+/// Bytes of a log in hex, followed by them as ASCII (`.` for the rest)
+///
+/// The bytes it was received as if they were kept, otherwise the ones of the decoded text.
+fn hex_dump(log: &LogMessage) -> String {
+    let bytes = log.raw.as_deref().unwrap_or(log.message.as_bytes());
+    let hex: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    let ascii: String = bytes
+        .iter()
+        .map(|b| match b.is_ascii_graphic() || *b == b' ' {
+            true => *b as char,
+            false => '.',
+        })
+        .collect();
+    format!("{}  |{}|", hex.join(" "), ascii)
+}
+
+/// Ratatui tabs and Paragarphs do not play too well together (https://github.com/ratatui/ratatui/issues/876) so for every line
+/// - Replace tabs with 4 spaces (hardcoded)
+/// - Replace `\r\n` to nothing
+/// - Replace `\n` to nothing
+/// - Replace `\r` to nothing
+pub(super) fn sanitize_log_msg(line: &str) -> String {
+    let line = line.replace("\t", &" ".repeat(4));
+    let line = line.trim_end_matches("\r\n");
+    let line = line.trim_end_matches("\r");
+    let line = line.trim_end_matches("\n");
+    line.to_string()
+}
+
+impl LayoutSection for SectionLogs {
+    fn ui(&mut self, frame: &mut Frame, area: Rect) {
+        // The minimap takes a few columns at the right
+        let (area, minimap_area) = match self.minimap && area.width > MINIMAP_WIDTH {
+            true => {
+                let chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Min(1), Constraint::Length(MINIMAP_WIDTH)])
+                    .split(area);
+                (chunks[0], Some(chunks[1]))
+            }
+            false => (area, None),
+        };
+
+        // -2 to take into account borders
+        self.page_width = area.width.saturating_sub(2);
+        self.update_scroll_state(area.height as usize);
+
+        if let Some(minimap_area) = minimap_area {
+            let minimap_block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(self.theme.border);
+            let minimap = Paragraph::new(self.minimap_lines(minimap_area.height.saturating_sub(2) as usize))
+                .block(minimap_block);
+            frame.render_widget(minimap, minimap_area);
+        }
+
+        // Only the rows on screen are built. When wrapping a row takes at least a line, so no
+        // more than that can be shown either
+        let rows = self.visible_rows(self.vertical_scroll, self.page_size.saturating_sub(2));
+        let mut log_lines = self.row_lines(&rows);
+        if let Some(line) = self
+            .cursor
+            .checked_sub(self.vertical_scroll)
+            .and_then(|row| log_lines.get_mut(row))
+        {
+            for span in &mut line.spans {
+                span.style = span.style.patch(self.theme.selected_row);
+            }
+            line.style = line.style.patch(self.theme.selected_row);
+        }
+
         // Calculate timestamp in seconds
         let ts_dif_sec = LogTimestamp::now().second_count() - self.last_log_ts.second_count();
-        let log_block_title = Line::from(format!("Logs [{:4}]", ts_dif_sec));
+        let active_search = &self.searches[self.active_search];
+        let follow_text = match self.sticky {
+            true => "[FOLLOW] ",
+            false => "",
+        };
+        let hex_text = match (self.hex_all, self.hex_sources.len()) {
+            (true, _) => String::from("[HEX] "),
+            (false, 0) => String::new(),
+            (false, count) => format!("[HEX {} sources] ", count),
+        };
+        let log_block_title = Line::from(vec![
+            Span::raw(format!("Logs [{:4}] {}{}", ts_dif_sec, follow_text, hex_text)),
+            Span::styled(
+                format!(
+                    "[/{}: {}]",
+                    active_search.name.clone().unwrap_or((self.active_search + 1).to_string()),
+                    active_search.text
+                ),
+                Style::default().fg(active_search.color),
+            ),
+        ]);
         let log_block = Block::default()
             .title(log_block_title)
+            .title_style(self.theme.title)
             .borders(Borders::ALL)
+            .border_style(self.theme.border)
             .style(Style::default());
 
-        let log_content = Paragraph::new(log_lines)
-            .block(log_block)
-            .scroll((self.vertical_scroll as u16, 0));
+        let mut log_content = Paragraph::new(log_lines).block(log_block);
+        if self.wrap {
+            log_content = log_content.wrap(Wrap { trim: false });
+        }
 
         // Render
         frame.render_widget(log_content, area);
     }
 
     fn process_key(&mut self, key: crossterm::event::KeyCode) {
+        // A count only applies to the key right after it
+        let pending_count = self.pending_count.take();
+
         match key {
             // Movement
             KeyCode::Char('j') | KeyCode::Down => {
-                self.vertical_scroll = self
-                    .vertical_scroll
-                    .saturating_add(1)
-                    .min(self.vertical_scroll_limit);
+                self.cursor_down();
             }
             KeyCode::Char('k') | KeyCode::Up => {
-                self.vertical_scroll = self.vertical_scroll.saturating_sub(1);
-                self.sticky = false;
+                self.cursor_up();
+            }
+            // Copy the selected log
+            KeyCode::Char('y') => {
+                self.yank_selected_log();
             }
             KeyCode::Home | KeyCode::Char('g') => {
                 self.vertical_scroll = 0;
+                self.cursor = 0;
                 self.sticky = false;
             }
             KeyCode::PageDown => {
-                self.vertical_scroll = self
-                    .vertical_scroll
-                    .saturating_add(self.page_size)
-                    .min(self.vertical_scroll_limit);
+                self.scroll_down(self.page_size);
             }
             KeyCode::End | KeyCode::Char('G') => {
-                self.vertical_scroll = self
-                    .vertical_scroll
-                    .saturating_add(self.vertical_scroll_limit)
-                    .min(self.vertical_scroll_limit);
+                self.vertical_scroll = self.vertical_scroll_limit;
+                self.sticky = true;
+            }
+            // Toggle following new logs
+            KeyCode::Char('f') => {
+                self.sticky = !self.sticky;
             }
             KeyCode::PageUp => {
                 self.vertical_scroll = self.vertical_scroll.saturating_sub(self.page_size);
                 self.sticky = false;
             }
-            // Show source id
+            // Show source name, then id
             KeyCode::Char('s') => {
-                self.show_source_id = !self.show_source_id;
+                self.source_prefix = match self.source_prefix {
+                    SourcePrefix::Hidden => SourcePrefix::Name,
+                    SourcePrefix::Name => SourcePrefix::Id,
+                    SourcePrefix::Id => SourcePrefix::Hidden,
+                };
             }
             // Show timestamp
             KeyCode::Char('t') => {
                 self.show_timestamp = !self.show_timestamp;
             }
+            // Wrap long logs
+            KeyCode::Char('w') => {
+                self.wrap = !self.wrap;
+            }
+            // Hex dump of every source
+            KeyCode::Char('x') => {
+                self.toggle_hex_view(None);
+            }
+
+            // Overview of the highlighted logs, and jumping between them
+            KeyCode::Char('m') => {
+                self.minimap = !self.minimap;
+            }
+            KeyCode::Char(']') => {
+                self.find_highlighted_log(SearchDirection::FOWARD);
+            }
+            KeyCode::Char('[') => {
+                self.find_highlighted_log(SearchDirection::BACKWARD);
+            }
 
             // Clear screent
             KeyCode::Char('C') => {
@@ -300,16 +1052,49 @@ impl LayoutSection for SectionLogs {
             }
 
             // Search log
-            KeyCode::Char('n') => {
-                if !self.search_string.is_empty() {
-                    self.find_log(self.search_string.clone(), SearchDirection::FOWARD);
+            KeyCode::Char(c @ ('n' | 'N')) => {
+                // The digits of the count selected a slot on the way, go back to the original
+                let count = match pending_count {
+                    Some((count, slot)) => {
+                        self.active_search = slot;
+                        count
+                    }
+                    None => 1,
+                };
+                if !self.searches[self.active_search].text.is_empty() {
+                    for _ in 0..count {
+                        match c {
+                            'n' => self.find_log(SearchDirection::FOWARD),
+                            _ => self.find_log(SearchDirection::BACKWARD),
+                        }
+                    }
                     self.sticky = false;
                 }
             }
-            KeyCode::Char('N') => {
-                if !self.search_string.is_empty() {
-                    self.find_log(self.search_string.clone(), SearchDirection::BACKWARD);
-                    self.sticky = false;
+            // Folding of the logs not matched by focus filters
+            KeyCode::Char('z') => {
+                self.fold = !self.fold;
+                self.expanded_folds.clear();
+            }
+            KeyCode::Char('o') => {
+                self.open_visible_folds();
+            }
+            // Toggle the column layout of structured logs
+            KeyCode::Char('|') => {
+                self.show_columns = !self.show_columns;
+            }
+            // Select search slot, digits are also a count for `n`/`N` (i.e. `3n`)
+            KeyCode::Char(c @ '0'..='9') => {
+                let digit = c as usize - '0' as usize;
+                let (count, slot) = match pending_count {
+                    Some((count, slot)) => (count.saturating_mul(10).saturating_add(digit), slot),
+                    None => (digit, self.active_search),
+                };
+                if digit > 0 {
+                    self.set_active_search(digit - 1);
+                }
+                if count > 0 {
+                    self.pending_count = Some((count, slot));
                 }
             }
             _ => (),
@@ -2,18 +2,27 @@ use std::sync::mpsc::Sender;
 
 use crossterm::event::KeyCode;
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect}, style::{Modifier, Style}, text::Line, widgets::{Block, BorderType, Borders, Gauge, Paragraph}, Frame
+    layout::{Constraint, Direction, Layout, Rect}, style::Style, text::Line, widgets::{Block, BorderType, Borders, Gauge, Paragraph}, Frame
 };
 use tracing::error;
 
 use crate::commander::Command;
 
-use super::LayoutSection;
+use super::{LayoutSection, theme::Theme};
 
 struct SourceInformation {
     /// Whether it is currently connected or not
     connected: bool,
 
+    /// Whether it already provided all its data (i.e. a file read till the end)
+    completed: bool,
+
+    /// Its logs are not being shown
+    paused: bool,
+
+    /// Lines received from it
+    line_count: usize,
+
     /// Name to display about the source
     name: String,
 
@@ -32,6 +41,9 @@ impl SourceInformation {
             id,
             name,
             connected: false,
+            completed: false,
+            paused: false,
+            line_count: 0,
             progress: 0,
             progress_stage: String::new(),
         }
@@ -39,6 +51,12 @@ impl SourceInformation {
 
     fn set_connected(&mut self, connected: bool) {
         self.connected = connected;
+        self.completed = false;
+    }
+
+    fn set_completed(&mut self) {
+        self.connected = false;
+        self.completed = true;
     }
 
     fn is_connected(&self) -> bool {
@@ -63,14 +81,18 @@ pub struct SectionSources {
 
     /// Currently selected source
     selected_source_idx: usize,
+
+    /// UI colors
+    theme: Theme,
 }
 
 impl SectionSources {
-    pub fn new(command_tx: Sender<Command>) -> Self {
+    pub fn new(command_tx: Sender<Command>, theme: Theme) -> Self {
         SectionSources {
             command_tx,
             sources: Vec::new(),
             selected_source_idx: 0,
+            theme,
         }
     }
 
@@ -82,6 +104,28 @@ impl SectionSources {
         }
     }
 
+    pub fn set_completed(&mut self, id: u32) {
+        if let Some(idx) = self.get_source_idx(id) {
+            self.sources[idx].set_completed();
+        } else {
+            error!("Unable to update source with ID {}, does not exist", id);
+        }
+    }
+
+    pub fn set_paused(&mut self, id: u32, is_paused: bool) {
+        if let Some(idx) = self.get_source_idx(id) {
+            self.sources[idx].paused = is_paused;
+        } else {
+            error!("Unable to update source with ID {}, does not exist", id);
+        }
+    }
+
+    pub fn set_line_count(&mut self, id: u32, line_count: usize) {
+        if let Some(idx) = self.get_source_idx(id) {
+            self.sources[idx].line_count = line_count;
+        }
+    }
+
     pub fn add_source(&mut self, id: u32, name: String) {
         self.sources.push(SourceInformation::new(id, name));
     }
@@ -117,15 +161,23 @@ impl SectionSources {
     fn draw_source_info(&mut self, frame: &mut Frame, area: Rect, idx: usize) {
 
         let source_info = self.sources.get(idx).unwrap();
-        let status = match source_info.is_connected() {
-            true => "Connected",
-            false => "Not connected",
+        let status = match (source_info.is_connected(), source_info.completed) {
+            (true, _) => "Connected",
+            (false, true) => "Finished",
+            (false, false) => "Not connected",
         };
 
         // Create line and make it Bold if it is the currently selected source
-        let mut line = Line::from(format!(" {} | {} {}", status, source_info.get_name(), source_info.progress_stage));
+        let mut line = Line::from(format!(
+            " {}{} | {} | {} lines {}",
+            status,
+            if source_info.paused { " PAUSED" } else { "" },
+            source_info.get_name(),
+            source_info.line_count,
+            source_info.progress_stage
+        ));
         if idx == self.selected_source_idx {
-            line.style = line.style.add_modifier(Modifier::BOLD);
+            line.style = line.style.patch(self.theme.selected_row);
         }
 
         frame.render_widget(line, area);
@@ -152,8 +204,10 @@ impl LayoutSection for SectionSources {
         let probse_block_title = Line::from("Log Sources");
         let probes_block = Block::default()
             .title(probse_block_title)
+            .title_style(self.theme.title)
             .borders(Borders::ALL)
             .border_type(BorderType::Double)
+            .border_style(self.theme.border)
             .style(Style::default());
 
         // Get the inner area before consuming probes_block
@@ -245,9 +299,28 @@ impl LayoutSection for SectionSources {
                 let _ = self.command_tx.send(Command::RefreshProbeInfo);
             }
             KeyCode::Char('R') => {
+                if self.sources.is_empty() {
+                    return;
+                }
+                let _ = self
+                    .command_tx
+                    .send(Command::Reset(self.sources[self.selected_source_idx].id, false));
+            }
+            KeyCode::Char('!') => {
+                if self.sources.is_empty() {
+                    return;
+                }
+                let _ = self
+                    .command_tx
+                    .send(Command::Reset(self.sources[self.selected_source_idx].id, true));
+            }
+            KeyCode::Char('p') => {
+                if self.sources.is_empty() {
+                    return;
+                }
                 let _ = self
                     .command_tx
-                    .send(Command::Reset(self.sources[self.selected_source_idx].id));
+                    .send(Command::TogglePause(self.sources[self.selected_source_idx].id));
             }
             KeyCode::Char('l') => {
                 let _ = self
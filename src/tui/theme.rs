@@ -0,0 +1,95 @@
+use std::str::FromStr;
+
+use ratatui::style::{Color, Modifier, Style};
+use tracing::warn;
+
+use crate::configuration::ThemeConfiguration;
+
+/// Styles used across the UI
+///
+/// Built from one of the built-in themes, selected by name in the configuration file, with
+/// the user overrides applied on top.
+#[derive(Clone, Debug)]
+pub struct Theme {
+    /// Borders of the blocks
+    pub border: Style,
+
+    /// Titles of the blocks
+    pub title: Style,
+
+    /// Bottom line (messages and command input)
+    pub status_line: Style,
+
+    /// Currently selected row in lists
+    pub selected_row: Style,
+
+    /// Text of the current search match (background is the search color)
+    pub search_highlight: Style,
+
+    /// Logs not affected by any filter
+    pub log_text: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    /// Meant for terminals with dark background
+    pub fn dark() -> Self {
+        Theme {
+            border: Style::default(),
+            title: Style::default(),
+            status_line: Style::default(),
+            selected_row: Style::default().add_modifier(Modifier::BOLD),
+            search_highlight: Style::default().fg(Color::Black),
+            log_text: Style::default().add_modifier(Modifier::DIM),
+        }
+    }
+
+    /// Meant for terminals with light background, where dimmed text is hard to read
+    pub fn light() -> Self {
+        Theme {
+            border: Style::default().fg(Color::DarkGray),
+            title: Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            status_line: Style::default().fg(Color::Black),
+            selected_row: Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            search_highlight: Style::default().fg(Color::White),
+            log_text: Style::default().fg(Color::Black),
+        }
+    }
+
+    /// Resolve the theme described in the configuration
+    pub fn from_cfg(cfg: &ThemeConfiguration) -> Self {
+        let mut theme = match cfg.name.as_str() {
+            "dark" => Theme::dark(),
+            "light" => Theme::light(),
+            name => {
+                warn!("Unknown theme <{}>, using default", name);
+                Theme::default()
+            }
+        };
+
+        // Apply overrides, only the foreground color is configurable
+        let overrides = [
+            (&cfg.border, &mut theme.border),
+            (&cfg.title, &mut theme.title),
+            (&cfg.status_line, &mut theme.status_line),
+            (&cfg.selected_row, &mut theme.selected_row),
+            (&cfg.search_highlight, &mut theme.search_highlight),
+            (&cfg.log_text, &mut theme.log_text),
+        ];
+        for (color, style) in overrides {
+            if let Some(color) = color {
+                match Color::from_str(color) {
+                    Ok(color) => *style = style.fg(color),
+                    Err(_) => warn!("Unknown color <{}> in theme", color),
+                }
+            }
+        }
+
+        theme
+    }
+}
@@ -0,0 +1,458 @@
+use std::sync::mpsc::{self, Receiver};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use uberlog_lib::{
+    LogFilter, LogFilterScope, LogFilterType,
+    command_parser::CommandParser,
+    commander::{self, Command, ExportFormat},
+    configuration::Alias,
+};
+
+fn new_parser() -> (CommandParser, Receiver<Command>) {
+    new_parser_with_aliases(Vec::new())
+}
+
+/// Parser with `:find` and `:filter` registered, as the application does
+fn new_parser_with_aliases(aliases: Vec<Alias>) -> (CommandParser, Receiver<Command>) {
+    let (tx, rx) = mpsc::channel();
+    let mut parser = CommandParser::new(tx, aliases);
+    parser.register_instruction(String::from(":find"), commander::find_log);
+    parser.register_instruction(String::from(":filter"), commander::add_filter);
+    (parser, rx)
+}
+
+/// Type a whole command and press enter
+fn run(parser: &mut CommandParser, text: &str) {
+    type_str(parser, text);
+    parser.process_key(KeyEvent::from(KeyCode::Enter));
+}
+
+fn type_str(parser: &mut CommandParser, text: &str) {
+    for c in text.chars() {
+        parser.process_key(KeyEvent::from(KeyCode::Char(c)));
+    }
+}
+
+fn ctrl(parser: &mut CommandParser, c: char) {
+    parser.process_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+}
+
+#[test]
+fn backspace_on_prompt_cancels_parsing() {
+    let (mut parser, _rx) = new_parser();
+
+    type_str(&mut parser, ":a");
+    assert!(!parser.is_idle());
+
+    parser.process_key(KeyEvent::from(KeyCode::Backspace));
+    assert!(!parser.is_idle());
+    assert_eq!(parser.get_parsed_cmd(), ":");
+
+    parser.process_key(KeyEvent::from(KeyCode::Backspace));
+    assert!(parser.is_idle());
+    assert_eq!(parser.get_parsed_cmd(), "");
+}
+
+#[test]
+fn ctrl_w_deletes_previous_word() {
+    let (mut parser, _rx) = new_parser();
+
+    type_str(&mut parser, ":filter h red ");
+    ctrl(&mut parser, 'w');
+    assert_eq!(parser.get_parsed_cmd(), ":filter h ");
+
+    ctrl(&mut parser, 'w');
+    ctrl(&mut parser, 'w');
+    assert_eq!(parser.get_parsed_cmd(), ":");
+
+    ctrl(&mut parser, 'w');
+    assert_eq!(parser.get_parsed_cmd(), ":");
+    assert!(!parser.is_idle());
+}
+
+#[test]
+fn ctrl_u_keeps_prompt() {
+    let (mut parser, _rx) = new_parser();
+
+    type_str(&mut parser, "/some search");
+    ctrl(&mut parser, 'u');
+    assert_eq!(parser.get_parsed_cmd(), "/");
+    assert!(!parser.is_idle());
+}
+
+#[test]
+fn find_command() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, ":find foo");
+    assert!(parser.is_idle());
+    match rx.try_recv() {
+        Ok(Command::FindLog(text, None, 1, None)) => assert_eq!(text, "foo"),
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn find_command_with_count() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, ":find foo 5");
+    match rx.try_recv() {
+        Ok(Command::FindLog(text, None, 5, None)) => assert_eq!(text, "foo"),
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+
+    run(&mut parser, ":find foo red 3");
+    match rx.try_recv() {
+        Ok(Command::FindLog(text, Some(_), 3, None)) => assert_eq!(text, "foo"),
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+}
+
+#[test]
+fn find_command_with_name() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, ":find --name tx send cyan");
+    match rx.try_recv() {
+        Ok(Command::FindLog(text, Some(_), 1, Some(name))) => {
+            assert_eq!(text, "send");
+            assert_eq!(name, "tx");
+        }
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+
+    run(&mut parser, ":find send --name");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+    run(&mut parser, ":find --name tx");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+}
+
+#[test]
+fn export_command() {
+    let (mut parser, rx) = new_parser();
+    parser.register_instruction(String::from(":export"), commander::export);
+
+    run(&mut parser, ":export out.log");
+    match rx.try_recv() {
+        Ok(Command::ExportLogs(path, ExportFormat::Raw, false)) => assert_eq!(path, "out.log"),
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+
+    run(&mut parser, ":export out.json --format json");
+    match rx.try_recv() {
+        Ok(Command::ExportLogs(path, ExportFormat::Json, false)) => assert_eq!(path, "out.json"),
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+
+    run(&mut parser, ":export out.log --view --format syslog");
+    match rx.try_recv() {
+        Ok(Command::ExportLogs(path, ExportFormat::Syslog, true)) => assert_eq!(path, "out.log"),
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+
+    run(&mut parser, ":export out.log --format");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+
+    run(&mut parser, ":export out.log --format xml");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn stream_in_modes() {
+    let (mut parser, rx) = new_parser();
+    parser.register_instruction(String::from(":stream_in"), commander::stream_file);
+
+    for (text, follow, tail) in [
+        (":stream_in app.log", true, false),
+        (":stream_in --once app.log", false, false),
+        (":stream_in -f app.log", true, true),
+    ] {
+        run(&mut parser, text);
+        match rx.try_recv() {
+            Ok(Command::StreamFile(path, f, t)) => assert_eq!((path.as_str(), f, t), ("app.log", follow, tail)),
+            other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+        }
+    }
+
+    run(&mut parser, ":stream_in -f");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+}
+
+#[test]
+fn hex_command() {
+    let (mut parser, rx) = new_parser();
+    parser.register_instruction(String::from(":hex"), commander::hex);
+
+    run(&mut parser, ":hex");
+    assert!(matches!(rx.try_recv(), Ok(Command::ToggleHexView(None))));
+    run(&mut parser, ":hex 2");
+    assert!(matches!(rx.try_recv(), Ok(Command::ToggleHexView(Some(2)))));
+    run(&mut parser, ":hex uart");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+}
+
+#[test]
+fn loglevel_command() {
+    let (mut parser, rx) = new_parser();
+    parser.register_instruction(String::from(":loglevel"), commander::loglevel);
+
+    run(&mut parser, ":loglevel debug");
+    assert!(matches!(rx.try_recv(), Ok(Command::SetLogLevel(None, level)) if level == "debug"));
+    run(&mut parser, ":loglevel 3 Main app (RTT)");
+    assert!(matches!(
+        rx.try_recv(),
+        Ok(Command::SetLogLevel(Some(target), level)) if target == "Main app (RTT)" && level == "3"
+    ));
+    run(&mut parser, ":loglevel");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+}
+
+#[test]
+fn on_match_keeps_shell_syntax() {
+    let (mut parser, rx) = new_parser();
+    parser.register_instruction(String::from(":on_match"), commander::on_match);
+
+    run(&mut parser, ":on_match PANIC dmesg | tail -n 5 > panic.txt && sync");
+    assert!(matches!(
+        rx.try_recv(),
+        Ok(Command::AddMatchTrigger(pattern, command))
+            if pattern == "PANIC" && command == "dmesg | tail -n 5 > panic.txt && sync"
+    ));
+    run(&mut parser, ":on_match PANIC \"pkill -f rig\"");
+    assert!(matches!(
+        rx.try_recv(),
+        Ok(Command::AddMatchTrigger(_, command)) if command == "pkill -f rig"
+    ));
+    run(&mut parser, ":on_match PANIC");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+}
+
+#[test]
+fn slash_search_becomes_find() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, "/bar baz");
+    assert!(parser.is_idle());
+    match rx.try_recv() {
+        Ok(Command::FindLog(text, None, 1, None)) => assert_eq!(text, "bar baz"),
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+}
+
+#[test]
+fn alias_is_expanded() {
+    let (mut parser, rx) = new_parser_with_aliases(vec![Alias {
+        alias: String::from(":fe"),
+        expanded: String::from(":filter e"),
+    }]);
+
+    run(&mut parser, ":fe noise");
+    match rx.try_recv() {
+        Ok(Command::AddFilter(filter)) => {
+            assert_eq!(filter.kind, LogFilterType::Exclusion);
+            assert_eq!(filter.msg, "noise");
+        }
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+}
+
+#[test]
+fn filter_with_group() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, ":filter @net i wifi");
+    match rx.try_recv() {
+        Ok(Command::AddFilter(filter)) => {
+            assert_eq!(filter.kind, LogFilterType::Inclusion);
+            assert_eq!(filter.msg, "wifi");
+            assert_eq!(filter.group.as_deref(), Some("net"));
+        }
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+
+    run(&mut parser, ":filter @ i wifi");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+}
+
+#[test]
+fn disabled_filter() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, ":filter off @net e wifi");
+    match rx.try_recv() {
+        Ok(Command::AddFilter(filter)) => {
+            assert!(!filter.enabled);
+            assert_eq!(filter.group.as_deref(), Some("net"));
+        }
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+
+    run(&mut parser, ":filter off");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+}
+
+#[test]
+fn column_range_filter() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, ":filter col:3-5 e ERR");
+    match rx.try_recv() {
+        Ok(Command::AddFilter(filter)) => {
+            assert_eq!(filter.kind, LogFilterType::Exclusion);
+            assert_eq!(filter.columns, Some((3, 5)));
+            assert!(filter.matches("> ERR boot\n"));
+            assert!(!filter.matches("ERR boot\n"));
+            assert!(!filter.matches("> ER"));
+        }
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+
+    run(&mut parser, ":filter col:0-5 e ERR");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+    run(&mut parser, ":filter col:3-5 len>10");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+}
+
+#[test]
+fn source_filter() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, ":filter src:#2 @net e heartbeat");
+    match rx.try_recv() {
+        Ok(Command::AddFilter(filter)) => {
+            assert_eq!(filter.kind, LogFilterType::Exclusion);
+            assert_eq!(filter.source.as_deref(), Some("#2"));
+            assert_eq!(filter.group.as_deref(), Some("net"));
+        }
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+
+    run(&mut parser, ":filter src: e heartbeat");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+    run(&mut parser, ":filter src:1 src:2 e heartbeat");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+}
+
+#[test]
+fn source_filter_round_trip() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, ":filter 'src:Main app (RTT)' @net off e heartbeat");
+    let filter = match rx.try_recv() {
+        Ok(Command::AddFilter(filter)) => filter,
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    };
+    assert_eq!(filter.source.as_deref(), Some("Main app (RTT)"));
+
+    let args = shell_words::split(&commander::filter_to_args(&filter)).unwrap();
+    let restored = commander::parse_filter(&args).unwrap();
+    assert_eq!(restored, filter);
+}
+
+#[test]
+fn length_filter_with_scope() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, ":filter lenx>200");
+    let filter = match rx.try_recv() {
+        Ok(Command::AddFilter(filter)) => filter,
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    };
+    assert_eq!(filter.kind, LogFilterType::LongerThan(200));
+    assert_eq!(filter.scope, LogFilterScope::Export);
+
+    let args = shell_words::split(&commander::filter_to_args(&filter)).unwrap();
+    let restored = commander::parse_filter(&args).unwrap();
+    assert_eq!(restored, filter);
+}
+
+#[test]
+fn ignore_case_filter() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, ":filter hi yellow error");
+    match rx.try_recv() {
+        Ok(Command::AddFilter(filter)) => {
+            assert_eq!(filter.kind, LogFilterType::Highlighter);
+            assert!(filter.ignore_case);
+            assert!(filter.matches("ERROR: disk full"));
+            assert!(filter.matches("Error: disk full"));
+        }
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+}
+
+#[test]
+fn regex_filter() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, r":filter hr red 'ERR\[\d+\]'");
+    match rx.try_recv() {
+        Ok(Command::AddFilter(filter)) => {
+            assert_eq!(filter.kind, LogFilterType::Highlighter);
+            assert!(filter.matches("ERR[42] overflow"));
+            assert!(!filter.matches("ERR[x] overflow"));
+        }
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+
+    run(&mut parser, ":filter eri 'heartbeat|keepalive'");
+    match rx.try_recv() {
+        Ok(Command::AddFilter(filter)) => assert!(filter.matches("KeepAlive sent")),
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+
+    run(&mut parser, ":filter er 'unclosed('");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+}
+
+#[test]
+fn filters_survive_yaml_round_trip() {
+    let (mut parser, rx) = new_parser();
+
+    let mut filters = Vec::new();
+    for text in [":filter hri red 'err\\d+'", ":filter @net ex wifi", ":filter after 10:00:00"] {
+        run(&mut parser, text);
+        match rx.try_recv() {
+            Ok(Command::AddFilter(filter)) => filters.push(filter),
+            other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+        }
+    }
+
+    let yaml = serde_yaml::to_string(&filters).unwrap();
+    let loaded: Vec<LogFilter> = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(loaded, filters);
+    assert!(loaded[0].matches("ERR42"));
+}
+
+#[test]
+fn unknown_command_sends_nothing() {
+    let (mut parser, rx) = new_parser();
+
+    run(&mut parser, ":does_not_exist foo");
+    assert!(parser.is_idle());
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn rtt_channel_command_takes_a_list() {
+    let (mut parser, rx) = new_parser();
+    parser.register_instruction(String::from(":rtt_channel"), commander::rtt_channel);
+
+    run(&mut parser, ":rtt_channel 0,2 board");
+    match rx.try_recv() {
+        Ok(Command::SetRttChannels(Some(target), channels)) => {
+            assert_eq!(target, "board");
+            assert_eq!(channels, vec![0, 2]);
+        }
+        other => panic!("Unexpected command {:?}", other.map(|c| c.to_string())),
+    }
+
+    run(&mut parser, ":rtt_channel 1,1");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+    run(&mut parser, ":rtt_channel 1,");
+    assert!(matches!(rx.try_recv(), Ok(Command::PrintError(_))));
+}
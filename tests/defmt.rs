@@ -0,0 +1,109 @@
+use defmt_decoder::Table;
+use uberlog_lib::commander::{MALFORMED_FRAME_MARKER, decode_defmt_frames};
+
+/// Table with a single `info!("t={=u32} h={=u8}")`, at index 257
+fn table(encoding: &str) -> Table {
+    let json = format!(
+        r#"{{
+            "timestamp": null,
+            "entries": {{"257": {{"string": {{"tag": "Info", "string": "t={{=u32}} h={{=u8}}"}}, "raw_symbol": "x"}}}},
+            "bitflags": {{}},
+            "encoding": "{}"
+        }}"#,
+        encoding
+    );
+    serde_json::from_str(&json).unwrap()
+}
+
+/// `t=21 h=5`, rzCOBS encoded and delimited
+const RZCOBS_FRAME: [u8; 6] = [1, 1, 21, 5, 0x38, 0];
+
+/// `t=21 h=5`, not encoded
+const RAW_FRAME: [u8; 7] = [1, 1, 21, 0, 0, 0, 5];
+
+#[test]
+fn rzcobs_frames() {
+    let table = table("Rzcobs");
+    let mut pending = [RZCOBS_FRAME, RZCOBS_FRAME].concat();
+
+    let lines = decode_defmt_frames(&table, &mut pending);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("t=21 h=5"), "{}", lines[0]);
+    assert_eq!(lines[0], lines[1]);
+    assert!(pending.is_empty());
+}
+
+#[test]
+fn rzcobs_frame_split_across_reads() {
+    let table = table("Rzcobs");
+    let mut pending = RZCOBS_FRAME[..3].to_vec();
+
+    assert!(decode_defmt_frames(&table, &mut pending).is_empty());
+    assert_eq!(pending, RZCOBS_FRAME[..3]);
+
+    pending.extend(&RZCOBS_FRAME[3..]);
+    let lines = decode_defmt_frames(&table, &mut pending);
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].ends_with("t=21 h=5"), "{}", lines[0]);
+    assert!(pending.is_empty());
+}
+
+#[test]
+fn rzcobs_malformed_frame_is_skipped() {
+    let table = table("Rzcobs");
+    let mut pending = [&[0xff, 0xff, 0][..], &RZCOBS_FRAME].concat();
+
+    let lines = decode_defmt_frames(&table, &mut pending);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], MALFORMED_FRAME_MARKER);
+    assert!(lines[1].ends_with("t=21 h=5"), "{}", lines[1]);
+}
+
+#[test]
+fn rzcobs_unknown_index() {
+    let table = table("Rzcobs");
+    // Index 514, not in the table
+    let mut pending = [&[2, 2, 21, 5, 0x38, 0][..], &RZCOBS_FRAME].concat();
+
+    let lines = decode_defmt_frames(&table, &mut pending);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], MALFORMED_FRAME_MARKER);
+    assert!(lines[1].ends_with("t=21 h=5"), "{}", lines[1]);
+}
+
+#[test]
+fn raw_frames() {
+    let table = table("Raw");
+    let mut pending = [RAW_FRAME, RAW_FRAME].concat();
+
+    let lines = decode_defmt_frames(&table, &mut pending);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("t=21 h=5"), "{}", lines[0]);
+    assert!(pending.is_empty());
+}
+
+#[test]
+fn raw_frame_split_across_reads() {
+    let table = table("Raw");
+    let mut pending = [&RAW_FRAME[..], &RAW_FRAME[..4]].concat();
+
+    assert_eq!(decode_defmt_frames(&table, &mut pending).len(), 1);
+    assert_eq!(pending, RAW_FRAME[..4]);
+
+    pending.extend(&RAW_FRAME[4..]);
+    assert_eq!(decode_defmt_frames(&table, &mut pending).len(), 1);
+    assert!(pending.is_empty());
+}
+
+#[test]
+fn raw_unknown_index_drops_the_rest() {
+    let table = table("Raw");
+    // Without delimiters, nothing after an unknown index can be decoded
+    let mut pending = [&RAW_FRAME[..], &[2, 2, 21, 0, 0, 0, 5], &RAW_FRAME].concat();
+
+    let lines = decode_defmt_frames(&table, &mut pending);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("t=21 h=5"), "{}", lines[0]);
+    assert_eq!(lines[1], MALFORMED_FRAME_MARKER);
+    assert!(pending.is_empty());
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use uberlog_lib::commander::{DiffKind, DiffRow, diff_lines, diff_lines_with_timeout};
+
+fn lines(text: &str) -> Vec<String> {
+    text.split_whitespace().map(String::from).collect()
+}
+
+fn kinds(rows: &[DiffRow]) -> Vec<DiffKind> {
+    rows.iter().map(|row| row.kind).collect()
+}
+
+/// Lines of each file, as shown by the rows
+fn sides(rows: &[DiffRow]) -> (Vec<String>, Vec<String>) {
+    let side = |pick: fn(&DiffRow) -> &Option<(usize, String)>| {
+        let shown: Vec<(usize, String)> = rows.iter().filter_map(|row| pick(row).clone()).collect();
+        for (pos, (number, _)) in shown.iter().enumerate() {
+            assert_eq!(*number, pos + 1, "lines out of order");
+        }
+        shown.into_iter().map(|(_, text)| text).collect()
+    };
+    (side(|row| &row.left), side(|row| &row.right))
+}
+
+#[test]
+fn identical_files() {
+    let a = lines("boot init ready");
+    let (rows, exact) = diff_lines(&a, &a);
+    assert!(exact);
+    assert_eq!(kinds(&rows), vec![DiffKind::Same; 3]);
+    assert_eq!(rows[1].left, Some((2, String::from("init"))));
+    assert_eq!(rows[1].right, Some((2, String::from("init"))));
+}
+
+#[test]
+fn pure_insertion() {
+    let (rows, exact) = diff_lines(&lines("boot ready"), &lines("boot init wifi ready"));
+    assert!(exact);
+    assert_eq!(
+        kinds(&rows),
+        vec![DiffKind::Same, DiffKind::Added, DiffKind::Added, DiffKind::Same]
+    );
+    assert_eq!(rows[1].left, None);
+    assert_eq!(rows[1].right, Some((2, String::from("init"))));
+    assert_eq!(rows[3].left, Some((2, String::from("ready"))));
+    assert_eq!(rows[3].right, Some((4, String::from("ready"))));
+}
+
+#[test]
+fn pure_deletion() {
+    let (rows, exact) = diff_lines(&lines("boot init wifi ready"), &lines("boot ready"));
+    assert!(exact);
+    assert_eq!(
+        kinds(&rows),
+        vec![DiffKind::Same, DiffKind::Removed, DiffKind::Removed, DiffKind::Same]
+    );
+    assert_eq!(rows[2].left, Some((3, String::from("wifi"))));
+    assert_eq!(rows[2].right, None);
+}
+
+#[test]
+fn changed_lines_are_paired() {
+    // Two lines replaced by three, the extra one is added
+    let (rows, exact) = diff_lines(&lines("boot t=1 t=2 ready"), &lines("boot t=5 t=6 t=7 ready"));
+    assert!(exact);
+    assert_eq!(
+        kinds(&rows),
+        vec![DiffKind::Same, DiffKind::Changed, DiffKind::Changed, DiffKind::Added, DiffKind::Same]
+    );
+    assert_eq!(rows[1].left, Some((2, String::from("t=1"))));
+    assert_eq!(rows[1].right, Some((2, String::from("t=5"))));
+    assert_eq!(rows[3].left, None);
+    assert_eq!(rows[3].right, Some((4, String::from("t=7"))));
+}
+
+#[test]
+fn common_start_and_end_are_kept() {
+    let a = lines("a b c x d e f");
+    let b = lines("a b c y d e f");
+    let (rows, exact) = diff_lines(&a, &b);
+    assert!(exact);
+    assert_eq!(rows.len(), 7);
+    assert!(rows[..3].iter().all(|row| row.kind == DiffKind::Same));
+    assert_eq!(rows[3].kind, DiffKind::Changed);
+    assert!(rows[4..].iter().all(|row| row.kind == DiffKind::Same));
+    assert_eq!(sides(&rows), (a, b));
+}
+
+#[test]
+fn empty_files() {
+    let (rows, exact) = diff_lines(&[], &lines("boot"));
+    assert!(exact);
+    assert_eq!(kinds(&rows), vec![DiffKind::Added]);
+    let (rows, _) = diff_lines(&[], &[]);
+    assert!(rows.is_empty());
+}
+
+#[test]
+fn timeout_still_shows_both_files() {
+    let a: Vec<String> = (0..300).map(|i| format!("line {}", i * 7 % 300)).collect();
+    let b: Vec<String> = (0..300).map(|i| format!("line {}", i * 11 % 300)).collect();
+    let (rows, exact) = diff_lines_with_timeout(&a, &b, Duration::ZERO);
+    assert!(!exact);
+    assert_eq!(sides(&rows), (a, b));
+}
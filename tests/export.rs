@@ -0,0 +1,55 @@
+use uberlog_lib::{
+    LogMessage, LogTimestamp,
+    commander::{ExportFormat, export_line},
+};
+
+fn log(time: &str, message: &str) -> LogMessage {
+    LogMessage {
+        timestamp: LogTimestamp::parse(time).unwrap(),
+        source_id: 3,
+        message: String::from(message),
+        style: LogMessage::default_style(),
+        highlighted: false,
+        focused: false,
+        raw: None,
+    }
+}
+
+#[test]
+fn export_raw() {
+    let line = export_line(&log("10:20:30", "boot done\r\n"), "Main app (RTT)", ExportFormat::Raw);
+    assert_eq!(line, "boot done\n");
+}
+
+#[test]
+fn export_syslog() {
+    let line = export_line(&log("10:20:30", "boot done\n"), "Main app (RTT)", ExportFormat::Syslog);
+
+    let fields: Vec<&str> = line.splitn(8, ' ').collect();
+    assert_eq!(fields[0], "<14>1");
+    assert!(fields[1].contains("T10:20:30.000"), "timestamp {}", fields[1]);
+    assert!(
+        fields[1].ends_with('Z') || fields[1][fields[1].len() - 6..].starts_with(['+', '-']),
+        "timestamp without offset {}",
+        fields[1]
+    );
+    assert_eq!(fields[2..7], ["-", "Main_app_(RTT)", "-", "-", "-"]);
+    assert_eq!(fields[7], "boot done\n");
+}
+
+#[test]
+fn export_syslog_without_source_name() {
+    let line = export_line(&log("10:20:30", "x"), "", ExportFormat::Syslog);
+    assert_eq!(line.split(' ').nth(3), Some("-"));
+}
+
+#[test]
+fn export_json() {
+    let line = export_line(&log("10:20:30", "say \"hi\"\n"), "Main app (RTT)", ExportFormat::Json);
+    assert!(line.ends_with('\n'));
+
+    let object: serde_json::Value = serde_json::from_str(line.trim_end()).unwrap();
+    assert_eq!(object["source"], "Main app (RTT)");
+    assert_eq!(object["message"], "say \"hi\"");
+    assert!(object["timestamp"].as_str().unwrap().ends_with(" 10:20:30.000"));
+}
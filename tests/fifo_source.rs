@@ -0,0 +1,89 @@
+#![cfg(unix)]
+
+use std::{
+    ffi::CString,
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    sync::mpsc::{Receiver, channel},
+    thread,
+    time::{Duration, Instant},
+};
+
+use uberlog_lib::{
+    commander::Command,
+    log_source::{FileSource, LogSourceTrait},
+};
+
+/// Named pipe in the temporary directory, unique to the test
+fn make_fifo(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("uberlog-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let c_path = CString::new(path.to_str().unwrap()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o644) }, 0, "mkfifo failed");
+    path
+}
+
+/// Write `text` into the pipe, opening it for the time of the write like a short lived writer
+fn write_fifo(path: &PathBuf, text: &str) {
+    let mut writer = OpenOptions::new().write(true).open(path).unwrap();
+    writer.write_all(text.as_bytes()).unwrap();
+}
+
+/// Collect what the source sent until `expected` was received, or some time passed
+fn receive(rx: &Receiver<Command>, expected: &str) -> String {
+    let mut received = String::new();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while received != expected && Instant::now() < deadline {
+        match rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(Command::ParseLogBytes(_, bytes)) => received.push_str(&String::from_utf8_lossy(&bytes)),
+            Ok(Command::SourceCompleted(_)) => panic!("the pipe source completed"),
+            _ => (),
+        }
+    }
+    received
+}
+
+#[test]
+fn fifo_source_outlives_its_writers() {
+    let path = make_fifo("writers");
+    let (tx, rx) = channel();
+    let mut source = FileSource::new(1, path.to_str().unwrap().to_string(), true, false, b'\n', tx);
+    source.connect();
+
+    write_fifo(&path, "one\ntwo\n");
+    assert_eq!(receive(&rx, "one\ntwo\n"), "one\ntwo\n");
+
+    // Nobody has the pipe open for writing meanwhile
+    thread::sleep(Duration::from_millis(500));
+    assert!(source.is_connected());
+
+    write_fifo(&path, "three\n");
+    assert_eq!(receive(&rx, "three\n"), "three\n");
+    assert!(source.is_connected());
+
+    source.disconnect();
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn fifo_source_waits_for_a_paused_writer() {
+    let path = make_fifo("paused");
+    let (tx, rx) = channel();
+    let mut source = FileSource::new(1, path.to_str().unwrap().to_string(), true, false, b'\n', tx);
+    source.connect();
+
+    // The writer keeps the pipe open while it has nothing to write
+    let mut writer = OpenOptions::new().write(true).open(&path).unwrap();
+    writer.write_all(b"before\n").unwrap();
+    assert_eq!(receive(&rx, "before\n"), "before\n");
+
+    thread::sleep(Duration::from_millis(500));
+    writer.write_all(b"after\n").unwrap();
+    assert_eq!(receive(&rx, "after\n"), "after\n");
+    assert!(source.is_connected());
+
+    drop(writer);
+    source.disconnect();
+    let _ = std::fs::remove_file(&path);
+}